@@ -0,0 +1,344 @@
+//allocator benchmarks: the manager against std::alloc::System on the same workloads
+//run with: cargo bench --bench allocator
+//the global heap is only 8 KiB, so every workload keeps a small live set,
+//failed allocations are counted instead of panicking, they show how fragmented the heap got,
+//ops/s and p99 only cover the successful operations, the failure rate is reported on its own
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::ptr::NonNull;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use memory_manager::arc::MyArc;
+use memory_manager::{compact, heap_stats, my_free, my_try_alloc, MyVec};
+
+const OPS: usize = 20_000;
+const LIVE: usize = 24;
+const THREADS: usize = 4;
+
+//the xorshift of the randomized tests, so the workloads are the same for both backends
+#[path = "../tests/support/rng.rs"]
+mod rng;
+
+use rng::Rng;
+
+trait Backend {
+    const NAME: &'static str;
+
+    fn alloc(&self, layout: Layout) -> Option<NonNull<u8>>;
+
+    unsafe fn free(&self, ptr: NonNull<u8>, layout: Layout);
+
+    //1 - largest free block / free bytes, None if the backend can't tell
+    fn fragmentation(&self) -> Option<f64>;
+}
+
+struct Manager;
+
+impl Backend for Manager {
+    const NAME: &'static str = "manager";
+
+    fn alloc(&self, layout: Layout) -> Option<NonNull<u8>> {
+        NonNull::new(my_try_alloc(layout.size(), layout.align()))
+    }
+
+    unsafe fn free(&self, ptr: NonNull<u8>, _layout: Layout) {
+        unsafe {
+            my_free(ptr.as_ptr());
+        }
+    }
+
+    fn fragmentation(&self) -> Option<f64> {
+        let stats = heap_stats();
+        if stats.free_bytes == 0 {
+            return Some(0.0);
+        }
+        Some(1.0 - stats.largest_free as f64 / stats.free_bytes as f64)
+    }
+}
+
+struct Sys;
+
+impl Backend for Sys {
+    const NAME: &'static str = "system";
+
+    fn alloc(&self, layout: Layout) -> Option<NonNull<u8>> {
+        unsafe {
+            NonNull::new(System.alloc(layout))
+        }
+    }
+
+    unsafe fn free(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe {
+            System.dealloc(ptr.as_ptr(), layout);
+        }
+    }
+
+    fn fragmentation(&self) -> Option<f64> {
+        None
+    }
+}
+
+//per operation latencies of the successful operations of one run
+struct Report {
+    total: Duration,
+    latencies: Vec<u64>,
+    failures: usize,
+    fragmentation: Option<f64>,
+}
+
+impl Report {
+    fn new() -> Report {
+        Report { total: Duration::ZERO, latencies: Vec::with_capacity(OPS * 2), failures: 0, fragmentation: None }
+    }
+
+    //times an operation which can't fail
+    fn time<R>(&mut self, f: impl FnOnce() -> R) -> R {
+        let start = Instant::now();
+        let out = f();
+        self.record(start.elapsed());
+        out
+    }
+
+    //times an operation which can fail, a failure is only counted, its time isn't part of the report
+    fn try_time<R>(&mut self, f: impl FnOnce() -> Option<R>) -> Option<R> {
+        let start = Instant::now();
+        let out = f();
+        let elapsed = start.elapsed();
+        match out {
+            Some(_) => self.record(elapsed),
+            None => self.failures += 1,
+        }
+        out
+    }
+
+    fn record(&mut self, elapsed: Duration) {
+        self.total += elapsed;
+        self.latencies.push(elapsed.as_nanos() as u64);
+    }
+
+    fn print(mut self, workload: &str, backend: &str) {
+        self.latencies.sort_unstable();
+        let ops = self.latencies.len();
+        let p99 = self.latencies.get(ops * 99 / 100).copied().unwrap_or(0);
+        let ops_per_sec = ops as f64 / self.total.as_secs_f64().max(f64::EPSILON);
+        let failure_rate = self.failures as f64 / (ops + self.failures).max(1) as f64;
+        let fragmentation = match self.fragmentation {
+            Some(f) => format!("{:.1}%", f * 100.0),
+            None => "n/a".to_string(),
+        };
+
+        println!(
+            "{:<14} {:<8} {:>14.0} ops/s   p99 {:>7} ns   failed {:>6} ({:>5.1}%)   fragmentation {:>6}",
+            workload, backend, ops_per_sec, p99, self.failures, failure_rate * 100.0, fragmentation
+        );
+    }
+}
+
+//allocates and frees a stack of blocks, the last allocated is freed first
+fn lifo<B: Backend>(backend: &B) -> Report {
+    let mut report = Report::new();
+    let layout = Layout::from_size_align(48, 8).unwrap();
+    let mut live = Vec::with_capacity(LIVE);
+
+    for _ in 0..OPS / LIVE {
+        for _ in 0..LIVE {
+            if let Some(ptr) = report.try_time(|| backend.alloc(layout)) {
+                live.push(ptr);
+            }
+        }
+        while let Some(ptr) = live.pop() {
+            report.time(|| unsafe { backend.free(ptr, layout) });
+        }
+    }
+
+    report.fragmentation = backend.fragmentation();
+    report
+}
+
+//the first allocated block is freed first
+fn fifo<B: Backend>(backend: &B) -> Report {
+    let mut report = Report::new();
+    let layout = Layout::from_size_align(48, 8).unwrap();
+    let mut live = std::collections::VecDeque::with_capacity(LIVE);
+
+    for _ in 0..OPS {
+        if let Some(ptr) = report.try_time(|| backend.alloc(layout)) {
+            live.push_back(ptr);
+        }
+
+        if live.len() == LIVE {
+            let ptr = live.pop_front().unwrap();
+            report.time(|| unsafe { backend.free(ptr, layout) });
+        }
+    }
+    for ptr in live {
+        unsafe { backend.free(ptr, layout) };
+    }
+
+    report.fragmentation = backend.fragmentation();
+    report
+}
+
+//random sizes and alignments, a random live block is freed on every step
+fn random_sizes<B: Backend>(backend: &B) -> Report {
+    let mut report = Report::new();
+    let mut rng = Rng(0x5EED);
+    let mut live: Vec<(NonNull<u8>, Layout)> = Vec::with_capacity(LIVE);
+
+    for _ in 0..OPS {
+        let layout = Layout::from_size_align(1 + rng.below(128), 1 << rng.below(5)).unwrap();
+        let ptr = report.try_time(|| backend.alloc(layout));
+        if let Some(ptr) = ptr {
+            live.push((ptr, layout));
+        }
+
+        if live.len() == LIVE || (ptr.is_none() && !live.is_empty()) {
+            let (ptr, layout) = live.swap_remove(rng.below(live.len()));
+            report.time(|| unsafe { backend.free(ptr, layout) });
+        }
+    }
+
+    report.fragmentation = backend.fragmentation();
+    for (ptr, layout) in live {
+        unsafe { backend.free(ptr, layout) };
+    }
+    report
+}
+
+//many small long lived blocks interleaved with short lived ones, than the short lived ones are freed
+//and bigger blocks are requested, which only fit if the free blocks are big enough
+fn fragmentation<B: Backend>(backend: &B) -> Report {
+    let mut report = Report::new();
+    let small = Layout::from_size_align(16, 8).unwrap();
+    let big = Layout::from_size_align(256, 8).unwrap();
+
+    for _ in 0..OPS / (LIVE * 3) {
+        let mut pinned = Vec::with_capacity(LIVE);
+        let mut temporary = Vec::with_capacity(LIVE);
+        for _ in 0..LIVE {
+            if let Some(ptr) = report.try_time(|| backend.alloc(small)) {
+                pinned.push(ptr);
+            }
+            if let Some(ptr) = report.try_time(|| backend.alloc(small)) {
+                temporary.push(ptr);
+            }
+        }
+        for ptr in temporary {
+            report.time(|| unsafe { backend.free(ptr, small) });
+        }
+
+        if let Some(ptr) = report.try_time(|| backend.alloc(big)) {
+            unsafe { backend.free(ptr, big) };
+        }
+
+        report.fragmentation = backend.fragmentation();
+        for ptr in pinned {
+            unsafe { backend.free(ptr, small) };
+        }
+    }
+
+    report
+}
+
+//pushes into a fresh vec until it holds 64 values, every reallocation is part of a push
+//the manager doesn't merge freed buffers, so a vec which can't grow anymore counts as a failure
+fn vec_growth_manager() -> Report {
+    let mut report = Report::new();
+    for _ in 0..OPS / 64 {
+        let mut v = MyVec::new();
+        for i in 0..64u32 {
+            let grown = report.try_time(|| v.try_reserve(1).ok().map(|_| v.push(black_box(i))));
+            if grown.is_none() {
+                break;
+            }
+        }
+    }
+    report.fragmentation = Manager.fragmentation();
+    report
+}
+
+fn vec_growth_system() -> Report {
+    let mut report = Report::new();
+    for _ in 0..OPS / 64 {
+        let mut v = Vec::new();
+        for i in 0..64u32 {
+            report.time(|| v.push(black_box(i)));
+        }
+    }
+    report
+}
+
+//every thread creates, clones and drops arcs, so all of them fight for the allocator
+fn arc_churn_manager() -> Report {
+    let handles: Vec<_> = (0..THREADS).map(|t| thread::spawn(move || {
+        let mut report = Report::new();
+        for i in 0..OPS / THREADS {
+            let arc = report.time(|| MyArc::new([t, i]));
+            let clone = arc.clone();
+            drop(arc);
+            report.time(|| drop(black_box(clone)));
+        }
+        report
+    })).collect();
+
+    let reports: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+    merge(reports, Manager.fragmentation())
+}
+
+fn arc_churn_system() -> Report {
+    let handles: Vec<_> = (0..THREADS).map(|t| thread::spawn(move || {
+        let mut report = Report::new();
+        for i in 0..OPS / THREADS {
+            let arc = report.time(|| Arc::new([t, i]));
+            let clone = arc.clone();
+            drop(arc);
+            report.time(|| drop(black_box(clone)));
+        }
+        report
+    })).collect();
+
+    let reports: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+    merge(reports, None)
+}
+
+//the threads run at the same time, so the wall clock time is the longest thread's time
+fn merge(reports: Vec<Report>, fragmentation: Option<f64>) -> Report {
+    let mut out = Report::new();
+    for report in reports {
+        out.total = out.total.max(report.total);
+        out.latencies.extend(report.latencies);
+        out.failures += report.failures;
+    }
+    out.fragmentation = fragmentation;
+    out
+}
+
+fn run<B: Backend>(name: &str, backend: &B, workload: fn(&B) -> Report) {
+    workload(backend).print(name, B::NAME);
+    //merge the free blocks, so every workload starts from the same heap
+    compact();
+}
+
+fn main() {
+    println!("{} ops per workload, {} live blocks, {} threads\n", OPS, LIVE, THREADS);
+
+    run("lifo", &Manager, lifo);
+    run("lifo", &Sys, lifo);
+    run("fifo", &Manager, fifo);
+    run("fifo", &Sys, fifo);
+    run("random sizes", &Manager, random_sizes);
+    run("random sizes", &Sys, random_sizes);
+    run("fragmentation", &Manager, fragmentation);
+    run("fragmentation", &Sys, fragmentation);
+
+    vec_growth_manager().print("vec growth", Manager::NAME);
+    compact();
+    vec_growth_system().print("vec growth", Sys::NAME);
+
+    arc_churn_manager().print("arc churn", Manager::NAME);
+    compact();
+    arc_churn_system().print("arc churn", Sys::NAME);
+}
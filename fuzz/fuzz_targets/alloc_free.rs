@@ -0,0 +1,22 @@
+//run with: cargo +nightly fuzz run alloc_free
+//every 3 bytes of the input are an alloc, a free or a shrink, see tests/support/heap_ops.rs
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+//live() is only used by the randomized test
+#[allow(dead_code)]
+#[path = "../../tests/support/heap_ops.rs"]
+mod heap_ops;
+#[path = "../../tests/support/serial.rs"]
+mod serial;
+
+use heap_ops::{Model, Op};
+
+fuzz_target!(|data: &[u8]| {
+    let mut model = Model::new();
+    for op in Op::decode(data) {
+        model.apply(op);
+    }
+    model.finish();
+});
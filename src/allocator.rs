@@ -0,0 +1,144 @@
+use core::{alloc::Layout, fmt::Display, ptr::{self, NonNull}};
+
+use crate::manager::{my_free, my_shrink, my_try_alloc, my_usable_size};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocError;
+
+impl Display for AllocError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "unable to allocate, not enough free space")
+    }
+}
+
+/// # Safety
+/// a block returned by allocate/grow/shrink must stay valid until it is passed to deallocate/grow/shrink
+/// and it must be at least usable_size bytes with layout.align() alignment,
+/// the block may be passed back with any layout of the same alignment whose size is between
+/// the requested size and its usable_size,
+/// moving or cloning the allocator must not invalidate its blocks and a clone must behave like the
+/// same allocator, a block from one of them can be passed to any other (MyRc and MyArc free through clones)
+pub unsafe trait MyAllocator {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError>;
+
+    /// # Safety
+    /// ptr must have been allocated by this allocator with the given layout
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+
+    /// how many bytes of the block can actually be used, at least layout.size(),
+    /// an allocator which accounts for layout.size() (e.g. Budget) should keep this default
+    ///
+    /// # Safety
+    /// ptr must have been allocated by this allocator with the given layout
+    unsafe fn usable_size(&self, _ptr: NonNull<u8>, layout: Layout) -> usize {
+        layout.size()
+    }
+
+    /// # Safety
+    /// ptr must have been allocated by this allocator with old_layout,
+    /// new_layout.size() must be >= old_layout.size()
+    unsafe fn grow(&self, ptr: NonNull<u8>, old_layout: Layout, new_layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        debug_assert!(new_layout.size() >= old_layout.size());
+
+        let new_ptr = self.allocate(new_layout)?;
+        unsafe {
+            ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), old_layout.size());
+            self.deallocate(ptr, old_layout);
+        }
+        Ok(new_ptr)
+    }
+
+    /// # Safety
+    /// ptr must have been allocated by this allocator with old_layout,
+    /// new_layout.size() must be <= old_layout.size()
+    unsafe fn shrink(&self, ptr: NonNull<u8>, old_layout: Layout, new_layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        debug_assert!(new_layout.size() <= old_layout.size());
+
+        let new_ptr = self.allocate(new_layout)?;
+        unsafe {
+            ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), new_layout.size());
+            self.deallocate(ptr, old_layout);
+        }
+        Ok(new_ptr)
+    }
+}
+
+//the default allocator: the global manager behind my_alloc and my_free
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GlobalHeap;
+
+unsafe impl MyAllocator for GlobalHeap {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        NonNull::new(my_try_alloc(layout.size(), layout.align())).ok_or(AllocError)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, _layout: Layout) {
+        unsafe {
+            my_free(ptr.as_ptr());
+        }
+    }
+
+    unsafe fn usable_size(&self, ptr: NonNull<u8>, _layout: Layout) -> usize {
+        unsafe {
+            my_usable_size(ptr.as_ptr())
+        }
+    }
+
+    //the block stays where it is, its tail is given back to the heap
+    unsafe fn shrink(&self, ptr: NonNull<u8>, old_layout: Layout, new_layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        debug_assert!(new_layout.size() <= old_layout.size());
+
+        if (ptr.as_ptr() as usize).is_multiple_of(new_layout.align()) {
+            unsafe {
+                my_shrink(ptr.as_ptr(), new_layout.size());
+            }
+            return Ok(ptr);
+        }
+
+        let new_ptr = self.allocate(new_layout)?;
+        unsafe {
+            ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), new_layout.size());
+            self.deallocate(ptr, old_layout);
+        }
+        Ok(new_ptr)
+    }
+}
+
+//lets a single allocator be shared between many containers
+unsafe impl<A: MyAllocator + ?Sized> MyAllocator for &A {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        (**self).allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe {
+            (**self).deallocate(ptr, layout)
+        }
+    }
+
+    unsafe fn usable_size(&self, ptr: NonNull<u8>, layout: Layout) -> usize {
+        unsafe {
+            (**self).usable_size(ptr, layout)
+        }
+    }
+
+    unsafe fn grow(&self, ptr: NonNull<u8>, old_layout: Layout, new_layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        unsafe {
+            (**self).grow(ptr, old_layout, new_layout)
+        }
+    }
+
+    unsafe fn shrink(&self, ptr: NonNull<u8>, old_layout: Layout, new_layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        unsafe {
+            (**self).shrink(ptr, old_layout, new_layout)
+        }
+    }
+}
+
+//used by the infallible container apis, mirrors the panic of my_alloc
+pub(crate) fn allocate_or_panic<A: MyAllocator + ?Sized>(alloc: &A, layout: Layout) -> *mut u8 {
+    match alloc.allocate(layout) {
+        Ok(ptr) => ptr.as_ptr(),
+        Err(e) => panic!("{}", e),
+    }
+}
@@ -0,0 +1,117 @@
+use core::{alloc::Layout, ptr::NonNull, sync::atomic::{AtomicUsize, Ordering}};
+
+use crate::allocator::{AllocError, GlobalHeap, MyAllocator};
+
+//what happens with an allocation that would go over the limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetMode {
+    //the allocation fails
+    Reject,
+    //the allocation succeeds, but it is counted in BudgetStats::over_limit
+    Report,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BudgetStats {
+    pub current: usize,
+    pub peak: usize,
+    pub limit: usize,
+    pub over_limit: usize,
+}
+
+//a named memory budget for one subsystem, pass &budget as the allocator of its containers
+pub struct Budget<A: MyAllocator = GlobalHeap> {
+    name: &'static str,
+    limit: usize,
+    mode: BudgetMode,
+    current: AtomicUsize,
+    peak: AtomicUsize,
+    over_limit: AtomicUsize,
+    inner: A,
+}
+
+impl Budget {
+    pub fn new(name: &'static str, limit: usize, mode: BudgetMode) -> Budget {
+        Budget::with_inner(name, limit, mode, GlobalHeap)
+    }
+}
+
+impl<A: MyAllocator> Budget<A> {
+    pub fn with_inner(name: &'static str, limit: usize, mode: BudgetMode, inner: A) -> Budget<A> {
+        Budget {
+            name,
+            limit,
+            mode,
+            current: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+            over_limit: AtomicUsize::new(0),
+            inner,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn current(&self) -> usize {
+        self.current.load(Ordering::Relaxed)
+    }
+
+    pub fn peak(&self) -> usize {
+        self.peak.load(Ordering::Relaxed)
+    }
+
+    pub fn stats(&self) -> BudgetStats {
+        BudgetStats {
+            current: self.current(),
+            peak: self.peak(),
+            limit: self.limit,
+            over_limit: self.over_limit.load(Ordering::Relaxed),
+        }
+    }
+
+    //reserves size bytes from the budget, fails only in Reject mode
+    fn charge(&self, size: usize) -> Result<(), AllocError> {
+        let mut current = self.current.load(Ordering::Relaxed);
+
+        loop {
+            let new = current.checked_add(size).ok_or(AllocError)?;
+            let over = new > self.limit;
+            if over && self.mode == BudgetMode::Reject {
+                self.over_limit.fetch_add(1, Ordering::Relaxed);
+                return Err(AllocError);
+            }
+
+            match self.current.compare_exchange_weak(current, new, Ordering::Relaxed, Ordering::Relaxed) {
+                Ok(_) => {
+                    if over {
+                        self.over_limit.fetch_add(1, Ordering::Relaxed);
+                    }
+                    self.peak.fetch_max(new, Ordering::Relaxed);
+                    return Ok(());
+                }
+                Err(prev) => current = prev,
+            }
+        }
+    }
+}
+
+//usable_size is left at the default, so the containers never use more than what was charged
+unsafe impl<A: MyAllocator> MyAllocator for Budget<A> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        self.charge(layout.size())?;
+
+        let result = self.inner.allocate(layout);
+        if result.is_err() {
+            self.current.fetch_sub(layout.size(), Ordering::Relaxed);
+        }
+        result
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe {
+            self.inner.deallocate(ptr, layout);
+        }
+        self.current.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
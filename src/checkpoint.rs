@@ -0,0 +1,106 @@
+use core::fmt::{self, Display};
+
+use crate::large::PAGES;
+use crate::manager::{checkpoint_blocks, diff_blocks, FREE_MAP_LEN};
+use crate::tag::Tag;
+
+//the first this many new blocks are kept in a diff, so making one doesn't allocate
+const MAX_REPORTED: usize = 16;
+pub(crate) const LARGE_MAP_LEN: usize = PAGES.div_ceil(64);
+
+//heap: a bit for every word of the heap, set if an allocated block starts there
+//large: a bit for every page of the large region, set if a run starts there
+//count, bytes: live allocations and their bytes, the same as the sum of usage_by_tag
+#[derive(Clone, Copy)]
+pub(crate) struct LiveBlocks {
+    pub(crate) heap: [u64; FREE_MAP_LEN],
+    pub(crate) large: [u64; LARGE_MAP_LEN],
+    pub(crate) count: usize,
+    pub(crate) bytes: usize,
+}
+
+//an allocation which is alive now, but wasn't at the checkpoint
+//ptr: the first byte of the user data, size: the bytes of the block (including header and padding)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Leak {
+    pub ptr: *mut u8,
+    pub size: usize,
+    pub tag: Tag,
+}
+
+//the state of the global heap at some point, so a test can check that a piece of code frees everything it allocates
+//note: every thread allocates from the same heap, so the allocations of other threads show up in the diff too
+pub struct HeapCheckpoint {
+    blocks: LiveBlocks,
+}
+
+impl HeapCheckpoint {
+    pub fn new() -> HeapCheckpoint {
+        HeapCheckpoint { blocks: checkpoint_blocks() }
+    }
+
+    pub fn diff(&self) -> HeapDiff {
+        diff_blocks(&self.blocks)
+    }
+
+    #[track_caller]
+    pub fn assert_no_leaks(&self) {
+        let diff = self.diff();
+        if !diff.is_clean() {
+            panic!("{}", diff);
+        }
+    }
+}
+
+impl Default for HeapCheckpoint {
+    fn default() -> Self {
+        HeapCheckpoint::new()
+    }
+}
+
+//allocations, bytes: the change of the live allocations and bytes since the checkpoint
+//new_blocks: the number of blocks alive now which weren't at the checkpoint (a block at the address
+//of one which was alive at the checkpoint is not counted, but it still changes allocations and bytes)
+#[derive(Debug, Clone, Copy)]
+pub struct HeapDiff {
+    pub allocations: isize,
+    pub bytes: isize,
+    pub new_blocks: usize,
+    leaks: [Leak; MAX_REPORTED],
+}
+
+impl HeapDiff {
+    pub(crate) fn new(allocations: isize, bytes: isize) -> HeapDiff {
+        let empty = Leak { ptr: core::ptr::null_mut(), size: 0, tag: Tag::UNTAGGED };
+        HeapDiff { allocations, bytes, new_blocks: 0, leaks: [empty; MAX_REPORTED] }
+    }
+
+    pub(crate) fn record(&mut self, leak: Leak) {
+        if self.new_blocks < MAX_REPORTED {
+            self.leaks[self.new_blocks] = leak;
+        }
+        self.new_blocks += 1;
+    }
+
+    //the first new blocks in address order (the heap first, than the large region)
+    pub fn leaks(&self) -> &[Leak] {
+        &self.leaks[..self.new_blocks.min(MAX_REPORTED)]
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.new_blocks == 0 && self.allocations <= 0 && self.bytes <= 0
+    }
+}
+
+impl Display for HeapDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:+} allocations, {:+} bytes since the checkpoint, {} new blocks alive", self.allocations, self.bytes, self.new_blocks)?;
+        for leak in self.leaks() {
+            write!(f, "\n  {:p}: {} bytes, tag {}", leak.ptr, leak.size, leak.tag.id())?;
+        }
+        if self.new_blocks > MAX_REPORTED {
+            write!(f, "\n  and {} more", self.new_blocks - MAX_REPORTED)?;
+        }
+        Ok(())
+    }
+}
@@ -1,2 +1,11 @@
 pub mod vec;
-pub mod string;
\ No newline at end of file
+pub mod vec_deque;
+pub mod hash_map;
+pub mod hash_set;
+pub mod btree_map;
+pub mod btree_set;
+pub mod binary_heap;
+pub mod linked_list;
+pub mod string;
+#[cfg(feature = "std")]
+pub mod shared_vec;
\ No newline at end of file
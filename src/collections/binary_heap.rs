@@ -0,0 +1,379 @@
+use core::{fmt::Debug, mem::{self, ManuallyDrop}, ops::{Deref, DerefMut}, ptr};
+
+use crate::allocator::{AllocError, GlobalHeap, MyAllocator};
+use super::vec::{MyDrain, MyVec, MyVecIntoIter, MyVecIter};
+
+/*
+    MyBinaryHeap layout:
+    a max-heap in a MyVec, the children of index i are 2i + 1 and 2i + 2 and neither is greater than i
+    for a min-heap wrap the values in core::cmp::Reverse, e.g. MyBinaryHeap<Reverse<(u32, Task)>> pops the lowest priority first
+ */
+pub struct MyBinaryHeap<T, A: MyAllocator = GlobalHeap> {
+    data: MyVec<T, A>,
+}
+
+//constructors on the global heap
+impl<T: Ord> MyBinaryHeap<T> {
+    pub fn new() -> MyBinaryHeap<T> {
+        MyBinaryHeap { data: MyVec::new() }
+    }
+
+    pub fn with_capacity(capacity: usize) -> MyBinaryHeap<T> {
+        MyBinaryHeap { data: MyVec::with_capacity(capacity) }
+    }
+}
+
+//constructors, getters
+impl<T, A: MyAllocator> MyBinaryHeap<T, A> {
+    pub fn new_in(alloc: A) -> MyBinaryHeap<T, A> {
+        MyBinaryHeap { data: MyVec::new_in(alloc) }
+    }
+
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> MyBinaryHeap<T, A> {
+        MyBinaryHeap { data: MyVec::with_capacity_in(capacity, alloc) }
+    }
+
+    pub fn allocator(&self) -> &A {
+        self.data.allocator()
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.data.capacity()
+    }
+
+    //the greatest value
+    pub fn peek(&self) -> Option<&T> {
+        self.data.first()
+    }
+
+    //the values in heap order
+    pub fn as_slice(&self) -> &[T] {
+        self.data.as_slice()
+    }
+
+    pub fn iter(&self) -> MyVecIter<'_, T, A> {
+        self.data.iter()
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.data.reserve(additional);
+    }
+
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        self.data.try_reserve(additional)
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.data.shrink_to_fit();
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+
+    //takes every value out in heap order, the heap is empty afterwards
+    pub fn drain(&mut self) -> MyDrain<'_, T, A> {
+        self.data.drain(..)
+    }
+
+    //the values in heap order, without sorting them
+    pub fn into_vec(self) -> MyVec<T, A> {
+        self.data
+    }
+}
+
+impl<T: Ord, A: MyAllocator + Default> Default for MyBinaryHeap<T, A> {
+    fn default() -> Self {
+        MyBinaryHeap::new_in(A::default())
+    }
+}
+
+//adding and removing values
+impl<T: Ord, A: MyAllocator> MyBinaryHeap<T, A> {
+    pub fn push(&mut self, value: T) {
+        let old_len = self.len();
+        self.data.push(value);
+        unsafe {
+            self.sift_up(0, old_len);
+        }
+    }
+
+    //removes the greatest value
+    pub fn pop(&mut self) -> Option<T> {
+        self.data.pop().map(|mut item| {
+            if !self.is_empty() {
+                mem::swap(&mut item, &mut self.data[0]);
+                unsafe {
+                    self.sift_down(0, self.len());
+                }
+            }
+            item
+        })
+    }
+
+    //a mutable reference to the greatest value, the heap is fixed up when it's dropped
+    pub fn peek_mut(&mut self) -> Option<MyPeekMut<'_, T, A>> {
+        if self.is_empty() {
+            return None;
+        }
+
+        //only the top is visible while the guard lives, so a leaked guard can't leave the heap out of order
+        let original_len = self.len();
+        unsafe {
+            self.data.set_len(1);
+        }
+        Some(MyPeekMut { heap: self, original_len })
+    }
+
+    //moves every value of other into self
+    pub fn append(&mut self, other: &mut MyBinaryHeap<T, A>) {
+        self.data.reserve(other.len());
+        for value in other.data.drain(..) {
+            self.push(value);
+        }
+    }
+
+    //keeps the values for which f returns true
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, f: F) {
+        self.data.retain(f);
+        self.rebuild();
+    }
+
+    //the values in ascending order, O(n log n)
+    pub fn into_sorted_vec(mut self) -> MyVec<T, A> {
+        let mut end = self.len();
+        while end > 1 {
+            end -= 1;
+            self.data.swap(0, end);
+            unsafe {
+                self.sift_down(0, end);
+            }
+        }
+        self.into_vec()
+    }
+
+    //restores the heap order of the whole vec bottom up, O(n)
+    fn rebuild(&mut self) {
+        let mut n = self.len() / 2;
+        while n > 0 {
+            n -= 1;
+            unsafe {
+                self.sift_down(n, self.len());
+            }
+        }
+    }
+
+    //moves the value at pos up, but not above start
+    //SAFETY: start <= pos < self.len()
+    unsafe fn sift_up(&mut self, start: usize, pos: usize) {
+        unsafe {
+            let mut hole = Hole::new(&mut self.data, pos);
+            while hole.pos > start {
+                let parent = (hole.pos - 1) / 2;
+                if hole.element() <= hole.get(parent) {
+                    break;
+                }
+                hole.move_to(parent);
+            }
+        }
+    }
+
+    //moves the value at pos down, while its greatest child in 0..end is greater
+    //SAFETY: pos < end <= self.len()
+    unsafe fn sift_down(&mut self, pos: usize, end: usize) {
+        unsafe {
+            let mut hole = Hole::new(&mut self.data, pos);
+            let mut child = 2 * hole.pos + 1;
+            while child < end {
+                if child + 1 < end && hole.get(child) <= hole.get(child + 1) {
+                    child += 1;
+                }
+                if hole.element() >= hole.get(child) {
+                    return;
+                }
+                hole.move_to(child);
+                child = 2 * hole.pos + 1;
+            }
+        }
+    }
+}
+
+//a value taken out of the slice while it's sifted, the other values are moved over the hole it left,
+//the value is written back into the hole when this is dropped, also if a comparison panics
+struct Hole<'a, T> {
+    data: &'a mut [T],
+    element: ManuallyDrop<T>,
+    pos: usize,
+}
+
+impl<'a, T> Hole<'a, T> {
+    //SAFETY: pos < data.len()
+    unsafe fn new(data: &'a mut [T], pos: usize) -> Hole<'a, T> {
+        let element = unsafe {
+            ptr::read(data.get_unchecked(pos))
+        };
+        Hole { data, element: ManuallyDrop::new(element), pos }
+    }
+
+    fn element(&self) -> &T {
+        &self.element
+    }
+
+    //SAFETY: index < data.len() and index != pos
+    unsafe fn get(&self, index: usize) -> &T {
+        unsafe {
+            self.data.get_unchecked(index)
+        }
+    }
+
+    //moves the value at index into the hole, the hole is at index afterwards
+    //SAFETY: index < data.len() and index != pos
+    unsafe fn move_to(&mut self, index: usize) {
+        unsafe {
+            let ptr = self.data.as_mut_ptr();
+            ptr::copy_nonoverlapping(ptr.add(index), ptr.add(self.pos), 1);
+        }
+        self.pos = index;
+    }
+}
+
+impl<'a, T> Drop for Hole<'a, T> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::copy_nonoverlapping(&*self.element, self.data.as_mut_ptr().add(self.pos), 1);
+        }
+    }
+}
+
+//PEEK
+pub struct MyPeekMut<'a, T: Ord, A: MyAllocator = GlobalHeap> {
+    heap: &'a mut MyBinaryHeap<T, A>,
+    original_len: usize,
+}
+
+impl<'a, T: Ord, A: MyAllocator> MyPeekMut<'a, T, A> {
+    //removes the peeked value
+    pub fn pop(this: MyPeekMut<'a, T, A>) -> T {
+        let mut this = ManuallyDrop::new(this);
+        let original_len = this.original_len;
+        unsafe {
+            this.heap.data.set_len(original_len);
+        }
+        this.heap.pop().unwrap()
+    }
+}
+
+impl<'a, T: Ord, A: MyAllocator> Deref for MyPeekMut<'a, T, A> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.heap.data[0]
+    }
+}
+
+impl<'a, T: Ord, A: MyAllocator> DerefMut for MyPeekMut<'a, T, A> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.heap.data[0]
+    }
+}
+
+impl<'a, T: Ord, A: MyAllocator> Drop for MyPeekMut<'a, T, A> {
+    fn drop(&mut self) {
+        unsafe {
+            self.heap.data.set_len(self.original_len);
+            self.heap.sift_down(0, self.original_len);
+        }
+    }
+}
+
+impl<'a, T: Ord + Debug, A: MyAllocator> Debug for MyPeekMut<'a, T, A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("MyPeekMut").field(&**self).finish()
+    }
+}
+
+//conversions
+
+//heapifies the values in place, O(n)
+impl<T: Ord, A: MyAllocator> From<MyVec<T, A>> for MyBinaryHeap<T, A> {
+    fn from(vec: MyVec<T, A>) -> Self {
+        let mut heap = MyBinaryHeap { data: vec };
+        heap.rebuild();
+        heap
+    }
+}
+
+impl<T: Ord, const N: usize> From<[T; N]> for MyBinaryHeap<T> {
+    fn from(values: [T; N]) -> Self {
+        MyBinaryHeap::from(MyVec::from(values))
+    }
+}
+
+impl<T, A: MyAllocator> From<MyBinaryHeap<T, A>> for MyVec<T, A> {
+    fn from(heap: MyBinaryHeap<T, A>) -> Self {
+        heap.data
+    }
+}
+
+impl<T: Ord, A: MyAllocator + Default> FromIterator<T> for MyBinaryHeap<T, A> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        MyBinaryHeap::from(iter.into_iter().collect::<MyVec<T, A>>())
+    }
+}
+
+impl<T: Ord, A: MyAllocator> Extend<T> for MyBinaryHeap<T, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<'a, T: Ord + Copy + 'a, A: MyAllocator> Extend<&'a T> for MyBinaryHeap<T, A> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+//iterator implementations, both in heap order
+impl<T, A: MyAllocator> IntoIterator for MyBinaryHeap<T, A> {
+    type Item = T;
+
+    type IntoIter = MyVecIntoIter<T, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.into_iter()
+    }
+}
+
+impl<'a, T, A: MyAllocator> IntoIterator for &'a MyBinaryHeap<T, A> {
+    type Item = &'a T;
+
+    type IntoIter = MyVecIter<'a, T, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: Debug, A: MyAllocator> Debug for MyBinaryHeap<T, A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Clone, A: MyAllocator + Clone> Clone for MyBinaryHeap<T, A> {
+    fn clone(&self) -> Self {
+        MyBinaryHeap { data: self.data.clone() }
+    }
+}
@@ -0,0 +1,1178 @@
+use core::{alloc::Layout, borrow::Borrow, cmp::Ordering, fmt::Debug, iter::FusedIterator, marker::PhantomData, mem::{self, MaybeUninit}, ops::{Bound, Index, RangeBounds, RangeInclusive}, ptr::{self, NonNull}};
+
+use crate::allocator::{allocate_or_panic, GlobalHeap, MyAllocator};
+
+/*
+    B-tree layout:
+    every node is one allocation from A, a leaf holds up to CAPACITY keys and values,
+    an internal node is a leaf followed by len + 1 edges to its children (repr(C), so a pointer to it is a pointer to its leaf part)
+    the keys of edge i lie between key i - 1 and key i, all leaves are at height 0, the root is at self.height
+    every node but the root has at least MIN_LEN keys, children point back at their parent, which is what the iterators walk up on
+    leaves are allocated with the size of internal nodes too, the manager doesn't merge neighbouring free blocks,
+    so with two sizes the blocks of freed nodes would be split into pieces too small for either
+ */
+const B: usize = 6;
+const CAPACITY: usize = 2 * B - 1;
+const MIN_LEN: usize = B - 1;
+
+#[repr(C)]
+struct LeafNode<K, V> {
+    parent: *mut InternalNode<K, V>,
+    parent_idx: u16,
+    len: u16,
+    keys: [MaybeUninit<K>; CAPACITY],
+    vals: [MaybeUninit<V>; CAPACITY],
+}
+
+#[repr(C)]
+struct InternalNode<K, V> {
+    data: LeafNode<K, V>,
+    edges: [MaybeUninit<NodePtr<K, V>>; CAPACITY + 1],
+}
+
+type NodePtr<K, V> = *mut LeafNode<K, V>;
+
+//node helpers, all of them need a valid node (and i in bounds)
+
+fn new_node<K, V, A: MyAllocator>(alloc: &A) -> NodePtr<K, V> {
+    let node = allocate_or_panic(alloc, Layout::new::<InternalNode<K, V>>()) as NodePtr<K, V>;
+    unsafe {
+        ptr::addr_of_mut!((*node).parent).write(ptr::null_mut());
+        ptr::addr_of_mut!((*node).len).write(0);
+    }
+    node
+}
+
+//frees the node without touching its keys, values or children
+unsafe fn free_node<K, V, A: MyAllocator>(alloc: &A, node: NodePtr<K, V>) {
+    unsafe {
+        alloc.deallocate(NonNull::new_unchecked(node as *mut u8), Layout::new::<InternalNode<K, V>>());
+    }
+}
+
+unsafe fn len<K, V>(node: NodePtr<K, V>) -> usize {
+    unsafe { (*node).len as usize }
+}
+
+unsafe fn set_len<K, V>(node: NodePtr<K, V>, len: usize) {
+    unsafe { (*node).len = len as u16 }
+}
+
+//raw places, so references handed out for other slots of the node stay valid
+unsafe fn key_ptr<K, V>(node: NodePtr<K, V>, i: usize) -> *mut K {
+    unsafe { (ptr::addr_of_mut!((*node).keys) as *mut K).add(i) }
+}
+
+unsafe fn val_ptr<K, V>(node: NodePtr<K, V>, i: usize) -> *mut V {
+    unsafe { (ptr::addr_of_mut!((*node).vals) as *mut V).add(i) }
+}
+
+unsafe fn edge_ptr<K, V>(node: NodePtr<K, V>, i: usize) -> *mut NodePtr<K, V> {
+    unsafe { (ptr::addr_of_mut!((*(node as *mut InternalNode<K, V>)).edges) as *mut NodePtr<K, V>).add(i) }
+}
+
+unsafe fn edge<K, V>(node: NodePtr<K, V>, i: usize) -> NodePtr<K, V> {
+    unsafe { *edge_ptr(node, i) }
+}
+
+unsafe fn key<'a, K, V>(node: NodePtr<K, V>, i: usize) -> &'a K {
+    unsafe { &*key_ptr(node, i) }
+}
+
+//points the children in range back at node
+unsafe fn correct_parent_links<K, V>(node: NodePtr<K, V>, range: RangeInclusive<usize>) {
+    for i in range {
+        unsafe {
+            let child = edge(node, i);
+            (*child).parent = node as *mut InternalNode<K, V>;
+            (*child).parent_idx = i as u16;
+        }
+    }
+}
+
+//moves ptr[idx..len] one to the right and writes value into the gap
+unsafe fn slice_insert<T>(ptr: *mut T, len: usize, idx: usize, value: T) {
+    unsafe {
+        ptr::copy(ptr.add(idx), ptr.add(idx + 1), len - idx);
+        ptr::write(ptr.add(idx), value);
+    }
+}
+
+unsafe fn slice_remove<T>(ptr: *mut T, len: usize, idx: usize) -> T {
+    unsafe {
+        let value = ptr::read(ptr.add(idx));
+        ptr::copy(ptr.add(idx + 1), ptr.add(idx), len - idx - 1);
+        value
+    }
+}
+
+//where a lookup is in a node: on key i, or on its way down edge i
+enum Search {
+    Found(usize),
+    GoDown(usize),
+}
+
+unsafe fn search_node<K: Borrow<Q>, Q: Ord + ?Sized, V>(node: NodePtr<K, V>, key: &Q) -> Search {
+    unsafe {
+        for i in 0..len(node) {
+            match key.cmp(self::key(node, i).borrow()) {
+                Ordering::Greater => {}
+                Ordering::Equal => return Search::Found(i),
+                Ordering::Less => return Search::GoDown(i),
+            }
+        }
+        Search::GoDown(len(node))
+    }
+}
+
+//finds the last key, used for pop_last and for the predecessor of a removed internal key
+//(plain fns rather than closures, so remove_rec isn't instantiated anew for every level)
+fn search_last<K, V>(node: NodePtr<K, V>, height: usize) -> Search {
+    let len = unsafe { len(node) };
+    if height == 0 {
+        Search::Found(len - 1)
+    } else {
+        Search::GoDown(len)
+    }
+}
+
+fn search_first<K, V>(_: NodePtr<K, V>, height: usize) -> Search {
+    if height == 0 {
+        Search::Found(0)
+    } else {
+        Search::GoDown(0)
+    }
+}
+
+//INSERTION
+
+//a node which was full split into itself and right, k and v go up into the parent between them
+enum Insertion<K, V> {
+    Fit,
+    Split(K, V, NodePtr<K, V>),
+}
+
+//the nodes an insertion needs are allocated before it changes the tree, so if an allocation fails, the map is left as it was
+//they are linked through their parent pointers, the ones which aren't used are freed on drop (also on unwind)
+struct SpareNodes<'a, K, V, A: MyAllocator> {
+    alloc: &'a A,
+    head: NodePtr<K, V>,
+}
+
+impl<'a, K, V, A: MyAllocator> SpareNodes<'a, K, V, A> {
+    fn new(alloc: &'a A, count: usize) -> SpareNodes<'a, K, V, A> {
+        let mut spare = SpareNodes { alloc, head: ptr::null_mut() };
+        for _ in 0..count {
+            let node = new_node::<K, V, A>(alloc);
+            unsafe {
+                (*node).parent = spare.head as *mut InternalNode<K, V>;
+            }
+            spare.head = node;
+        }
+        spare
+    }
+
+    fn take(&mut self) -> NodePtr<K, V> {
+        let node = self.head;
+        assert!(!node.is_null(), "btree insertion needs more nodes than it allocated");
+        unsafe {
+            self.head = (*node).parent as NodePtr<K, V>;
+            (*node).parent = ptr::null_mut();
+        }
+        node
+    }
+}
+
+impl<'a, K, V, A: MyAllocator> Drop for SpareNodes<'a, K, V, A> {
+    fn drop(&mut self) {
+        while !self.head.is_null() {
+            let node = self.take();
+            unsafe {
+                free_node(self.alloc, node);
+            }
+        }
+    }
+}
+
+//the number of nodes inserting k allocates: the full nodes at the bottom of its way split, and a new root if the root splits too
+//the key must not be in the tree yet
+unsafe fn nodes_needed<K: Ord, V>(mut node: NodePtr<K, V>, height: usize, k: &K) -> usize {
+    unsafe {
+        let mut full = 0;
+        for h in (0..=height).rev() {
+            full = if len(node) == CAPACITY { full + 1 } else { 0 };
+            if h != 0 {
+                let idx = match search_node(node, k) {
+                    Search::Found(i) | Search::GoDown(i) => i,
+                };
+                node = edge(node, idx);
+            }
+        }
+        if full == height + 1 { full + 1 } else { full }
+    }
+}
+
+//the key must not be in the subtree yet, spare must hold the nodes_needed
+unsafe fn insert_rec<K: Ord, V, A: MyAllocator>(spare: &mut SpareNodes<'_, K, V, A>, node: NodePtr<K, V>, height: usize, k: K, v: V) -> Insertion<K, V> {
+    unsafe {
+        let idx = match search_node(node, &k) {
+            Search::Found(i) | Search::GoDown(i) => i,
+        };
+        if height == 0 {
+            return insert_into(spare, node, 0, idx, k, v, ptr::null_mut());
+        }
+        match insert_rec(spare, edge(node, idx), height - 1, k, v) {
+            Insertion::Fit => Insertion::Fit,
+            Insertion::Split(k, v, right) => insert_into(spare, node, height, idx, k, v, right),
+        }
+    }
+}
+
+//inserts k and v at idx and right as the edge after them (internal nodes only), splits the node if it's full
+unsafe fn insert_into<K, V, A: MyAllocator>(spare: &mut SpareNodes<'_, K, V, A>, node: NodePtr<K, V>, height: usize, idx: usize, k: K, v: V, right: NodePtr<K, V>) -> Insertion<K, V> {
+    unsafe {
+        if len(node) < CAPACITY {
+            insert_fit(node, height, idx, k, v, right);
+            return Insertion::Fit;
+        }
+
+        //the left half keeps keys 0..MIN_LEN, key MIN_LEN goes up and the right half gets the rest
+        let new = spare.take();
+        let new_len = CAPACITY - MIN_LEN - 1;
+        ptr::copy_nonoverlapping(key_ptr(node, MIN_LEN + 1), key_ptr(new, 0), new_len);
+        ptr::copy_nonoverlapping(val_ptr(node, MIN_LEN + 1), val_ptr(new, 0), new_len);
+        if height != 0 {
+            ptr::copy_nonoverlapping(edge_ptr(node, MIN_LEN + 1), edge_ptr(new, 0), new_len + 1);
+        }
+        let middle_k = ptr::read(key_ptr(node, MIN_LEN));
+        let middle_v = ptr::read(val_ptr(node, MIN_LEN));
+        set_len(node, MIN_LEN);
+        set_len(new, new_len);
+        if height != 0 {
+            correct_parent_links(new, 0..=new_len);
+        }
+
+        if idx <= MIN_LEN {
+            insert_fit(node, height, idx, k, v, right);
+        } else {
+            insert_fit(new, height, idx - MIN_LEN - 1, k, v, right);
+        }
+        Insertion::Split(middle_k, middle_v, new)
+    }
+}
+
+unsafe fn insert_fit<K, V>(node: NodePtr<K, V>, height: usize, idx: usize, k: K, v: V, right: NodePtr<K, V>) {
+    unsafe {
+        let len = len(node);
+        slice_insert(key_ptr(node, 0), len, idx, k);
+        slice_insert(val_ptr(node, 0), len, idx, v);
+        if height != 0 {
+            slice_insert(edge_ptr(node, 0), len + 1, idx + 1, right);
+            correct_parent_links(node, idx + 1..=len + 1);
+        }
+        set_len(node, len + 1);
+    }
+}
+
+//REMOVAL
+
+//removes the key search leads to, every child on the way is refilled to MIN_LEN afterwards
+unsafe fn remove_rec<K, V, A: MyAllocator>(alloc: &A, node: NodePtr<K, V>, height: usize, search: &mut impl FnMut(NodePtr<K, V>, usize) -> Search) -> Option<(K, V)> {
+    unsafe {
+        match search(node, height) {
+            Search::Found(i) if height == 0 => {
+                let len = len(node);
+                let k = slice_remove(key_ptr(node, 0), len, i);
+                let v = slice_remove(val_ptr(node, 0), len, i);
+                set_len(node, len - 1);
+                Some((k, v))
+            }
+            Search::Found(i) => {
+                //the key is replaced by its predecessor, the last key of the subtree left of it
+                let (pk, pv) = remove_rec(alloc, edge(node, i), height - 1, &mut search_last).unwrap();
+                let k = mem::replace(&mut *key_ptr(node, i), pk);
+                let v = mem::replace(&mut *val_ptr(node, i), pv);
+                fix_underfull(alloc, node, height, i);
+                Some((k, v))
+            }
+            Search::GoDown(_) if height == 0 => None,
+            Search::GoDown(i) => {
+                let removed = remove_rec(alloc, edge(node, i), height - 1, search)?;
+                fix_underfull(alloc, node, height, i);
+                Some(removed)
+            }
+        }
+    }
+}
+
+//refills child i of node from a sibling, or merges it with one
+unsafe fn fix_underfull<K, V, A: MyAllocator>(alloc: &A, node: NodePtr<K, V>, height: usize, i: usize) {
+    unsafe {
+        if len(edge(node, i)) >= MIN_LEN {
+            return;
+        }
+        if i > 0 && len(edge(node, i - 1)) > MIN_LEN {
+            steal_left(node, height, i);
+        } else if i < len(node) && len(edge(node, i + 1)) > MIN_LEN {
+            steal_right(node, height, i);
+        } else if i > 0 {
+            merge(alloc, node, height, i - 1);
+        } else {
+            merge(alloc, node, height, i);
+        }
+    }
+}
+
+//rotates the last key of child i - 1 through the parent into the front of child i
+unsafe fn steal_left<K, V>(node: NodePtr<K, V>, height: usize, i: usize) {
+    unsafe {
+        let left = edge(node, i - 1);
+        let child = edge(node, i);
+        let (left_len, child_len) = (len(left), len(child));
+
+        let k = mem::replace(&mut *key_ptr(node, i - 1), ptr::read(key_ptr(left, left_len - 1)));
+        let v = mem::replace(&mut *val_ptr(node, i - 1), ptr::read(val_ptr(left, left_len - 1)));
+        slice_insert(key_ptr(child, 0), child_len, 0, k);
+        slice_insert(val_ptr(child, 0), child_len, 0, v);
+        if height > 1 {
+            slice_insert(edge_ptr(child, 0), child_len + 1, 0, edge(left, left_len));
+            correct_parent_links(child, 0..=child_len + 1);
+        }
+        set_len(left, left_len - 1);
+        set_len(child, child_len + 1);
+    }
+}
+
+//rotates the first key of child i + 1 through the parent onto the end of child i
+unsafe fn steal_right<K, V>(node: NodePtr<K, V>, height: usize, i: usize) {
+    unsafe {
+        let child = edge(node, i);
+        let right = edge(node, i + 1);
+        let (child_len, right_len) = (len(child), len(right));
+
+        let k = mem::replace(&mut *key_ptr(node, i), slice_remove(key_ptr(right, 0), right_len, 0));
+        let v = mem::replace(&mut *val_ptr(node, i), slice_remove(val_ptr(right, 0), right_len, 0));
+        ptr::write(key_ptr(child, child_len), k);
+        ptr::write(val_ptr(child, child_len), v);
+        if height > 1 {
+            ptr::write(edge_ptr(child, child_len + 1), slice_remove(edge_ptr(right, 0), right_len + 1, 0));
+            correct_parent_links(child, child_len + 1..=child_len + 1);
+            correct_parent_links(right, 0..=right_len - 1);
+        }
+        set_len(child, child_len + 1);
+        set_len(right, right_len - 1);
+    }
+}
+
+//moves key i of node and all of child i + 1 into child i, then frees child i + 1
+unsafe fn merge<K, V, A: MyAllocator>(alloc: &A, node: NodePtr<K, V>, height: usize, i: usize) {
+    unsafe {
+        let left = edge(node, i);
+        let right = edge(node, i + 1);
+        let (len, left_len, right_len) = (len(node), len(left), len(right));
+
+        ptr::write(key_ptr(left, left_len), slice_remove(key_ptr(node, 0), len, i));
+        ptr::write(val_ptr(left, left_len), slice_remove(val_ptr(node, 0), len, i));
+        slice_remove(edge_ptr(node, 0), len + 1, i + 1);
+        correct_parent_links(node, i + 1..=len - 1);
+        set_len(node, len - 1);
+
+        ptr::copy_nonoverlapping(key_ptr(right, 0), key_ptr(left, left_len + 1), right_len);
+        ptr::copy_nonoverlapping(val_ptr(right, 0), val_ptr(left, left_len + 1), right_len);
+        if height > 1 {
+            ptr::copy_nonoverlapping(edge_ptr(right, 0), edge_ptr(left, left_len + 1), right_len + 1);
+            correct_parent_links(left, left_len + 1..=left_len + 1 + right_len);
+        }
+        set_len(left, left_len + 1 + right_len);
+        free_node(alloc, right);
+    }
+}
+
+//drops every key and value below node and frees the nodes
+unsafe fn drop_subtree<K, V, A: MyAllocator>(alloc: &A, node: NodePtr<K, V>, height: usize) {
+    unsafe {
+        let len = len(node);
+        for i in 0..len {
+            ptr::drop_in_place(key_ptr(node, i));
+            ptr::drop_in_place(val_ptr(node, i));
+        }
+        if height != 0 {
+            for i in 0..=len {
+                drop_subtree(alloc, edge(node, i), height - 1);
+            }
+        }
+        free_node(alloc, node);
+    }
+}
+
+//NAVIGATION
+//the iterators sit on leaf edges, the gaps between the keys of leaves,
+//between two neighbouring keys of the whole tree there is exactly one leaf edge
+
+struct LeafEdge<K, V> {
+    node: NodePtr<K, V>,
+    idx: usize,
+}
+
+impl<K, V> Clone for LeafEdge<K, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K, V> Copy for LeafEdge<K, V> {}
+
+impl<K, V> PartialEq for LeafEdge<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.node == other.node && self.idx == other.idx
+    }
+}
+
+impl<K, V> LeafEdge<K, V> {
+    //follows the edges pick chooses down to a leaf
+    unsafe fn descend(mut node: NodePtr<K, V>, mut height: usize, mut pick: impl FnMut(NodePtr<K, V>) -> usize) -> LeafEdge<K, V> {
+        unsafe {
+            loop {
+                let idx = pick(node);
+                if height == 0 {
+                    return LeafEdge { node, idx };
+                }
+                node = edge(node, idx);
+                height -= 1;
+            }
+        }
+    }
+
+    //returns the key right of the edge and moves past it, there must be one
+    unsafe fn next_kv(&mut self) -> (NodePtr<K, V>, usize) {
+        unsafe {
+            let (mut node, mut idx, mut height) = (self.node, self.idx, 0);
+            while idx >= len(node) {
+                idx = (*node).parent_idx as usize;
+                node = (*node).parent as NodePtr<K, V>;
+                height += 1;
+            }
+            *self = if height == 0 {
+                LeafEdge { node, idx: idx + 1 }
+            } else {
+                LeafEdge::descend(edge(node, idx + 1), height - 1, |_| 0)
+            };
+            (node, idx)
+        }
+    }
+
+    //returns the key left of the edge and moves before it, there must be one
+    unsafe fn next_back_kv(&mut self) -> (NodePtr<K, V>, usize) {
+        unsafe {
+            let (mut node, mut idx, mut height) = (self.node, self.idx, 0);
+            while idx == 0 {
+                idx = (*node).parent_idx as usize;
+                node = (*node).parent as NodePtr<K, V>;
+                height += 1;
+            }
+            *self = if height == 0 {
+                LeafEdge { node, idx: idx - 1 }
+            } else {
+                LeafEdge::descend(edge(node, idx - 1), height - 1, |n| len(n))
+            };
+            (node, idx - 1)
+        }
+    }
+}
+
+//the keys between two leaf edges
+struct LeafRange<K, V> {
+    front: LeafEdge<K, V>,
+    back: LeafEdge<K, V>,
+}
+
+impl<K, V> Clone for LeafRange<K, V> {
+    fn clone(&self) -> Self {
+        LeafRange { front: self.front, back: self.back }
+    }
+}
+
+impl<K, V> LeafRange<K, V> {
+    fn empty() -> LeafRange<K, V> {
+        let edge = LeafEdge { node: ptr::null_mut(), idx: 0 };
+        LeafRange { front: edge, back: edge }
+    }
+
+    fn next_kv(&mut self) -> Option<(NodePtr<K, V>, usize)> {
+        if self.front == self.back {
+            return None;
+        }
+        unsafe {
+            Some(self.front.next_kv())
+        }
+    }
+
+    fn next_back_kv(&mut self) -> Option<(NodePtr<K, V>, usize)> {
+        if self.front == self.back {
+            return None;
+        }
+        unsafe {
+            Some(self.back.next_back_kv())
+        }
+    }
+}
+
+//first edge index of node whose key isn't below the bound (after == false), or is above it (after == true)
+fn bound_idx<K: Borrow<Q>, Q: Ord + ?Sized, V>(node: NodePtr<K, V>, key: &Q, after: bool) -> usize {
+    unsafe {
+        (0..len(node)).find(|&i| match self::key(node, i).borrow().cmp(key) {
+            Ordering::Less => false,
+            Ordering::Equal => !after,
+            Ordering::Greater => true,
+        }).unwrap_or(len(node))
+    }
+}
+
+//an ordered map, a B-tree whose nodes come from A (the global heap by default)
+pub struct MyBTreeMap<K, V, A: MyAllocator = GlobalHeap> {
+    root: NodePtr<K, V>,
+    height: usize,
+    len: usize,
+    alloc: A,
+    marker: PhantomData<(K, V)>,
+}
+
+unsafe impl<K: Send, V: Send, A: MyAllocator + Send> Send for MyBTreeMap<K, V, A> {}
+unsafe impl<K: Sync, V: Sync, A: MyAllocator + Sync> Sync for MyBTreeMap<K, V, A> {}
+
+//constructors on the global heap
+impl<K, V> MyBTreeMap<K, V> {
+    pub const fn new() -> MyBTreeMap<K, V> {
+        MyBTreeMap::new_in(GlobalHeap)
+    }
+}
+
+//constructors, getters
+impl<K, V, A: MyAllocator> MyBTreeMap<K, V, A> {
+    //nothing is allocated until the first insert
+    pub const fn new_in(alloc: A) -> MyBTreeMap<K, V, A> {
+        MyBTreeMap { root: ptr::null_mut(), height: 0, len: 0, alloc, marker: PhantomData }
+    }
+
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    //frees every node
+    pub fn clear(&mut self) {
+        let root = mem::replace(&mut self.root, ptr::null_mut());
+        self.len = 0;
+        if !root.is_null() {
+            unsafe {
+                drop_subtree(&self.alloc, root, self.height);
+            }
+        }
+        self.height = 0;
+    }
+
+    fn full_range(&self) -> LeafRange<K, V> {
+        if self.root.is_null() {
+            return LeafRange::empty();
+        }
+        unsafe {
+            LeafRange {
+                front: LeafEdge::descend(self.root, self.height, |_| 0),
+                back: LeafEdge::descend(self.root, self.height, |n| len(n)),
+            }
+        }
+    }
+
+    pub fn iter(&self) -> MyBTreeMapIter<'_, K, V> {
+        MyBTreeMapIter { range: self.full_range(), remaining: self.len, marker: PhantomData }
+    }
+
+    pub fn iter_mut(&mut self) -> MyBTreeMapIterMut<'_, K, V> {
+        MyBTreeMapIterMut { range: self.full_range(), remaining: self.len, marker: PhantomData }
+    }
+
+    pub fn keys(&self) -> MyBTreeMapKeys<'_, K, V> {
+        MyBTreeMapKeys { iter: self.iter() }
+    }
+
+    pub fn values(&self) -> MyBTreeMapValues<'_, K, V> {
+        MyBTreeMapValues { iter: self.iter() }
+    }
+
+    pub fn values_mut(&mut self) -> MyBTreeMapValuesMut<'_, K, V> {
+        MyBTreeMapValuesMut { iter: self.iter_mut() }
+    }
+
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        self.iter().next()
+    }
+
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        self.iter().next_back()
+    }
+
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        self.remove_by(search_first)
+    }
+
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        self.remove_by(search_last)
+    }
+
+    //removes the key search leads to and shrinks the tree if the root ran empty
+    fn remove_by(&mut self, mut search: impl FnMut(NodePtr<K, V>, usize) -> Search) -> Option<(K, V)> {
+        if self.root.is_null() {
+            return None;
+        }
+        unsafe {
+            let removed = remove_rec(&self.alloc, self.root, self.height, &mut search)?;
+            self.len -= 1;
+            let old = self.root;
+            if len(old) == 0 && self.height == 0 {
+                self.root = ptr::null_mut();
+                free_node(&self.alloc, old);
+            } else if len(old) == 0 {
+                self.root = edge(old, 0);
+                (*self.root).parent = ptr::null_mut();
+                free_node(&self.alloc, old);
+                self.height -= 1;
+            }
+            Some(removed)
+        }
+    }
+}
+
+impl<K, V, A: MyAllocator + Default> Default for MyBTreeMap<K, V, A> {
+    fn default() -> Self {
+        MyBTreeMap::new_in(A::default())
+    }
+}
+
+//lookups
+impl<K: Ord, V, A: MyAllocator> MyBTreeMap<K, V, A> {
+    fn find<Q: Ord + ?Sized>(&self, key: &Q) -> Option<(NodePtr<K, V>, usize)>
+    where K: Borrow<Q> {
+        if self.root.is_null() {
+            return None;
+        }
+        let (mut node, mut height) = (self.root, self.height);
+        unsafe {
+            loop {
+                match search_node(node, key) {
+                    Search::Found(i) => return Some((node, i)),
+                    Search::GoDown(_) if height == 0 => return None,
+                    Search::GoDown(i) => {
+                        node = edge(node, i);
+                        height -= 1;
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn get<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&V>
+    where K: Borrow<Q> {
+        self.get_key_value(key).map(|(_, v)| v)
+    }
+
+    pub fn get_key_value<Q: Ord + ?Sized>(&self, key: &Q) -> Option<(&K, &V)>
+    where K: Borrow<Q> {
+        let (node, i) = self.find(key)?;
+        unsafe {
+            Some((&*key_ptr(node, i), &*val_ptr(node, i)))
+        }
+    }
+
+    pub fn get_mut<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<&mut V>
+    where K: Borrow<Q> {
+        let (node, i) = self.find(key)?;
+        unsafe {
+            Some(&mut *val_ptr(node, i))
+        }
+    }
+
+    pub fn contains_key<Q: Ord + ?Sized>(&self, key: &Q) -> bool
+    where K: Borrow<Q> {
+        self.find(key).is_some()
+    }
+
+    //the leaf edges around the keys in range
+    fn range_edges<Q: Ord + ?Sized, R: RangeBounds<Q>>(&self, range: R) -> LeafRange<K, V>
+    where K: Borrow<Q> {
+        match (range.start_bound(), range.end_bound()) {
+            (Bound::Excluded(s), Bound::Excluded(e)) if s == e => panic!("range start and end are equal and excluded in MyBTreeMap"),
+            (Bound::Included(s) | Bound::Excluded(s), Bound::Included(e) | Bound::Excluded(e)) if s > e => panic!("range start is greater than range end in MyBTreeMap"),
+            _ => {}
+        }
+        if self.root.is_null() {
+            return LeafRange::empty();
+        }
+
+        unsafe {
+            let front = LeafEdge::descend(self.root, self.height, |n| match range.start_bound() {
+                Bound::Included(key) => bound_idx(n, key, false),
+                Bound::Excluded(key) => bound_idx(n, key, true),
+                Bound::Unbounded => 0,
+            });
+            let back = LeafEdge::descend(self.root, self.height, |n| match range.end_bound() {
+                Bound::Included(key) => bound_idx(n, key, true),
+                Bound::Excluded(key) => bound_idx(n, key, false),
+                Bound::Unbounded => len(n),
+            });
+            LeafRange { front, back }
+        }
+    }
+
+    //panics if the start of the range is after its end
+    pub fn range<Q: Ord + ?Sized, R: RangeBounds<Q>>(&self, range: R) -> MyBTreeMapRange<'_, K, V>
+    where K: Borrow<Q> {
+        MyBTreeMapRange { range: self.range_edges(range), marker: PhantomData }
+    }
+
+    pub fn range_mut<Q: Ord + ?Sized, R: RangeBounds<Q>>(&mut self, range: R) -> MyBTreeMapRangeMut<'_, K, V>
+    where K: Borrow<Q> {
+        MyBTreeMapRangeMut { range: self.range_edges(range), marker: PhantomData }
+    }
+}
+
+//adding and removing values
+impl<K: Ord, V, A: MyAllocator> MyBTreeMap<K, V, A> {
+    //returns the old value if the key was already in the map (the old key is kept)
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some((node, i)) = self.find(&key) {
+            unsafe {
+                return Some(mem::replace(&mut *val_ptr(node, i), value));
+            }
+        }
+
+        if self.root.is_null() {
+            self.root = new_node(&self.alloc);
+        }
+        unsafe {
+            let mut spare = SpareNodes::new(&self.alloc, nodes_needed(self.root, self.height, &key));
+            if let Insertion::Split(k, v, right) = insert_rec(&mut spare, self.root, self.height, key, value) {
+                //the root split, the tree grows by a level
+                let root = spare.take();
+                ptr::write(key_ptr(root, 0), k);
+                ptr::write(val_ptr(root, 0), v);
+                ptr::write(edge_ptr(root, 0), self.root);
+                ptr::write(edge_ptr(root, 1), right);
+                set_len(root, 1);
+                correct_parent_links(root, 0..=1);
+                self.root = root;
+                self.height += 1;
+            }
+        }
+        self.len += 1;
+        None
+    }
+
+    pub fn remove<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<V>
+    where K: Borrow<Q> {
+        self.remove_entry(key).map(|(_, v)| v)
+    }
+
+    pub fn remove_entry<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<(K, V)>
+    where K: Borrow<Q> {
+        self.remove_by(|n, _| unsafe { search_node(n, key) })
+    }
+
+    //moves the keys from key on into a new map one by one, O(k log n) for the k moved keys,
+    //the new map allocates a node whenever a reinsert needs one, while self frees its emptied ones
+    //if an allocation fails (insert panics), the entries before the failed one stay in self,
+    //the moved ones are dropped with the new map and so is the entry which was being moved
+    pub fn split_off<Q: Ord + ?Sized>(&mut self, key: &Q) -> MyBTreeMap<K, V, A>
+    where K: Borrow<Q>, A: Clone {
+        let mut other = MyBTreeMap::new_in(self.alloc.clone());
+        while self.last_key_value().is_some_and(|(k, _)| k.borrow() >= key) {
+            let (k, v) = self.pop_last().unwrap();
+            other.insert(k, v);
+        }
+        other
+    }
+
+    //moves every entry of other into self one by one, values of other win over values of self,
+    //O(m log(n + m)) for the m entries of other, self allocates a node whenever a reinsert needs one
+    //if an allocation fails (insert panics), the entries moved so far are in self, the rest stay in other
+    //and the entry which was being moved is dropped
+    pub fn append(&mut self, other: &mut MyBTreeMap<K, V, A>) {
+        while let Some((k, v)) = other.pop_first() {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<K, V, A: MyAllocator> Drop for MyBTreeMap<K, V, A> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+//access
+impl<K: Ord + Borrow<Q>, Q: Ord + ?Sized, V, A: MyAllocator> Index<&Q> for MyBTreeMap<K, V, A> {
+    type Output = V;
+
+    fn index(&self, key: &Q) -> &Self::Output {
+        self.get(key).expect("key not found in the map")
+    }
+}
+
+//conversions
+impl<K: Ord, V, A: MyAllocator + Default> FromIterator<(K, V)> for MyBTreeMap<K, V, A> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = MyBTreeMap::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Ord, V, A: MyAllocator + Default, const N: usize> From<[(K, V); N]> for MyBTreeMap<K, V, A> {
+    fn from(entries: [(K, V); N]) -> Self {
+        entries.into_iter().collect()
+    }
+}
+
+impl<K: Ord, V, A: MyAllocator> Extend<(K, V)> for MyBTreeMap<K, V, A> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<'a, K: Ord + Copy, V: Copy, A: MyAllocator> Extend<(&'a K, &'a V)> for MyBTreeMap<K, V, A> {
+    fn extend<I: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().map(|(k, v)| (*k, *v)));
+    }
+}
+
+//iterator implementations
+impl<K, V, A: MyAllocator> IntoIterator for MyBTreeMap<K, V, A> {
+    type Item = (K, V);
+
+    type IntoIter = MyBTreeMapIntoIter<K, V, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        MyBTreeMapIntoIter { map: self }
+    }
+}
+
+impl<'a, K, V, A: MyAllocator> IntoIterator for &'a MyBTreeMap<K, V, A> {
+    type Item = (&'a K, &'a V);
+
+    type IntoIter = MyBTreeMapIter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, A: MyAllocator> IntoIterator for &'a mut MyBTreeMap<K, V, A> {
+    type Item = (&'a K, &'a mut V);
+
+    type IntoIter = MyBTreeMapIterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K: Debug, V: Debug, A: MyAllocator> Debug for MyBTreeMap<K, V, A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord + Clone, V: Clone, A: MyAllocator + Clone> Clone for MyBTreeMap<K, V, A> {
+    fn clone(&self) -> Self {
+        let mut out = MyBTreeMap::new_in(self.alloc.clone());
+        for (k, v) in self {
+            out.insert(k.clone(), v.clone());
+        }
+        out
+    }
+}
+
+impl<K: PartialEq, V: PartialEq, A: MyAllocator> PartialEq for MyBTreeMap<K, V, A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<K: Eq, V: Eq, A: MyAllocator> Eq for MyBTreeMap<K, V, A> {}
+
+//ITERATORS
+
+pub struct MyBTreeMapIter<'a, K, V> {
+    range: LeafRange<K, V>,
+    remaining: usize,
+    marker: PhantomData<&'a (K, V)>,
+}
+
+unsafe impl<'a, K: Sync, V: Sync> Send for MyBTreeMapIter<'a, K, V> {}
+unsafe impl<'a, K: Sync, V: Sync> Sync for MyBTreeMapIter<'a, K, V> {}
+
+impl<'a, K, V> Clone for MyBTreeMapIter<'a, K, V> {
+    fn clone(&self) -> Self {
+        MyBTreeMapIter { range: self.range.clone(), remaining: self.remaining, marker: PhantomData }
+    }
+}
+
+impl<'a, K, V> Iterator for MyBTreeMapIter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, i) = self.range.next_kv()?;
+        self.remaining -= 1;
+        unsafe {
+            Some((&*key_ptr(node, i), &*val_ptr(node, i)))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for MyBTreeMapIter<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (node, i) = self.range.next_back_kv()?;
+        self.remaining -= 1;
+        unsafe {
+            Some((&*key_ptr(node, i), &*val_ptr(node, i)))
+        }
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for MyBTreeMapIter<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for MyBTreeMapIter<'a, K, V> {}
+
+pub struct MyBTreeMapIterMut<'a, K, V> {
+    range: LeafRange<K, V>,
+    remaining: usize,
+    marker: PhantomData<&'a mut (K, V)>,
+}
+
+unsafe impl<'a, K: Sync, V: Send> Send for MyBTreeMapIterMut<'a, K, V> {}
+unsafe impl<'a, K: Sync, V: Sync> Sync for MyBTreeMapIterMut<'a, K, V> {}
+
+impl<'a, K, V> Iterator for MyBTreeMapIterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, i) = self.range.next_kv()?;
+        self.remaining -= 1;
+        unsafe {
+            Some((&*key_ptr(node, i), &mut *val_ptr(node, i)))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for MyBTreeMapIterMut<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (node, i) = self.range.next_back_kv()?;
+        self.remaining -= 1;
+        unsafe {
+            Some((&*key_ptr(node, i), &mut *val_ptr(node, i)))
+        }
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for MyBTreeMapIterMut<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for MyBTreeMapIterMut<'a, K, V> {}
+
+pub struct MyBTreeMapKeys<'a, K, V> {
+    iter: MyBTreeMapIter<'a, K, V>,
+}
+
+impl<'a, K, V> Clone for MyBTreeMapKeys<'a, K, V> {
+    fn clone(&self) -> Self {
+        MyBTreeMapKeys { iter: self.iter.clone() }
+    }
+}
+
+impl<'a, K, V> Iterator for MyBTreeMapKeys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(k, _)| k)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for MyBTreeMapKeys<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(k, _)| k)
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for MyBTreeMapKeys<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for MyBTreeMapKeys<'a, K, V> {}
+
+pub struct MyBTreeMapValues<'a, K, V> {
+    iter: MyBTreeMapIter<'a, K, V>,
+}
+
+impl<'a, K, V> Clone for MyBTreeMapValues<'a, K, V> {
+    fn clone(&self) -> Self {
+        MyBTreeMapValues { iter: self.iter.clone() }
+    }
+}
+
+impl<'a, K, V> Iterator for MyBTreeMapValues<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(_, v)| v)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for MyBTreeMapValues<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(_, v)| v)
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for MyBTreeMapValues<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for MyBTreeMapValues<'a, K, V> {}
+
+pub struct MyBTreeMapValuesMut<'a, K, V> {
+    iter: MyBTreeMapIterMut<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for MyBTreeMapValuesMut<'a, K, V> {
+    type Item = &'a mut V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(_, v)| v)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for MyBTreeMapValuesMut<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(_, v)| v)
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for MyBTreeMapValuesMut<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for MyBTreeMapValuesMut<'a, K, V> {}
+
+pub struct MyBTreeMapRange<'a, K, V> {
+    range: LeafRange<K, V>,
+    marker: PhantomData<&'a (K, V)>,
+}
+
+unsafe impl<'a, K: Sync, V: Sync> Send for MyBTreeMapRange<'a, K, V> {}
+unsafe impl<'a, K: Sync, V: Sync> Sync for MyBTreeMapRange<'a, K, V> {}
+
+impl<'a, K, V> Clone for MyBTreeMapRange<'a, K, V> {
+    fn clone(&self) -> Self {
+        MyBTreeMapRange { range: self.range.clone(), marker: PhantomData }
+    }
+}
+
+impl<'a, K, V> Iterator for MyBTreeMapRange<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, i) = self.range.next_kv()?;
+        unsafe {
+            Some((&*key_ptr(node, i), &*val_ptr(node, i)))
+        }
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for MyBTreeMapRange<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (node, i) = self.range.next_back_kv()?;
+        unsafe {
+            Some((&*key_ptr(node, i), &*val_ptr(node, i)))
+        }
+    }
+}
+
+impl<'a, K, V> FusedIterator for MyBTreeMapRange<'a, K, V> {}
+
+pub struct MyBTreeMapRangeMut<'a, K, V> {
+    range: LeafRange<K, V>,
+    marker: PhantomData<&'a mut (K, V)>,
+}
+
+unsafe impl<'a, K: Sync, V: Send> Send for MyBTreeMapRangeMut<'a, K, V> {}
+unsafe impl<'a, K: Sync, V: Sync> Sync for MyBTreeMapRangeMut<'a, K, V> {}
+
+impl<'a, K, V> Iterator for MyBTreeMapRangeMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, i) = self.range.next_kv()?;
+        unsafe {
+            Some((&*key_ptr(node, i), &mut *val_ptr(node, i)))
+        }
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for MyBTreeMapRangeMut<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (node, i) = self.range.next_back_kv()?;
+        unsafe {
+            Some((&*key_ptr(node, i), &mut *val_ptr(node, i)))
+        }
+    }
+}
+
+impl<'a, K, V> FusedIterator for MyBTreeMapRangeMut<'a, K, V> {}
+
+//pops from the map, which frees the nodes as they run empty and drops the rest at the end
+pub struct MyBTreeMapIntoIter<K, V, A: MyAllocator = GlobalHeap> {
+    map: MyBTreeMap<K, V, A>,
+}
+
+impl<K, V, A: MyAllocator> Iterator for MyBTreeMapIntoIter<K, V, A> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.map.pop_first()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.map.len, Some(self.map.len))
+    }
+}
+
+impl<K, V, A: MyAllocator> DoubleEndedIterator for MyBTreeMapIntoIter<K, V, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.map.pop_last()
+    }
+}
+
+impl<K, V, A: MyAllocator> ExactSizeIterator for MyBTreeMapIntoIter<K, V, A> {}
+
+impl<K, V, A: MyAllocator> FusedIterator for MyBTreeMapIntoIter<K, V, A> {}
@@ -0,0 +1,262 @@
+use core::{borrow::Borrow, fmt::Debug, iter::FusedIterator, ops::RangeBounds};
+
+use crate::allocator::{GlobalHeap, MyAllocator};
+use super::btree_map::{MyBTreeMap, MyBTreeMapIntoIter, MyBTreeMapKeys, MyBTreeMapRange};
+
+//a MyBTreeMap without values
+pub struct MyBTreeSet<T, A: MyAllocator = GlobalHeap> {
+    map: MyBTreeMap<T, (), A>,
+}
+
+//constructors on the global heap
+impl<T> MyBTreeSet<T> {
+    pub const fn new() -> MyBTreeSet<T> {
+        MyBTreeSet { map: MyBTreeMap::new() }
+    }
+}
+
+//constructors, getters
+impl<T, A: MyAllocator> MyBTreeSet<T, A> {
+    pub const fn new_in(alloc: A) -> MyBTreeSet<T, A> {
+        MyBTreeSet { map: MyBTreeMap::new_in(alloc) }
+    }
+
+    pub fn allocator(&self) -> &A {
+        self.map.allocator()
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    pub fn iter(&self) -> MyBTreeSetIter<'_, T> {
+        MyBTreeSetIter { keys: self.map.keys() }
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.map.first_key_value().map(|(value, _)| value)
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.map.last_key_value().map(|(value, _)| value)
+    }
+
+    pub fn pop_first(&mut self) -> Option<T> {
+        self.map.pop_first().map(|(value, _)| value)
+    }
+
+    pub fn pop_last(&mut self) -> Option<T> {
+        self.map.pop_last().map(|(value, _)| value)
+    }
+}
+
+impl<T, A: MyAllocator + Default> Default for MyBTreeSet<T, A> {
+    fn default() -> Self {
+        MyBTreeSet { map: MyBTreeMap::default() }
+    }
+}
+
+impl<T: Ord, A: MyAllocator> MyBTreeSet<T, A> {
+    //returns false if the value was already in the set (the old one is kept)
+    pub fn insert(&mut self, value: T) -> bool {
+        self.map.insert(value, ()).is_none()
+    }
+
+    pub fn contains<Q: Ord + ?Sized>(&self, value: &Q) -> bool
+    where T: Borrow<Q> {
+        self.map.contains_key(value)
+    }
+
+    pub fn get<Q: Ord + ?Sized>(&self, value: &Q) -> Option<&T>
+    where T: Borrow<Q> {
+        self.map.get_key_value(value).map(|(v, _)| v)
+    }
+
+    pub fn remove<Q: Ord + ?Sized>(&mut self, value: &Q) -> bool
+    where T: Borrow<Q> {
+        self.map.remove(value).is_some()
+    }
+
+    pub fn take<Q: Ord + ?Sized>(&mut self, value: &Q) -> Option<T>
+    where T: Borrow<Q> {
+        self.map.remove_entry(value).map(|(v, _)| v)
+    }
+
+    //panics if the start of the range is after its end
+    pub fn range<Q: Ord + ?Sized, R: RangeBounds<Q>>(&self, range: R) -> MyBTreeSetRange<'_, T>
+    where T: Borrow<Q> {
+        MyBTreeSetRange { range: self.map.range(range) }
+    }
+
+    //moves the values from value on into a new set
+    pub fn split_off<Q: Ord + ?Sized>(&mut self, value: &Q) -> MyBTreeSet<T, A>
+    where T: Borrow<Q>, A: Clone {
+        MyBTreeSet { map: self.map.split_off(value) }
+    }
+
+    pub fn append(&mut self, other: &mut MyBTreeSet<T, A>) {
+        self.map.append(&mut other.map);
+    }
+}
+
+//conversions
+impl<T: Ord, A: MyAllocator + Default> FromIterator<T> for MyBTreeSet<T, A> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = MyBTreeSet::default();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T: Ord, A: MyAllocator + Default, const N: usize> From<[T; N]> for MyBTreeSet<T, A> {
+    fn from(values: [T; N]) -> Self {
+        values.into_iter().collect()
+    }
+}
+
+impl<T: Ord, A: MyAllocator> Extend<T> for MyBTreeSet<T, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.map.extend(iter.into_iter().map(|value| (value, ())));
+    }
+}
+
+impl<'a, T: Ord + Copy, A: MyAllocator> Extend<&'a T> for MyBTreeSet<T, A> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+//iterator implementations
+impl<T, A: MyAllocator> IntoIterator for MyBTreeSet<T, A> {
+    type Item = T;
+
+    type IntoIter = MyBTreeSetIntoIter<T, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        MyBTreeSetIntoIter { iter: self.map.into_iter() }
+    }
+}
+
+impl<'a, T, A: MyAllocator> IntoIterator for &'a MyBTreeSet<T, A> {
+    type Item = &'a T;
+
+    type IntoIter = MyBTreeSetIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: Debug, A: MyAllocator> Debug for MyBTreeSet<T, A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Ord + Clone, A: MyAllocator + Clone> Clone for MyBTreeSet<T, A> {
+    fn clone(&self) -> Self {
+        MyBTreeSet { map: self.map.clone() }
+    }
+}
+
+impl<T: PartialEq, A: MyAllocator> PartialEq for MyBTreeSet<T, A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+    }
+}
+
+impl<T: Eq, A: MyAllocator> Eq for MyBTreeSet<T, A> {}
+
+//ITERATORS
+
+pub struct MyBTreeSetIter<'a, T> {
+    keys: MyBTreeMapKeys<'a, T, ()>,
+}
+
+impl<'a, T> Clone for MyBTreeSetIter<'a, T> {
+    fn clone(&self) -> Self {
+        MyBTreeSetIter { keys: self.keys.clone() }
+    }
+}
+
+impl<'a, T> Iterator for MyBTreeSetIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.keys.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.keys.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for MyBTreeSetIter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.keys.next_back()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for MyBTreeSetIter<'a, T> {}
+
+impl<'a, T> FusedIterator for MyBTreeSetIter<'a, T> {}
+
+pub struct MyBTreeSetRange<'a, T> {
+    range: MyBTreeMapRange<'a, T, ()>,
+}
+
+impl<'a, T> Clone for MyBTreeSetRange<'a, T> {
+    fn clone(&self) -> Self {
+        MyBTreeSetRange { range: self.range.clone() }
+    }
+}
+
+impl<'a, T> Iterator for MyBTreeSetRange<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.range.next().map(|(value, _)| value)
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for MyBTreeSetRange<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.range.next_back().map(|(value, _)| value)
+    }
+}
+
+impl<'a, T> FusedIterator for MyBTreeSetRange<'a, T> {}
+
+pub struct MyBTreeSetIntoIter<T, A: MyAllocator = GlobalHeap> {
+    iter: MyBTreeMapIntoIter<T, (), A>,
+}
+
+impl<T, A: MyAllocator> Iterator for MyBTreeSetIntoIter<T, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(value, _)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<T, A: MyAllocator> DoubleEndedIterator for MyBTreeSetIntoIter<T, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(value, _)| value)
+    }
+}
+
+impl<T, A: MyAllocator> ExactSizeIterator for MyBTreeSetIntoIter<T, A> {}
+
+impl<T, A: MyAllocator> FusedIterator for MyBTreeSetIntoIter<T, A> {}
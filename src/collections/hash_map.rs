@@ -0,0 +1,960 @@
+use core::{alloc::Layout, borrow::Borrow, fmt::Debug, hash::{BuildHasher, BuildHasherDefault, Hash, Hasher}, iter::FusedIterator, marker::PhantomData, mem, ops::Index, ptr::{self, NonNull}};
+
+use crate::allocator::{AllocError, GlobalHeap, MyAllocator};
+
+//hashing
+
+//a fast deterministic hasher (FxHash's mixing step with a final avalanche),
+//keys chosen by an attacker can be made to collide, use a seeded BuildHasher (e.g. std's RandomState) for them
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MyHasher {
+    hash: u64,
+}
+
+pub type DefaultHashBuilder = BuildHasherDefault<MyHasher>;
+
+impl MyHasher {
+    fn add(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(0x51_7c_c1_b7_27_22_0a_95);
+    }
+}
+
+impl Hasher for MyHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        let mut words = bytes.chunks_exact(8);
+        for word in &mut words {
+            self.add(u64::from_le_bytes(word.try_into().unwrap()));
+        }
+
+        let rest = words.remainder();
+        if !rest.is_empty() {
+            let mut word = [0; 8];
+            word[..rest.len()].copy_from_slice(rest);
+            self.add(u64::from_le_bytes(word));
+        }
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.add(i as u64);
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.add(i as u64);
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.add(i as u64);
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.add(i);
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.add(i as u64);
+    }
+
+    //murmur3's finalizer, so the top bits (the tag) and the low bits (the position) both depend on every input bit
+    fn finish(&self) -> u64 {
+        let mut h = self.hash;
+        h ^= h >> 33;
+        h = h.wrapping_mul(0xff51_afd7_ed55_8ccd);
+        h ^= h >> 33;
+        h = h.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+        h ^ (h >> 33)
+    }
+}
+
+fn make_hash<Q: Hash + ?Sized, S: BuildHasher>(hash_builder: &S, key: &Q) -> u64 {
+    hash_builder.hash_one(key)
+}
+
+/*
+    RawTable layout:
+    one allocation of buckets slots followed by buckets control bytes, buckets is a power of two (or 0 without allocation)
+    a control byte is EMPTY, DELETED or the top 7 bits of the hash of the value in the slot (then its top bit is 0)
+    a value is looked up from the slot (hash & mask) forward until an EMPTY control byte,
+    at most 7/8 of the slots are used or DELETED, so there is always an EMPTY one to stop at
+ */
+const EMPTY: u8 = 0x80;
+const DELETED: u8 = 0xFE;
+
+fn is_full(ctrl: u8) -> bool {
+    ctrl & 0x80 == 0
+}
+
+fn tag(hash: u64) -> u8 {
+    (hash >> 57) as u8
+}
+
+//how many values fit into this many buckets
+fn bucket_capacity(buckets: usize) -> usize {
+    if buckets < 8 {
+        buckets.saturating_sub(1)
+    } else {
+        buckets / 8 * 7
+    }
+}
+
+fn capacity_to_buckets(capacity: usize) -> Option<usize> {
+    if capacity < 4 {
+        return Some(4);
+    }
+    if capacity < 7 {
+        return Some(8);
+    }
+    capacity.checked_mul(8).map(|c| (c / 7).next_power_of_two())
+}
+
+//growth_left: how many more EMPTY slots can be used before the table has to grow
+struct RawTable<T, A: MyAllocator> {
+    slots: *mut T,
+    ctrl: *mut u8,
+    buckets: usize,
+    len: usize,
+    growth_left: usize,
+    alloc: A,
+    marker: PhantomData<T>,
+}
+
+unsafe impl<T: Send, A: MyAllocator + Send> Send for RawTable<T, A> {}
+unsafe impl<T: Sync, A: MyAllocator + Sync> Sync for RawTable<T, A> {}
+
+impl<T, A: MyAllocator> RawTable<T, A> {
+    fn new_in(alloc: A) -> RawTable<T, A> {
+        RawTable { slots: NonNull::dangling().as_ptr(), ctrl: NonNull::dangling().as_ptr(), buckets: 0, len: 0, growth_left: 0, alloc, marker: PhantomData }
+    }
+
+    fn capacity(&self) -> usize {
+        self.len + self.growth_left
+    }
+
+    //the layout of the allocation and where the control bytes start in it
+    fn layout(buckets: usize) -> Option<(Layout, usize)> {
+        let slots = Layout::array::<T>(buckets).ok()?;
+        slots.extend(Layout::array::<u8>(buckets).ok()?).ok()
+    }
+
+    //returns the slots and the control bytes, which are all EMPTY
+    fn allocate_buckets(alloc: &A, buckets: usize) -> Result<(*mut T, *mut u8), AllocError> {
+        let (layout, ctrl_offset) = Self::layout(buckets).ok_or(AllocError)?;
+        let base = alloc.allocate(layout)?.as_ptr();
+        unsafe {
+            let ctrl = base.add(ctrl_offset);
+            ptr::write_bytes(ctrl, EMPTY, buckets);
+            Ok((base as *mut T, ctrl))
+        }
+    }
+
+    //frees the memory without touching the values
+    unsafe fn free_buckets(alloc: &A, slots: *mut T, buckets: usize) {
+        if buckets != 0 {
+            let (layout, _) = Self::layout(buckets).unwrap();
+            unsafe {
+                alloc.deallocate(NonNull::new_unchecked(slots as *mut u8), layout);
+            }
+        }
+    }
+
+    fn ctrl(&self, index: usize) -> u8 {
+        unsafe {
+            *self.ctrl.add(index)
+        }
+    }
+
+    fn set_ctrl(&mut self, index: usize, ctrl: u8) {
+        unsafe {
+            *self.ctrl.add(index) = ctrl;
+        }
+    }
+
+    fn slot(&self, index: usize) -> *mut T {
+        unsafe {
+            self.slots.add(index)
+        }
+    }
+
+    //the slot of the value with this hash for which eq returns true
+    fn find(&self, hash: u64, mut eq: impl FnMut(&T) -> bool) -> Option<usize> {
+        if self.buckets == 0 {
+            return None;
+        }
+
+        let mask = self.buckets - 1;
+        let tag = tag(hash);
+        let mut index = hash as usize & mask;
+        loop {
+            let ctrl = self.ctrl(index);
+            if ctrl == EMPTY {
+                return None;
+            }
+            if ctrl == tag && eq(unsafe { &*self.slot(index) }) {
+                return Some(index);
+            }
+            index = (index + 1) & mask;
+        }
+    }
+
+    //the first EMPTY or DELETED slot on the way of the hash
+    fn find_insert_slot(ctrl: *const u8, buckets: usize, hash: u64) -> usize {
+        let mask = buckets - 1;
+        let mut index = hash as usize & mask;
+        while is_full(unsafe { *ctrl.add(index) }) {
+            index = (index + 1) & mask;
+        }
+        index
+    }
+
+    //inserts a value which isn't in the table yet, returns its slot
+    fn insert(&mut self, hash: u64, value: T, hasher: impl Fn(&T) -> u64) -> usize {
+        if self.growth_left == 0 {
+            //a DELETED slot can be reused without growing
+            let reusable = self.buckets != 0 && self.ctrl(Self::find_insert_slot(self.ctrl, self.buckets, hash)) == DELETED;
+            if !reusable {
+                self.reserve(1, hasher);
+            }
+        }
+
+        let index = Self::find_insert_slot(self.ctrl, self.buckets, hash);
+        if self.ctrl(index) == EMPTY {
+            self.growth_left -= 1;
+        }
+        self.set_ctrl(index, tag(hash));
+        unsafe {
+            ptr::write(self.slot(index), value);
+        }
+        self.len += 1;
+        index
+    }
+
+    //removes the value from the slot and returns it
+    //SAFETY: the slot must be full
+    unsafe fn erase(&mut self, index: usize) -> T {
+        //a lookup which passes this slot stops at the next one anyway if it's EMPTY
+        let next = (index + 1) & (self.buckets - 1);
+        if self.ctrl(next) == EMPTY {
+            self.set_ctrl(index, EMPTY);
+            self.growth_left += 1;
+        } else {
+            self.set_ctrl(index, DELETED);
+        }
+
+        self.len -= 1;
+        unsafe {
+            ptr::read(self.slot(index))
+        }
+    }
+
+    fn reserve(&mut self, additional: usize, hasher: impl Fn(&T) -> u64) {
+        if let Err(e) = self.try_reserve(additional, hasher) {
+            panic!("{}", e);
+        }
+    }
+
+    fn try_reserve(&mut self, additional: usize, hasher: impl Fn(&T) -> u64) -> Result<(), AllocError> {
+        if additional <= self.growth_left {
+            return Ok(());
+        }
+
+        let needed = self.len.checked_add(additional).ok_or(AllocError)?;
+        let full_capacity = bucket_capacity(self.buckets);
+        //if DELETED slots fill the table, rebuilding it with the same size is enough
+        let capacity = if needed <= full_capacity / 2 { full_capacity } else { needed.max(full_capacity + 1) };
+        self.resize(capacity_to_buckets(capacity).ok_or(AllocError)?, hasher)
+    }
+
+    //moves the values into a new allocation, if the hasher panics the old table is left as it was
+    fn resize(&mut self, buckets: usize, hasher: impl Fn(&T) -> u64) -> Result<(), AllocError> {
+        struct FreeOnUnwind<'a, T, A: MyAllocator> {
+            alloc: &'a A,
+            slots: *mut T,
+            buckets: usize,
+        }
+
+        impl<'a, T, A: MyAllocator> Drop for FreeOnUnwind<'a, T, A> {
+            fn drop(&mut self) {
+                unsafe {
+                    RawTable::<T, A>::free_buckets(self.alloc, self.slots, self.buckets);
+                }
+            }
+        }
+
+        let (slots, ctrl) = Self::allocate_buckets(&self.alloc, buckets)?;
+        let guard = FreeOnUnwind { alloc: &self.alloc, slots, buckets };
+
+        for index in 0..self.buckets {
+            if is_full(self.ctrl(index)) {
+                unsafe {
+                    let hash = hasher(&*self.slot(index));
+                    let new_index = Self::find_insert_slot(ctrl, buckets, hash);
+                    *ctrl.add(new_index) = tag(hash);
+                    ptr::copy_nonoverlapping(self.slot(index), slots.add(new_index), 1);
+                }
+            }
+        }
+
+        mem::forget(guard);
+        unsafe {
+            Self::free_buckets(&self.alloc, self.slots, self.buckets);
+        }
+        self.slots = slots;
+        self.ctrl = ctrl;
+        self.buckets = buckets;
+        self.growth_left = bucket_capacity(buckets) - self.len;
+        Ok(())
+    }
+
+    //the next full slot from index
+    fn next_full(&self, mut index: usize) -> Option<usize> {
+        while index < self.buckets {
+            if is_full(self.ctrl(index)) {
+                return Some(index);
+            }
+            index += 1;
+        }
+        None
+    }
+
+    //removes every value for which f returns false
+    fn retain(&mut self, mut f: impl FnMut(&mut T) -> bool) {
+        for index in 0..self.buckets {
+            if is_full(self.ctrl(index)) && !f(unsafe { &mut *self.slot(index) }) {
+                //erased before it's dropped, so a panicking drop leaves the table valid
+                drop(unsafe { self.erase(index) });
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.retain(|_| false);
+        if self.buckets != 0 {
+            unsafe {
+                ptr::write_bytes(self.ctrl, EMPTY, self.buckets);
+            }
+        }
+        self.growth_left = bucket_capacity(self.buckets);
+    }
+}
+
+impl<T, A: MyAllocator> Drop for RawTable<T, A> {
+    fn drop(&mut self) {
+        self.clear();
+        unsafe {
+            Self::free_buckets(&self.alloc, self.slots, self.buckets);
+        }
+    }
+}
+
+//a hash map with open addressing, its table is one allocation from A (the global heap by default)
+pub struct MyHashMap<K, V, S = DefaultHashBuilder, A: MyAllocator = GlobalHeap> {
+    hash_builder: S,
+    table: RawTable<(K, V), A>,
+}
+
+//constructors on the global heap
+impl<K, V> MyHashMap<K, V> {
+    pub fn new() -> MyHashMap<K, V> {
+        MyHashMap::with_hasher_in(DefaultHashBuilder::default(), GlobalHeap)
+    }
+
+    pub fn with_capacity(capacity: usize) -> MyHashMap<K, V> {
+        MyHashMap::with_capacity_and_hasher_in(capacity, DefaultHashBuilder::default(), GlobalHeap)
+    }
+}
+
+impl<K, V, S> MyHashMap<K, V, S> {
+    pub fn with_hasher(hash_builder: S) -> MyHashMap<K, V, S> {
+        MyHashMap::with_hasher_in(hash_builder, GlobalHeap)
+    }
+
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> MyHashMap<K, V, S> {
+        MyHashMap::with_capacity_and_hasher_in(capacity, hash_builder, GlobalHeap)
+    }
+}
+
+//constructors, getters
+impl<K, V, S, A: MyAllocator> MyHashMap<K, V, S, A> {
+    pub fn with_hasher_in(hash_builder: S, alloc: A) -> MyHashMap<K, V, S, A> {
+        MyHashMap { hash_builder, table: RawTable::new_in(alloc) }
+    }
+
+    pub fn with_capacity_and_hasher_in(capacity: usize, hash_builder: S, alloc: A) -> MyHashMap<K, V, S, A> {
+        let mut map = MyHashMap::with_hasher_in(hash_builder, alloc);
+        if capacity != 0 {
+            let buckets = capacity_to_buckets(capacity).expect("capacity overflow");
+            //the table is empty, nothing is hashed
+            if let Err(e) = map.table.resize(buckets, |_| 0) {
+                panic!("{}", e);
+            }
+        }
+        map
+    }
+
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    pub fn allocator(&self) -> &A {
+        &self.table.alloc
+    }
+
+    pub fn len(&self) -> usize {
+        self.table.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.len == 0
+    }
+
+    //how many values fit without growing
+    pub fn capacity(&self) -> usize {
+        self.table.capacity()
+    }
+
+    pub fn iter(&self) -> MyHashMapIter<'_, K, V, A> {
+        MyHashMapIter { table: &self.table, index: 0, remaining: self.table.len }
+    }
+
+    pub fn iter_mut(&mut self) -> MyHashMapIterMut<'_, K, V, A> {
+        let remaining = self.table.len;
+        MyHashMapIterMut { table: &mut self.table, index: 0, remaining }
+    }
+
+    pub fn keys(&self) -> MyHashMapKeys<'_, K, V, A> {
+        MyHashMapKeys { iter: self.iter() }
+    }
+
+    pub fn values(&self) -> MyHashMapValues<'_, K, V, A> {
+        MyHashMapValues { iter: self.iter() }
+    }
+
+    pub fn values_mut(&mut self) -> MyHashMapValuesMut<'_, K, V, A> {
+        MyHashMapValuesMut { iter: self.iter_mut() }
+    }
+}
+
+impl<K, V, S: Default, A: MyAllocator + Default> Default for MyHashMap<K, V, S, A> {
+    fn default() -> Self {
+        MyHashMap::with_hasher_in(S::default(), A::default())
+    }
+}
+
+//lookups
+impl<K: Eq + Hash, V, S: BuildHasher, A: MyAllocator> MyHashMap<K, V, S, A> {
+    fn find<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<usize>
+    where K: Borrow<Q> {
+        let hash = make_hash(&self.hash_builder, key);
+        self.table.find(hash, |(k, _)| key == k.borrow())
+    }
+
+    pub fn get<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<&V>
+    where K: Borrow<Q> {
+        self.get_key_value(key).map(|(_, v)| v)
+    }
+
+    pub fn get_key_value<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<(&K, &V)>
+    where K: Borrow<Q> {
+        let index = self.find(key)?;
+        let (k, v) = unsafe { &*self.table.slot(index) };
+        Some((k, v))
+    }
+
+    pub fn get_mut<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<&mut V>
+    where K: Borrow<Q> {
+        let index = self.find(key)?;
+        unsafe {
+            Some(&mut (*self.table.slot(index)).1)
+        }
+    }
+
+    pub fn contains_key<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> bool
+    where K: Borrow<Q> {
+        self.find(key).is_some()
+    }
+}
+
+//adding values
+impl<K: Eq + Hash, V, S: BuildHasher, A: MyAllocator> MyHashMap<K, V, S, A> {
+    //returns the old value if the key was already in the map (the old key is kept)
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.entry(key) {
+            MyHashMapEntry::Occupied(mut entry) => Some(entry.insert(value)),
+            MyHashMapEntry::Vacant(entry) => {
+                entry.insert(value);
+                None
+            }
+        }
+    }
+
+    pub fn entry(&mut self, key: K) -> MyHashMapEntry<'_, K, V, S, A> {
+        let hash = make_hash(&self.hash_builder, &key);
+        match self.table.find(hash, |(k, _)| *k == key) {
+            Some(index) => MyHashMapEntry::Occupied(MyHashMapOccupiedEntry { map: self, index }),
+            None => MyHashMapEntry::Vacant(MyHashMapVacantEntry { map: self, hash, key }),
+        }
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        if let Err(e) = self.try_reserve(additional) {
+            panic!("{}", e);
+        }
+    }
+
+    //on failure the map is left unchanged
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        let hash_builder = &self.hash_builder;
+        self.table.try_reserve(additional, |(k, _)| make_hash(hash_builder, k))
+    }
+}
+
+//removing values
+impl<K: Eq + Hash, V, S: BuildHasher, A: MyAllocator> MyHashMap<K, V, S, A> {
+    pub fn remove<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<V>
+    where K: Borrow<Q> {
+        self.remove_entry(key).map(|(_, v)| v)
+    }
+
+    pub fn remove_entry<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<(K, V)>
+    where K: Borrow<Q> {
+        let index = self.find(key)?;
+        unsafe {
+            Some(self.table.erase(index))
+        }
+    }
+}
+
+impl<K, V, S, A: MyAllocator> MyHashMap<K, V, S, A> {
+    //keeps the entries for which f returns true
+    pub fn retain<F: FnMut(&K, &mut V) -> bool>(&mut self, mut f: F) {
+        self.table.retain(|(k, v)| f(k, v));
+    }
+
+    //keeps the memory
+    pub fn clear(&mut self) {
+        self.table.clear();
+    }
+
+    //the entries which aren't taken are removed when the iterator is dropped
+    //(if it's forgotten instead, they stay in the map)
+    pub fn drain(&mut self) -> MyHashMapDrain<'_, K, V, A> {
+        let remaining = self.table.len;
+        MyHashMapDrain { table: &mut self.table, index: 0, remaining }
+    }
+}
+
+//ENTRY
+pub enum MyHashMapEntry<'a, K, V, S, A: MyAllocator = GlobalHeap> {
+    Occupied(MyHashMapOccupiedEntry<'a, K, V, S, A>),
+    Vacant(MyHashMapVacantEntry<'a, K, V, S, A>),
+}
+
+pub struct MyHashMapOccupiedEntry<'a, K, V, S, A: MyAllocator = GlobalHeap> {
+    map: &'a mut MyHashMap<K, V, S, A>,
+    index: usize,
+}
+
+pub struct MyHashMapVacantEntry<'a, K, V, S, A: MyAllocator = GlobalHeap> {
+    map: &'a mut MyHashMap<K, V, S, A>,
+    hash: u64,
+    key: K,
+}
+
+impl<'a, K: Eq + Hash, V, S: BuildHasher, A: MyAllocator> MyHashMapEntry<'a, K, V, S, A> {
+    pub fn key(&self) -> &K {
+        match self {
+            MyHashMapEntry::Occupied(entry) => entry.key(),
+            MyHashMapEntry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        self.or_insert_with_key(|_| default())
+    }
+
+    pub fn or_insert_with_key<F: FnOnce(&K) -> V>(self, default: F) -> &'a mut V {
+        match self {
+            MyHashMapEntry::Occupied(entry) => entry.into_mut(),
+            MyHashMapEntry::Vacant(entry) => {
+                let value = default(entry.key());
+                entry.insert(value)
+            }
+        }
+    }
+
+    pub fn or_default(self) -> &'a mut V
+    where V: Default {
+        self.or_insert_with(V::default)
+    }
+
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let MyHashMapEntry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K: Eq + Hash, V, S: BuildHasher, A: MyAllocator> MyHashMapOccupiedEntry<'a, K, V, S, A> {
+    fn pair(&self) -> *mut (K, V) {
+        self.map.table.slot(self.index)
+    }
+
+    pub fn key(&self) -> &K {
+        unsafe {
+            &(*self.pair()).0
+        }
+    }
+
+    pub fn get(&self) -> &V {
+        unsafe {
+            &(*self.pair()).1
+        }
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        unsafe {
+            &mut (*self.pair()).1
+        }
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        unsafe {
+            &mut (*self.pair()).1
+        }
+    }
+
+    //returns the old value
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    pub fn remove_entry(self) -> (K, V) {
+        unsafe {
+            self.map.table.erase(self.index)
+        }
+    }
+}
+
+impl<'a, K: Eq + Hash, V, S: BuildHasher, A: MyAllocator> MyHashMapVacantEntry<'a, K, V, S, A> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    pub fn insert(self, value: V) -> &'a mut V {
+        let MyHashMap { hash_builder, table } = self.map;
+        let index = table.insert(self.hash, (self.key, value), |(k, _)| make_hash(hash_builder, k));
+        unsafe {
+            &mut (*table.slot(index)).1
+        }
+    }
+}
+
+//access
+impl<K: Eq + Hash + Borrow<Q>, Q: Eq + Hash + ?Sized, V, S: BuildHasher, A: MyAllocator> Index<&Q> for MyHashMap<K, V, S, A> {
+    type Output = V;
+
+    fn index(&self, key: &Q) -> &Self::Output {
+        self.get(key).expect("key not found in the map")
+    }
+}
+
+//conversions
+impl<K: Eq + Hash, V, S: BuildHasher + Default, A: MyAllocator + Default> FromIterator<(K, V)> for MyHashMap<K, V, S, A> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = MyHashMap::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Eq + Hash, V, S: BuildHasher, A: MyAllocator, const N: usize> From<[(K, V); N]> for MyHashMap<K, V, S, A>
+where S: Default, A: Default {
+    fn from(entries: [(K, V); N]) -> Self {
+        entries.into_iter().collect()
+    }
+}
+
+//reserves the lower bound of the size hint up front
+impl<K: Eq + Hash, V, S: BuildHasher, A: MyAllocator> Extend<(K, V)> for MyHashMap<K, V, S, A> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<'a, K: Eq + Hash + Copy, V: Copy, S: BuildHasher, A: MyAllocator> Extend<(&'a K, &'a V)> for MyHashMap<K, V, S, A> {
+    fn extend<I: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().map(|(k, v)| (*k, *v)));
+    }
+}
+
+//iterator implementations
+impl<K, V, S, A: MyAllocator> IntoIterator for MyHashMap<K, V, S, A> {
+    type Item = (K, V);
+
+    type IntoIter = MyHashMapIntoIter<K, V, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let remaining = self.table.len;
+        MyHashMapIntoIter { table: self.table, index: 0, remaining }
+    }
+}
+
+impl<'a, K, V, S, A: MyAllocator> IntoIterator for &'a MyHashMap<K, V, S, A> {
+    type Item = (&'a K, &'a V);
+
+    type IntoIter = MyHashMapIter<'a, K, V, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, S, A: MyAllocator> IntoIterator for &'a mut MyHashMap<K, V, S, A> {
+    type Item = (&'a K, &'a mut V);
+
+    type IntoIter = MyHashMapIterMut<'a, K, V, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K: Debug, V: Debug, S, A: MyAllocator> Debug for MyHashMap<K, V, S, A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Eq + Hash + Clone, V: Clone, S: BuildHasher + Clone, A: MyAllocator + Clone> Clone for MyHashMap<K, V, S, A> {
+    fn clone(&self) -> Self {
+        let mut out = MyHashMap::with_capacity_and_hasher_in(self.len(), self.hash_builder.clone(), self.allocator().clone());
+        for (k, v) in self {
+            out.insert(k.clone(), v.clone());
+        }
+        out
+    }
+}
+
+//the same entries, no matter in which order
+impl<K: Eq + Hash, V: PartialEq, S: BuildHasher, A: MyAllocator> PartialEq for MyHashMap<K, V, S, A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+impl<K: Eq + Hash, V: Eq, S: BuildHasher, A: MyAllocator> Eq for MyHashMap<K, V, S, A> {}
+
+//ITERATORS
+//every iterator walks the slots from index, remaining is the number of values it hasn't yielded yet
+
+pub struct MyHashMapIter<'a, K, V, A: MyAllocator = GlobalHeap> {
+    table: &'a RawTable<(K, V), A>,
+    index: usize,
+    remaining: usize,
+}
+
+//derive would require A: Clone
+impl<'a, K, V, A: MyAllocator> Clone for MyHashMapIter<'a, K, V, A> {
+    fn clone(&self) -> Self {
+        MyHashMapIter { table: self.table, index: self.index, remaining: self.remaining }
+    }
+}
+
+impl<'a, K, V, A: MyAllocator> Iterator for MyHashMapIter<'a, K, V, A> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.table.next_full(self.index)?;
+        self.index = index + 1;
+        self.remaining -= 1;
+        let (k, v) = unsafe { &*self.table.slot(index) };
+        Some((k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V, A: MyAllocator> ExactSizeIterator for MyHashMapIter<'a, K, V, A> {}
+
+impl<'a, K, V, A: MyAllocator> FusedIterator for MyHashMapIter<'a, K, V, A> {}
+
+pub struct MyHashMapIterMut<'a, K, V, A: MyAllocator = GlobalHeap> {
+    table: &'a mut RawTable<(K, V), A>,
+    index: usize,
+    remaining: usize,
+}
+
+impl<'a, K, V, A: MyAllocator> Iterator for MyHashMapIterMut<'a, K, V, A> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.table.next_full(self.index)?;
+        self.index = index + 1;
+        self.remaining -= 1;
+        let (k, v) = unsafe { &mut *self.table.slot(index) };
+        Some((k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V, A: MyAllocator> ExactSizeIterator for MyHashMapIterMut<'a, K, V, A> {}
+
+impl<'a, K, V, A: MyAllocator> FusedIterator for MyHashMapIterMut<'a, K, V, A> {}
+
+pub struct MyHashMapKeys<'a, K, V, A: MyAllocator = GlobalHeap> {
+    iter: MyHashMapIter<'a, K, V, A>,
+}
+
+impl<'a, K, V, A: MyAllocator> Clone for MyHashMapKeys<'a, K, V, A> {
+    fn clone(&self) -> Self {
+        MyHashMapKeys { iter: self.iter.clone() }
+    }
+}
+
+impl<'a, K, V, A: MyAllocator> Iterator for MyHashMapKeys<'a, K, V, A> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(k, _)| k)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V, A: MyAllocator> ExactSizeIterator for MyHashMapKeys<'a, K, V, A> {}
+
+impl<'a, K, V, A: MyAllocator> FusedIterator for MyHashMapKeys<'a, K, V, A> {}
+
+pub struct MyHashMapValues<'a, K, V, A: MyAllocator = GlobalHeap> {
+    iter: MyHashMapIter<'a, K, V, A>,
+}
+
+impl<'a, K, V, A: MyAllocator> Clone for MyHashMapValues<'a, K, V, A> {
+    fn clone(&self) -> Self {
+        MyHashMapValues { iter: self.iter.clone() }
+    }
+}
+
+impl<'a, K, V, A: MyAllocator> Iterator for MyHashMapValues<'a, K, V, A> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(_, v)| v)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V, A: MyAllocator> ExactSizeIterator for MyHashMapValues<'a, K, V, A> {}
+
+impl<'a, K, V, A: MyAllocator> FusedIterator for MyHashMapValues<'a, K, V, A> {}
+
+pub struct MyHashMapValuesMut<'a, K, V, A: MyAllocator = GlobalHeap> {
+    iter: MyHashMapIterMut<'a, K, V, A>,
+}
+
+impl<'a, K, V, A: MyAllocator> Iterator for MyHashMapValuesMut<'a, K, V, A> {
+    type Item = &'a mut V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(_, v)| v)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V, A: MyAllocator> ExactSizeIterator for MyHashMapValuesMut<'a, K, V, A> {}
+
+impl<'a, K, V, A: MyAllocator> FusedIterator for MyHashMapValuesMut<'a, K, V, A> {}
+
+//the taken values are marked EMPTY, so the table only drops the rest
+pub struct MyHashMapIntoIter<K, V, A: MyAllocator = GlobalHeap> {
+    table: RawTable<(K, V), A>,
+    index: usize,
+    remaining: usize,
+}
+
+impl<K, V, A: MyAllocator> Iterator for MyHashMapIntoIter<K, V, A> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.table.next_full(self.index)?;
+        self.index = index + 1;
+        self.remaining -= 1;
+        self.table.set_ctrl(index, EMPTY);
+        self.table.len -= 1;
+        unsafe {
+            Some(ptr::read(self.table.slot(index)))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V, A: MyAllocator> ExactSizeIterator for MyHashMapIntoIter<K, V, A> {}
+
+impl<K, V, A: MyAllocator> FusedIterator for MyHashMapIntoIter<K, V, A> {}
+
+//the same as the owning iterator, but the table is cleared (and keeps its memory) when it's dropped
+pub struct MyHashMapDrain<'a, K, V, A: MyAllocator = GlobalHeap> {
+    table: &'a mut RawTable<(K, V), A>,
+    index: usize,
+    remaining: usize,
+}
+
+impl<'a, K, V, A: MyAllocator> Iterator for MyHashMapDrain<'a, K, V, A> {
+    type Item = (K, V);
+
+    //erased like a removed entry (not just marked EMPTY), so the probe chains of the rest stay intact if the drain is forgotten
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.table.next_full(self.index)?;
+        self.index = index + 1;
+        self.remaining -= 1;
+        unsafe {
+            Some(self.table.erase(index))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V, A: MyAllocator> ExactSizeIterator for MyHashMapDrain<'a, K, V, A> {}
+
+impl<'a, K, V, A: MyAllocator> FusedIterator for MyHashMapDrain<'a, K, V, A> {}
+
+impl<'a, K, V, A: MyAllocator> Drop for MyHashMapDrain<'a, K, V, A> {
+    fn drop(&mut self) {
+        self.table.clear();
+    }
+}
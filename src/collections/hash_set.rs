@@ -0,0 +1,258 @@
+use core::{borrow::Borrow, fmt::Debug, hash::{BuildHasher, Hash}, iter::FusedIterator};
+
+use crate::allocator::{AllocError, GlobalHeap, MyAllocator};
+use super::hash_map::{DefaultHashBuilder, MyHashMap, MyHashMapDrain, MyHashMapIntoIter, MyHashMapKeys};
+
+//a MyHashMap without values
+pub struct MyHashSet<T, S = DefaultHashBuilder, A: MyAllocator = GlobalHeap> {
+    map: MyHashMap<T, (), S, A>,
+}
+
+//constructors on the global heap
+impl<T> MyHashSet<T> {
+    pub fn new() -> MyHashSet<T> {
+        MyHashSet { map: MyHashMap::new() }
+    }
+
+    pub fn with_capacity(capacity: usize) -> MyHashSet<T> {
+        MyHashSet { map: MyHashMap::with_capacity(capacity) }
+    }
+}
+
+impl<T, S> MyHashSet<T, S> {
+    pub fn with_hasher(hash_builder: S) -> MyHashSet<T, S> {
+        MyHashSet { map: MyHashMap::with_hasher(hash_builder) }
+    }
+
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> MyHashSet<T, S> {
+        MyHashSet { map: MyHashMap::with_capacity_and_hasher(capacity, hash_builder) }
+    }
+}
+
+//constructors, getters
+impl<T, S, A: MyAllocator> MyHashSet<T, S, A> {
+    pub fn with_hasher_in(hash_builder: S, alloc: A) -> MyHashSet<T, S, A> {
+        MyHashSet { map: MyHashMap::with_hasher_in(hash_builder, alloc) }
+    }
+
+    pub fn with_capacity_and_hasher_in(capacity: usize, hash_builder: S, alloc: A) -> MyHashSet<T, S, A> {
+        MyHashSet { map: MyHashMap::with_capacity_and_hasher_in(capacity, hash_builder, alloc) }
+    }
+
+    pub fn hasher(&self) -> &S {
+        self.map.hasher()
+    }
+
+    pub fn allocator(&self) -> &A {
+        self.map.allocator()
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.map.capacity()
+    }
+
+    pub fn iter(&self) -> MyHashSetIter<'_, T, A> {
+        MyHashSetIter { keys: self.map.keys() }
+    }
+
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        self.map.retain(|value, _| f(value));
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    pub fn drain(&mut self) -> MyHashSetDrain<'_, T, A> {
+        MyHashSetDrain { drain: self.map.drain() }
+    }
+}
+
+impl<T, S: Default, A: MyAllocator + Default> Default for MyHashSet<T, S, A> {
+    fn default() -> Self {
+        MyHashSet { map: MyHashMap::default() }
+    }
+}
+
+impl<T: Eq + Hash, S: BuildHasher, A: MyAllocator> MyHashSet<T, S, A> {
+    //returns false if the value was already in the set (the old one is kept)
+    pub fn insert(&mut self, value: T) -> bool {
+        self.map.insert(value, ()).is_none()
+    }
+
+    pub fn contains<Q: Hash + Eq + ?Sized>(&self, value: &Q) -> bool
+    where T: Borrow<Q> {
+        self.map.contains_key(value)
+    }
+
+    pub fn get<Q: Hash + Eq + ?Sized>(&self, value: &Q) -> Option<&T>
+    where T: Borrow<Q> {
+        self.map.get_key_value(value).map(|(v, _)| v)
+    }
+
+    pub fn remove<Q: Hash + Eq + ?Sized>(&mut self, value: &Q) -> bool
+    where T: Borrow<Q> {
+        self.map.remove(value).is_some()
+    }
+
+    pub fn take<Q: Hash + Eq + ?Sized>(&mut self, value: &Q) -> Option<T>
+    where T: Borrow<Q> {
+        self.map.remove_entry(value).map(|(v, _)| v)
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.map.reserve(additional);
+    }
+
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        self.map.try_reserve(additional)
+    }
+
+    pub fn is_subset<B: MyAllocator>(&self, other: &MyHashSet<T, S, B>) -> bool {
+        self.len() <= other.len() && self.iter().all(|value| other.contains(value))
+    }
+}
+
+//conversions
+impl<T: Eq + Hash, S: BuildHasher + Default, A: MyAllocator + Default> FromIterator<T> for MyHashSet<T, S, A> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = MyHashSet::default();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T: Eq + Hash, S: BuildHasher + Default, A: MyAllocator + Default, const N: usize> From<[T; N]> for MyHashSet<T, S, A> {
+    fn from(values: [T; N]) -> Self {
+        values.into_iter().collect()
+    }
+}
+
+impl<T: Eq + Hash, S: BuildHasher, A: MyAllocator> Extend<T> for MyHashSet<T, S, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.map.extend(iter.into_iter().map(|value| (value, ())));
+    }
+}
+
+impl<'a, T: Eq + Hash + Copy, S: BuildHasher, A: MyAllocator> Extend<&'a T> for MyHashSet<T, S, A> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+//iterator implementations
+impl<T, S, A: MyAllocator> IntoIterator for MyHashSet<T, S, A> {
+    type Item = T;
+
+    type IntoIter = MyHashSetIntoIter<T, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        MyHashSetIntoIter { iter: self.map.into_iter() }
+    }
+}
+
+impl<'a, T, S, A: MyAllocator> IntoIterator for &'a MyHashSet<T, S, A> {
+    type Item = &'a T;
+
+    type IntoIter = MyHashSetIter<'a, T, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: Debug, S, A: MyAllocator> Debug for MyHashSet<T, S, A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Eq + Hash + Clone, S: BuildHasher + Clone, A: MyAllocator + Clone> Clone for MyHashSet<T, S, A> {
+    fn clone(&self) -> Self {
+        MyHashSet { map: self.map.clone() }
+    }
+}
+
+impl<T: Eq + Hash, S: BuildHasher, A: MyAllocator> PartialEq for MyHashSet<T, S, A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+    }
+}
+
+impl<T: Eq + Hash, S: BuildHasher, A: MyAllocator> Eq for MyHashSet<T, S, A> {}
+
+//ITERATORS
+
+pub struct MyHashSetIter<'a, T, A: MyAllocator = GlobalHeap> {
+    keys: MyHashMapKeys<'a, T, (), A>,
+}
+
+impl<'a, T, A: MyAllocator> Clone for MyHashSetIter<'a, T, A> {
+    fn clone(&self) -> Self {
+        MyHashSetIter { keys: self.keys.clone() }
+    }
+}
+
+impl<'a, T, A: MyAllocator> Iterator for MyHashSetIter<'a, T, A> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.keys.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.keys.size_hint()
+    }
+}
+
+impl<'a, T, A: MyAllocator> ExactSizeIterator for MyHashSetIter<'a, T, A> {}
+
+impl<'a, T, A: MyAllocator> FusedIterator for MyHashSetIter<'a, T, A> {}
+
+pub struct MyHashSetIntoIter<T, A: MyAllocator = GlobalHeap> {
+    iter: MyHashMapIntoIter<T, (), A>,
+}
+
+impl<T, A: MyAllocator> Iterator for MyHashSetIntoIter<T, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(value, _)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<T, A: MyAllocator> ExactSizeIterator for MyHashSetIntoIter<T, A> {}
+
+impl<T, A: MyAllocator> FusedIterator for MyHashSetIntoIter<T, A> {}
+
+pub struct MyHashSetDrain<'a, T, A: MyAllocator = GlobalHeap> {
+    drain: MyHashMapDrain<'a, T, (), A>,
+}
+
+impl<'a, T, A: MyAllocator> Iterator for MyHashSetDrain<'a, T, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.drain.next().map(|(value, _)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.drain.size_hint()
+    }
+}
+
+impl<'a, T, A: MyAllocator> ExactSizeIterator for MyHashSetDrain<'a, T, A> {}
+
+impl<'a, T, A: MyAllocator> FusedIterator for MyHashSetDrain<'a, T, A> {}
@@ -0,0 +1,588 @@
+use core::{alloc::Layout, fmt::Debug, iter::FusedIterator, marker::PhantomData, mem, ptr::{self, NonNull}};
+
+use crate::allocator::{allocate_or_panic, GlobalHeap, MyAllocator};
+
+struct Node<T> {
+    next: Option<NonNull<Node<T>>>,
+    prev: Option<NonNull<Node<T>>>,
+    value: T,
+}
+
+//a doubly linked list, every node is its own allocation from A (the global heap by default)
+pub struct MyLinkedList<T, A: MyAllocator = GlobalHeap> {
+    head: Option<NonNull<Node<T>>>,
+    tail: Option<NonNull<Node<T>>>,
+    len: usize,
+    alloc: A,
+    marker: PhantomData<Node<T>>,
+}
+
+unsafe impl<T: Send, A: MyAllocator + Send> Send for MyLinkedList<T, A> {}
+unsafe impl<T: Sync, A: MyAllocator + Sync> Sync for MyLinkedList<T, A> {}
+
+//constructors on the global heap
+impl<T> MyLinkedList<T> {
+    pub const fn new() -> MyLinkedList<T> {
+        MyLinkedList::new_in(GlobalHeap)
+    }
+}
+
+//constructors, getters
+impl<T, A: MyAllocator> MyLinkedList<T, A> {
+    pub const fn new_in(alloc: A) -> MyLinkedList<T, A> {
+        MyLinkedList { head: None, tail: None, len: 0, alloc, marker: PhantomData }
+    }
+
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.head.map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.head.map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.tail.map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.tail.map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    pub fn contains(&self, value: &T) -> bool
+    where T: PartialEq {
+        self.iter().any(|v| v == value)
+    }
+
+    pub fn iter(&self) -> MyLinkedListIter<'_, T> {
+        MyLinkedListIter { head: self.head, tail: self.tail, len: self.len, marker: PhantomData }
+    }
+
+    pub fn iter_mut(&mut self) -> MyLinkedListIterMut<'_, T> {
+        MyLinkedListIterMut { head: self.head, tail: self.tail, len: self.len, marker: PhantomData }
+    }
+
+    //a cursor on the first value (on the ghost position if the list is empty)
+    pub fn cursor_front_mut(&mut self) -> MyCursorMut<'_, T, A> {
+        MyCursorMut { current: self.head, index: 0, list: self }
+    }
+
+    //a cursor on the last value (on the ghost position if the list is empty)
+    pub fn cursor_back_mut(&mut self) -> MyCursorMut<'_, T, A> {
+        let index = self.len.saturating_sub(1);
+        MyCursorMut { current: self.tail, index, list: self }
+    }
+}
+
+impl<T, A: MyAllocator + Default> Default for MyLinkedList<T, A> {
+    fn default() -> Self {
+        MyLinkedList::new_in(A::default())
+    }
+}
+
+//node helpers
+impl<T, A: MyAllocator> MyLinkedList<T, A> {
+    fn new_node(&self, value: T) -> NonNull<Node<T>> {
+        let node = allocate_or_panic(&self.alloc, Layout::new::<Node<T>>()) as *mut Node<T>;
+        unsafe {
+            ptr::write(node, Node { next: None, prev: None, value });
+            NonNull::new_unchecked(node)
+        }
+    }
+
+    //frees the node and returns its value
+    //SAFETY: the node must be unlinked and come from this list's allocator
+    unsafe fn free_node(&self, node: NonNull<Node<T>>) -> T {
+        unsafe {
+            let value = ptr::read(&(*node.as_ptr()).value);
+            self.alloc.deallocate(node.cast(), Layout::new::<Node<T>>());
+            value
+        }
+    }
+
+    //links node in between prev and next (None for the ends of the list)
+    //SAFETY: prev and next must be neighbours in this list
+    unsafe fn link(&mut self, node: NonNull<Node<T>>, prev: Option<NonNull<Node<T>>>, next: Option<NonNull<Node<T>>>) {
+        unsafe {
+            (*node.as_ptr()).prev = prev;
+            (*node.as_ptr()).next = next;
+            match prev {
+                Some(prev) => (*prev.as_ptr()).next = Some(node),
+                None => self.head = Some(node),
+            }
+            match next {
+                Some(next) => (*next.as_ptr()).prev = Some(node),
+                None => self.tail = Some(node),
+            }
+        }
+        self.len += 1;
+    }
+
+    //SAFETY: node must be in this list
+    unsafe fn unlink(&mut self, node: NonNull<Node<T>>) {
+        unsafe {
+            let Node { prev, next, .. } = *node.as_ptr();
+            match prev {
+                Some(prev) => (*prev.as_ptr()).next = next,
+                None => self.head = next,
+            }
+            match next {
+                Some(next) => (*next.as_ptr()).prev = prev,
+                None => self.tail = prev,
+            }
+        }
+        self.len -= 1;
+    }
+}
+
+//adding and removing values
+impl<T, A: MyAllocator> MyLinkedList<T, A> {
+    pub fn push_front(&mut self, value: T) {
+        let node = self.new_node(value);
+        unsafe {
+            self.link(node, None, self.head);
+        }
+    }
+
+    pub fn push_back(&mut self, value: T) {
+        let node = self.new_node(value);
+        unsafe {
+            self.link(node, self.tail, None);
+        }
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        let node = self.head?;
+        unsafe {
+            self.unlink(node);
+            Some(self.free_node(node))
+        }
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        let node = self.tail?;
+        unsafe {
+            self.unlink(node);
+            Some(self.free_node(node))
+        }
+    }
+
+    //drops the values and frees the nodes
+    pub fn clear(&mut self) {
+        //if a drop panics, the guard drops the rest
+        struct DropGuard<'a, T, A: MyAllocator>(&'a mut MyLinkedList<T, A>);
+
+        impl<'a, T, A: MyAllocator> Drop for DropGuard<'a, T, A> {
+            fn drop(&mut self) {
+                while self.0.pop_front().is_some() {}
+            }
+        }
+
+        let guard = DropGuard(self);
+        while guard.0.pop_front().is_some() {}
+        mem::forget(guard);
+    }
+
+    //moves all nodes of other to the end of self in O(1), without allocating
+    //the nodes keep their memory, so both lists have to use the same allocator (or clones of it)
+    pub fn append(&mut self, other: &mut MyLinkedList<T, A>) {
+        let Some(other_head) = other.head.take() else {
+            return;
+        };
+        let other_tail = other.tail.take();
+        match self.tail {
+            Some(tail) => unsafe {
+                (*tail.as_ptr()).next = Some(other_head);
+                (*other_head.as_ptr()).prev = Some(tail);
+            },
+            None => self.head = Some(other_head),
+        }
+        self.tail = other_tail;
+        self.len += mem::replace(&mut other.len, 0);
+    }
+
+    //moves the values from index at on into a new list, walks from the nearer end
+    pub fn split_off(&mut self, at: usize) -> MyLinkedList<T, A>
+    where A: Clone {
+        assert!(at <= self.len, "split index (is {}) should be <= len (is {})", at, self.len);
+
+        let mut other = MyLinkedList::new_in(self.alloc.clone());
+        if at == self.len {
+            return other;
+        }
+        if at == 0 {
+            mem::swap(&mut self.head, &mut other.head);
+            mem::swap(&mut self.tail, &mut other.tail);
+            mem::swap(&mut self.len, &mut other.len);
+            return other;
+        }
+
+        //the last node which stays
+        let last = if at <= self.len / 2 {
+            let mut node = self.head;
+            for _ in 1..at {
+                node = unsafe { (*node.unwrap().as_ptr()).next };
+            }
+            node
+        } else {
+            let mut node = self.tail;
+            for _ in at..self.len {
+                node = unsafe { (*node.unwrap().as_ptr()).prev };
+            }
+            node
+        }
+        .unwrap();
+
+        unsafe {
+            let first = (*last.as_ptr()).next.take().unwrap();
+            (*first.as_ptr()).prev = None;
+            other.head = Some(first);
+            other.tail = self.tail.replace(last);
+        }
+        other.len = self.len - at;
+        self.len = at;
+        other
+    }
+}
+
+impl<T, A: MyAllocator> Drop for MyLinkedList<T, A> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+//CURSOR
+/*
+    MyCursorMut:
+    points at a value of the list or at the ghost position between the last and the first value,
+    from the ghost move_next goes to the front and move_prev to the back
+ */
+pub struct MyCursorMut<'a, T, A: MyAllocator = GlobalHeap> {
+    list: &'a mut MyLinkedList<T, A>,
+    current: Option<NonNull<Node<T>>>,
+    //the index of current, list.len on the ghost position
+    index: usize,
+}
+
+impl<'a, T, A: MyAllocator> MyCursorMut<'a, T, A> {
+    //None on the ghost position
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    pub fn move_next(&mut self) {
+        match self.current {
+            Some(node) => unsafe {
+                self.current = (*node.as_ptr()).next;
+                self.index += 1;
+            },
+            None => {
+                self.current = self.list.head;
+                self.index = 0;
+            }
+        }
+    }
+
+    pub fn move_prev(&mut self) {
+        match self.current {
+            Some(node) => unsafe {
+                self.current = (*node.as_ptr()).prev;
+                self.index = self.index.checked_sub(1).unwrap_or(self.list.len);
+            },
+            None => {
+                self.current = self.list.tail;
+                self.index = self.list.len.saturating_sub(1);
+            }
+        }
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        self.current.map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        let next = match self.current {
+            Some(node) => unsafe { (*node.as_ptr()).next },
+            None => self.list.head,
+        };
+        next.map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        let prev = match self.current {
+            Some(node) => unsafe { (*node.as_ptr()).prev },
+            None => self.list.tail,
+        };
+        prev.map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    //on the ghost position the value becomes the new front
+    pub fn insert_after(&mut self, value: T) {
+        let node = self.list.new_node(value);
+        unsafe {
+            match self.current {
+                Some(current) => self.list.link(node, Some(current), (*current.as_ptr()).next),
+                None => {
+                    self.list.link(node, None, self.list.head);
+                    self.index += 1;
+                }
+            }
+        }
+    }
+
+    //on the ghost position the value becomes the new back
+    pub fn insert_before(&mut self, value: T) {
+        let node = self.list.new_node(value);
+        unsafe {
+            match self.current {
+                Some(current) => self.list.link(node, (*current.as_ptr()).prev, Some(current)),
+                None => self.list.link(node, self.list.tail, None),
+            }
+        }
+        self.index += 1;
+    }
+
+    //removes the current value and moves to the next one, does nothing on the ghost position
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.current?;
+        unsafe {
+            self.current = (*node.as_ptr()).next;
+            self.list.unlink(node);
+            Some(self.list.free_node(node))
+        }
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.list.front_mut()
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.list.back_mut()
+    }
+}
+
+//conversions
+impl<T, A: MyAllocator + Default> FromIterator<T> for MyLinkedList<T, A> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = MyLinkedList::default();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for MyLinkedList<T> {
+    fn from(values: [T; N]) -> Self {
+        values.into_iter().collect()
+    }
+}
+
+impl<T, A: MyAllocator> Extend<T> for MyLinkedList<T, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push_back(value);
+        }
+    }
+}
+
+impl<'a, T: Copy + 'a, A: MyAllocator> Extend<&'a T> for MyLinkedList<T, A> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+//iterator implementations
+impl<T, A: MyAllocator> IntoIterator for MyLinkedList<T, A> {
+    type Item = T;
+
+    type IntoIter = MyLinkedListIntoIter<T, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        MyLinkedListIntoIter { list: self }
+    }
+}
+
+impl<'a, T, A: MyAllocator> IntoIterator for &'a MyLinkedList<T, A> {
+    type Item = &'a T;
+
+    type IntoIter = MyLinkedListIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, A: MyAllocator> IntoIterator for &'a mut MyLinkedList<T, A> {
+    type Item = &'a mut T;
+
+    type IntoIter = MyLinkedListIterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T: Debug, A: MyAllocator> Debug for MyLinkedList<T, A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Clone, A: MyAllocator + Clone> Clone for MyLinkedList<T, A> {
+    fn clone(&self) -> Self {
+        let mut out = MyLinkedList::new_in(self.alloc.clone());
+        out.extend(self.iter().cloned());
+        out
+    }
+}
+
+impl<T: PartialEq, A: MyAllocator> PartialEq for MyLinkedList<T, A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq, A: MyAllocator> Eq for MyLinkedList<T, A> {}
+
+//ITERATORS
+//head and tail are the next nodes from each end, len says how many are left between them
+
+pub struct MyLinkedListIter<'a, T> {
+    head: Option<NonNull<Node<T>>>,
+    tail: Option<NonNull<Node<T>>>,
+    len: usize,
+    marker: PhantomData<&'a Node<T>>,
+}
+
+unsafe impl<'a, T: Sync> Send for MyLinkedListIter<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for MyLinkedListIter<'a, T> {}
+
+impl<'a, T> Clone for MyLinkedListIter<'a, T> {
+    fn clone(&self) -> Self {
+        MyLinkedListIter { head: self.head, tail: self.tail, len: self.len, marker: PhantomData }
+    }
+}
+
+impl<'a, T> Iterator for MyLinkedListIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.head?;
+        self.len -= 1;
+        unsafe {
+            self.head = (*node.as_ptr()).next;
+            Some(&(*node.as_ptr()).value)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for MyLinkedListIter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.tail?;
+        self.len -= 1;
+        unsafe {
+            self.tail = (*node.as_ptr()).prev;
+            Some(&(*node.as_ptr()).value)
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for MyLinkedListIter<'a, T> {}
+
+impl<'a, T> FusedIterator for MyLinkedListIter<'a, T> {}
+
+pub struct MyLinkedListIterMut<'a, T> {
+    head: Option<NonNull<Node<T>>>,
+    tail: Option<NonNull<Node<T>>>,
+    len: usize,
+    marker: PhantomData<&'a mut Node<T>>,
+}
+
+unsafe impl<'a, T: Send> Send for MyLinkedListIterMut<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for MyLinkedListIterMut<'a, T> {}
+
+impl<'a, T> Iterator for MyLinkedListIterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.head?;
+        self.len -= 1;
+        unsafe {
+            self.head = (*node.as_ptr()).next;
+            Some(&mut (*node.as_ptr()).value)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for MyLinkedListIterMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let node = self.tail?;
+        self.len -= 1;
+        unsafe {
+            self.tail = (*node.as_ptr()).prev;
+            Some(&mut (*node.as_ptr()).value)
+        }
+    }
+}
+
+impl<'a, T> ExactSizeIterator for MyLinkedListIterMut<'a, T> {}
+
+impl<'a, T> FusedIterator for MyLinkedListIterMut<'a, T> {}
+
+//pops from the list, which frees the rest when the iterator is dropped
+pub struct MyLinkedListIntoIter<T, A: MyAllocator = GlobalHeap> {
+    list: MyLinkedList<T, A>,
+}
+
+impl<T, A: MyAllocator> Iterator for MyLinkedListIntoIter<T, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.list.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.list.len, Some(self.list.len))
+    }
+}
+
+impl<T, A: MyAllocator> DoubleEndedIterator for MyLinkedListIntoIter<T, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.list.pop_back()
+    }
+}
+
+impl<T, A: MyAllocator> ExactSizeIterator for MyLinkedListIntoIter<T, A> {}
+
+impl<T, A: MyAllocator> FusedIterator for MyLinkedListIntoIter<T, A> {}
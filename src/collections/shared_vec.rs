@@ -0,0 +1,126 @@
+use core::{marker::PhantomData, ptr};
+
+use crate::shared::SharedHeap;
+
+//a vec inside a SharedHeap, it only stores offsets, so it can be placed in the region itself
+//and used by every process which mapped it, the heap has to be passed to every operation
+//T: Copy, so the values can't own memory (a pointer in them would only be valid in one process)
+#[repr(C)]
+pub struct SharedVec<T: Copy> {
+    offset: usize,
+    len: usize,
+    cap: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Copy> SharedVec<T> {
+    pub const fn new() -> SharedVec<T> {
+        SharedVec { offset: 0, len: 0, cap: 0, _marker: PhantomData }
+    }
+
+    pub fn with_capacity(heap: &SharedHeap, capacity: usize) -> SharedVec<T> {
+        let mut v = SharedVec::new();
+        if capacity != 0 {
+            v.reallocate(heap, capacity);
+        }
+        v
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn as_slice<'a>(&'a self, heap: &'a SharedHeap) -> &'a [T] {
+        if self.cap == 0 {
+            return &[];
+        }
+        unsafe {
+            core::slice::from_raw_parts(heap.at::<T>(self.offset), self.len)
+        }
+    }
+
+    pub fn as_slice_mut<'a>(&'a mut self, heap: &'a SharedHeap) -> &'a mut [T] {
+        if self.cap == 0 {
+            return &mut [];
+        }
+        unsafe {
+            core::slice::from_raw_parts_mut(heap.at::<T>(self.offset), self.len)
+        }
+    }
+
+    pub fn get(&self, heap: &SharedHeap, index: usize) -> Option<T> {
+        self.as_slice(heap).get(index).copied()
+    }
+
+    pub fn push(&mut self, heap: &SharedHeap, value: T) {
+        if self.len == self.cap {
+            let new_cap = if self.cap == 0 { 4 } else { self.cap * 2 };
+            self.reallocate(heap, new_cap);
+        }
+
+        unsafe {
+            ptr::write(heap.at::<T>(self.offset).add(self.len), value);
+        }
+        self.len += 1;
+    }
+
+    pub fn pop(&mut self, heap: &SharedHeap) -> Option<T> {
+        if self.len == 0 {
+            None
+        } else {
+            self.len -= 1;
+            unsafe {
+                Some(ptr::read(heap.at::<T>(self.offset).add(self.len)))
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    //gives the buffer back to the heap, the vec is empty afterwards
+    pub fn free(&mut self, heap: &SharedHeap) {
+        if self.cap != 0 {
+            unsafe {
+                heap.free(self.offset);
+            }
+        }
+        *self = SharedVec::new();
+    }
+}
+
+//local helper functions
+impl<T: Copy> SharedVec<T> {
+    fn reallocate(&mut self, heap: &SharedHeap, new_cap: usize) {
+        let size = new_cap.checked_mul(size_of::<T>()).expect("capacity overflow");
+        let offset = match heap.alloc(size, align_of::<T>()) {
+            Some(offset) => offset,
+            None => panic!("unable to allocate, not enough free space"),
+        };
+
+        if self.cap != 0 {
+            unsafe {
+                ptr::copy_nonoverlapping(heap.at::<T>(self.offset), heap.at::<T>(offset), self.len);
+                heap.free(self.offset);
+            }
+        }
+
+        self.offset = offset;
+        self.cap = new_cap;
+    }
+}
+
+impl<T: Copy> Default for SharedVec<T> {
+    fn default() -> Self {
+        SharedVec::new()
+    }
+}
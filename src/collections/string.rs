@@ -1,23 +1,44 @@
 use core::{str, fmt::Display, ops::{Add, AddAssign, Deref, DerefMut}};
 
 use super::vec::MyVec;
+use crate::allocator::{GlobalHeap, MyAllocator};
 
-pub struct MyString {
-    vec: MyVec<u8>
+pub struct MyString<A: MyAllocator = GlobalHeap> {
+    vec: MyVec<u8, A>
 }
 
-//constructors, getters
+//constructors on the global heap
 impl MyString {
     pub fn new() -> MyString {
-        MyString { vec: MyVec::new() }
+        MyString::new_in(GlobalHeap)
     }
 
     pub fn with_capacity(capacity: usize) -> MyString {
-        MyString { vec: MyVec::with_capacity(capacity) }
+        MyString::with_capacity_in(capacity, GlobalHeap)
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> MyString {
-        MyString { vec: MyVec::from_slice(s.as_bytes()) }
+        MyString::from_str_in(s, GlobalHeap)
+    }
+}
+
+//constructors, getters
+impl<A: MyAllocator> MyString<A> {
+    pub fn new_in(alloc: A) -> MyString<A> {
+        MyString { vec: MyVec::new_in(alloc) }
+    }
+
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> MyString<A> {
+        MyString { vec: MyVec::with_capacity_in(capacity, alloc) }
+    }
+
+    pub fn from_str_in(s: &str, alloc: A) -> MyString<A> {
+        MyString { vec: MyVec::from_slice_in(s.as_bytes(), alloc) }
+    }
+
+    pub fn allocator(&self) -> &A {
+        self.vec.allocator()
     }
 
     pub fn as_str(&self) -> &str {
@@ -33,8 +54,33 @@ impl MyString {
     }
 }
 
+//capacity
+impl<A: MyAllocator> MyString<A> {
+    pub fn capacity(&self) -> usize {
+        self.vec.capacity()
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.vec.reserve(additional);
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.vec.shrink_to_fit();
+    }
+
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.vec.shrink_to(min_capacity);
+    }
+}
+
+impl<A: MyAllocator + Default> Default for MyString<A> {
+    fn default() -> Self {
+        MyString::new_in(A::default())
+    }
+}
+
 //adding elements
-impl MyString {
+impl<A: MyAllocator> MyString<A> {
     pub fn push(&mut self, c: char) {
         let mut buf = [0u8; 4];
         let bytes = c.encode_utf8(&mut buf).as_bytes();
@@ -59,7 +105,7 @@ impl MyString {
 }
 
 //removing elements
-impl MyString {
+impl<A: MyAllocator> MyString<A> {
     pub fn pop(&mut self) -> Option<char> {
         if let Some((idx, c)) = self.char_indices().next_back() {
             self.vec.drain(idx..);
@@ -90,7 +136,7 @@ impl MyString {
     
 }
 
-impl Add<&str> for MyString {
+impl<A: MyAllocator> Add<&str> for MyString<A> {
     type Output = Self;
 
     fn add(mut self, rhs: &str) -> Self::Output {
@@ -99,13 +145,13 @@ impl Add<&str> for MyString {
     }
 }
 
-impl AddAssign<&str> for MyString {
+impl<A: MyAllocator> AddAssign<&str> for MyString<A> {
     fn add_assign(&mut self, rhs: &str) {
         self.push_str(rhs);
     }
 }
 
-impl Deref for MyString {
+impl<A: MyAllocator> Deref for MyString<A> {
     type Target = str;
 
     fn deref(&self) -> &Self::Target {
@@ -113,14 +159,14 @@ impl Deref for MyString {
     }
 }
 
-impl DerefMut for MyString {
+impl<A: MyAllocator> DerefMut for MyString<A> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         self.as_str_mut()
     }
 }
 
-impl Display for MyString {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl<A: MyAllocator> Display for MyString<A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self.as_str())
     }
 }
\ No newline at end of file
@@ -1,35 +1,89 @@
-use core::{fmt::Debug, ops::{Index, IndexMut, RangeBounds}, ptr, marker::{Send, Sync}};
-
-use crate::manager::{my_alloc, my_free};
-
-pub struct MyVec<T> {
+use core::{alloc::Layout, borrow::{Borrow, BorrowMut}, cmp::Ordering, fmt::Debug, hash::{Hash, Hasher}, iter::FusedIterator, mem::ManuallyDrop, ops::{Bound, Deref, DerefMut, Index, IndexMut, RangeBounds}, ptr::{self, NonNull}, marker::{Send, Sync}, slice::SliceIndex};
+
+use crate::allocator::{allocate_or_panic, AllocError, GlobalHeap, MyAllocator};
+use crate::smart_pointers::boxed::MyBox;
+
+//an empty vec and a vec of zero sized values never touch the allocator: their ptr is dangling (but aligned),
+//and a vec of zero sized values has usize::MAX capacity
+
+/*
+    Panic safety:
+    if a Clone, Drop or closure of the user panics inside a method, the vec is left valid,
+    no value is dropped twice and no value or buffer is leaked:
+    - len is lowered before values are dropped (clear, truncate, drop), the rest of them are still dropped
+    - values are added one by one and len grows after each of them (clone, extend, resize, extend_from_slice)
+    - retain and dedup keep the values which weren't checked yet, in their order
+    - drain, splice and the owning iterator still put the tail back or free the buffer
+    a value whose Drop panicked counts as dropped
+ */
+pub struct MyVec<T, A: MyAllocator = GlobalHeap> {
     ptr: *mut T,
     len: usize,
     cap: usize,
+    alloc: A,
 }
 
 //marker traits
-unsafe impl<T: Send> Send for MyVec<T> {}
-unsafe impl<T: Sync> Sync for MyVec<T> {}
+unsafe impl<T: Send, A: MyAllocator + Send> Send for MyVec<T, A> {}
+unsafe impl<T: Sync, A: MyAllocator + Sync> Sync for MyVec<T, A> {}
 
-//constructors, getters
+//constructors on the global heap
 impl<T> MyVec<T> {
     pub fn new() -> MyVec<T> {
-        MyVec { ptr: ptr::null_mut(), len: 0, cap: 0 }
+        MyVec::new_in(GlobalHeap)
     }
     
     pub fn with_capacity(capacity: usize) -> MyVec<T> {
-        let ptr = my_alloc(size_of::<T>() * capacity, align_of::<T>()) as *mut T;
+        MyVec::with_capacity_in(capacity, GlobalHeap)
+    }
 
-        MyVec { ptr, len: 0, cap: capacity }
+    pub fn from_slice(slice: &[T]) -> MyVec<T>
+    where T: Clone {
+        MyVec::from_slice_in(slice, GlobalHeap)
     }
 
-    pub fn from_slice(slice: &[T]) -> MyVec<T> {
-        let mut v = MyVec::with_capacity(slice.len());
+    pub fn try_with_capacity(capacity: usize) -> Result<MyVec<T>, AllocError> {
+        MyVec::try_with_capacity_in(capacity, GlobalHeap)
+    }
+}
+
+//constructors, getters
+impl<T, A: MyAllocator> MyVec<T, A> {
+    pub fn new_in(alloc: A) -> MyVec<T, A> {
+        MyVec { ptr: NonNull::dangling().as_ptr(), len: 0, cap: Self::EMPTY_CAP, alloc }
+    }
+
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> MyVec<T, A> {
+        if capacity == 0 || size_of::<T>() == 0 {
+            return MyVec::new_in(alloc);
+        }
+
+        let layout = Self::layout(capacity);
+        let ptr = allocate_or_panic(&alloc, layout) as *mut T;
+        let cap = unsafe {
+            Self::granted_cap(&alloc, NonNull::new_unchecked(ptr as *mut u8), layout, capacity)
+        };
+
+        MyVec { ptr, len: 0, cap, alloc }
+    }
+
+    pub fn try_with_capacity_in(capacity: usize, alloc: A) -> Result<MyVec<T, A>, AllocError> {
+        let mut v = MyVec::new_in(alloc);
+        v.try_reserve(capacity)?;
+        Ok(v)
+    }
+
+    pub fn from_slice_in(slice: &[T], alloc: A) -> MyVec<T, A>
+    where T: Clone {
+        let mut v = MyVec::with_capacity_in(slice.len(), alloc);
         v.extend_from_slice(slice);
         v
     }
 
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
     pub fn len(&self) -> usize {
         self.len
     }
@@ -44,27 +98,102 @@ impl<T> MyVec<T> {
 
     pub fn as_slice(&self) -> &[T] {
         unsafe {
-            std::slice::from_raw_parts(self.ptr, self.len)
+            core::slice::from_raw_parts(self.ptr, self.len)
         }
     }
 
-    pub fn as_slice_mut(&self) -> &mut [T] {
+    pub fn as_slice_mut(&mut self) -> &mut [T] {
         unsafe {
-            std::slice::from_raw_parts_mut(self.ptr, self.len)
+            core::slice::from_raw_parts_mut(self.ptr, self.len)
         }
     }
 
-    pub fn iter<'a>(&'a self) -> MyVecIter<'a, T> {
-        MyVecIter { vec: self, index: 0 }
+    //the pointer to the buffer, dangling if there is no buffer
+    pub fn as_ptr(&self) -> *const T {
+        self.ptr
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        self.ptr
     }
 
-    pub fn iter_mut<'a>(&'a mut self) -> MyVecIterMut<'a, T> {
-        MyVecIterMut { vec: self, index: 0 }
+    /// # Safety
+    /// new_len must be <= capacity and the values in [0, new_len) must be initialized,
+    /// the values past new_len are not dropped
+    pub unsafe fn set_len(&mut self, new_len: usize) {
+        self.len = new_len;
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.as_slice().first()
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.as_slice().last()
+    }
+
+    //index can be a position or a range, the same as for slices
+    pub fn get<I: SliceIndex<[T]>>(&self, index: I) -> Option<&I::Output> {
+        self.as_slice().get(index)
+    }
+
+    pub fn get_mut<I: SliceIndex<[T]>>(&mut self, index: I) -> Option<&mut I::Output> {
+        self.as_slice_mut().get_mut(index)
+    }
+
+    pub fn contains(&self, value: &T) -> bool
+    where T: PartialEq {
+        self.as_slice().contains(value)
+    }
+
+    pub fn iter<'a>(&'a self) -> MyVecIter<'a, T, A> {
+        MyVecIter { vec: self, index: 0, end: self.len }
+    }
+
+    pub fn iter_mut<'a>(&'a mut self) -> MyVecIterMut<'a, T, A> {
+        let end = self.len;
+        MyVecIterMut { vec: self, index: 0, end }
+    }
+}
+
+impl<T, A: MyAllocator + Default> Default for MyVec<T, A> {
+    fn default() -> Self {
+        MyVec::new_in(A::default())
     }
 }
 
 //adding values
-impl<T> MyVec<T> {
+impl<T, A: MyAllocator> MyVec<T, A> {
+    pub fn reserve(&mut self, additional: usize) {
+        if let Err(e) = self.try_reserve(additional) {
+            panic!("{}", e);
+        }
+    }
+
+    //on failure the vec is left unchanged
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        let needed = self.len.checked_add(additional).ok_or(AllocError)?;
+        if needed > self.cap {
+            self.try_reallocate(Some(needed))?;
+        }
+        Ok(())
+    }
+
+    //the same as reserve, but without the growth rule (the allocator may still give more)
+    pub fn reserve_exact(&mut self, additional: usize) {
+        if let Err(e) = self.try_reserve_exact(additional) {
+            panic!("{}", e);
+        }
+    }
+
+    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), AllocError> {
+        let needed = self.len.checked_add(additional).ok_or(AllocError)?;
+        if needed > self.cap {
+            self.try_grow_to(needed)?;
+        }
+        Ok(())
+    }
+
     pub fn push(&mut self, value: T) {
         if self.len == self.cap {
             self.reallocate(None);
@@ -92,23 +221,16 @@ impl<T> MyVec<T> {
         self.len += 1;
     }
 
-    pub fn insert_slice(&mut self, index: usize, slice: &[T]) {
+    //the clones are added to the end and rotated into place, so there is never a gap of uninitialized values
+    pub fn insert_slice(&mut self, index: usize, slice: &[T])
+    where T: Clone {
         assert!(index <= self.len);
 
-        let sum_len = self.len + slice.len();
-        if sum_len > self.cap {
-            self.reallocate(Some(sum_len));
-        }
-
-        unsafe {
-            ptr::copy(self.ptr.add(index), self.ptr.add(index + slice.len()), self.len - index);
-            ptr::copy_nonoverlapping(slice.as_ptr(), self.ptr.add(index), slice.len());
-        }
-
-        self.len = sum_len;
+        self.extend_from_slice(slice);
+        self.as_slice_mut()[index..].rotate_right(slice.len());
     }
 
-    pub fn append(&mut self, other: MyVec<T>) {
+    pub fn append(&mut self, mut other: MyVec<T, A>) {
         let sum_len = self.len + other.len;
         if sum_len > self.cap {
             self.reallocate(Some(sum_len));
@@ -119,33 +241,79 @@ impl<T> MyVec<T> {
         }
         
         self.len += other.len;
+        //the values are moved out, other only has to free its buffer
+        other.len = 0;
     }
 
-    pub fn extend_from_slice(&mut self, slice: &[T]) {
-        let sum_len = self.len + slice.len();
-        if sum_len > self.cap {
-            self.reallocate(Some(sum_len));
+    pub fn resize(&mut self, new_len: usize, value: T)
+    where T: Clone {
+        if new_len <= self.len {
+            self.truncate(new_len);
+            return;
         }
 
-        unsafe {
-            ptr::copy_nonoverlapping(slice.as_ptr(), self.ptr.add(self.len), slice.len());
+        self.reserve(new_len - self.len);
+        while self.len + 1 < new_len {
+            self.push(value.clone());
+        }
+        self.push(value);
+    }
+
+    pub fn resize_with<F: FnMut() -> T>(&mut self, new_len: usize, mut f: F) {
+        if new_len <= self.len {
+            self.truncate(new_len);
+            return;
+        }
+
+        self.reserve(new_len - self.len);
+        while self.len < new_len {
+            self.push(f());
+        }
+    }
+
+    //clones the values of the range to the end
+    pub fn extend_from_within<R>(&mut self, range: R)
+    where R: RangeBounds<usize>, T: Clone {
+        let (start, end) = Self::range_of(range, self.len);
+        self.reserve(end - start);
+
+        for i in start..end {
+            //the clone is written before len grows, so a panic in clone leaves the vec valid
+            unsafe {
+                let value = (*self.ptr.add(i)).clone();
+                ptr::write(self.ptr.add(self.len), value);
+            }
+            self.len += 1;
         }
+    }
+
+    //removes the range and puts the values of replace_with in its place,
+    //the removed values are yielded by the returned iterator, the replacement happens when it's dropped
+    pub fn splice<R, I>(&mut self, range: R, replace_with: I) -> MySplice<'_, I::IntoIter, A>
+    where R: RangeBounds<usize>, I: IntoIterator<Item = T> {
+        MySplice { drain: self.drain(range), replace_with: replace_with.into_iter() }
+    }
+
+    pub fn extend_from_slice(&mut self, slice: &[T])
+    where T: Clone {
+        self.reserve(slice.len());
 
-        self.len = sum_len;
+        for value in slice {
+            //len grows with every clone, so a panic in clone leaves the vec valid
+            unsafe {
+                ptr::write(self.ptr.add(self.len), value.clone());
+            }
+            self.len += 1;
+        }
     }
 
 
 }
 
 //removing values
-impl<T> MyVec<T> {
+impl<T, A: MyAllocator> MyVec<T, A> {
     pub fn clear(&mut self) {
-        unsafe {
-            for i in 0..self.len {
-                drop(ptr::read(self.ptr.add(i)));
-            }
-        }
-        self.len = 0;
+        self.truncate(0);
     }
 
     pub fn pop(&mut self) -> Option<T> {
@@ -175,50 +343,222 @@ impl<T> MyVec<T> {
         removed
     }
 
-    pub fn truncate(&mut self, len: usize) {
-        assert!(len <= self.len);
+    //the last value takes the place of the removed one, so it's O(1) but the order changes
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        assert!(index < self.len, "swap_remove index out of bounds");
+
+        unsafe {
+            let removed = ptr::read(self.ptr.add(index));
+            self.len -= 1;
+            ptr::copy(self.ptr.add(self.len), self.ptr.add(index), 1);
+            removed
+        }
+    }
+
+    //the values from at are moved into a new vec with the same allocator
+    pub fn split_off(&mut self, at: usize) -> MyVec<T, A>
+    where A: Clone {
+        assert!(at <= self.len, "split_off index out of bounds");
+
+        let other_len = self.len - at;
+        let mut other = MyVec::with_capacity_in(other_len, self.alloc.clone());
         unsafe {
-            for i in len..self.len {
-                drop(ptr::read(self.ptr.add(i)));
+            ptr::copy_nonoverlapping(self.ptr.add(at), other.ptr, other_len);
+        }
+        self.len = at;
+        other.len = other_len;
+        other
+    }
+
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        self.retain_mut(|value| f(value));
+    }
+
+    //keeps the values for which f returns true, in their original order
+    pub fn retain_mut<F: FnMut(&mut T) -> bool>(&mut self, mut f: F) {
+        let len = self.len;
+        let mut gaps = Gaps { vec: self, read: 0, write: 0, len };
+
+        while gaps.read < len {
+            unsafe {
+                let current = gaps.vec.ptr.add(gaps.read);
+                if f(&mut *current) {
+                    if gaps.read != gaps.write {
+                        ptr::copy_nonoverlapping(current, gaps.vec.ptr.add(gaps.write), 1);
+                    }
+                    gaps.write += 1;
+                    gaps.read += 1;
+                } else {
+                    gaps.read += 1;
+                    ptr::drop_in_place(current);
+                }
             }
         }
+    }
+
+    pub fn dedup(&mut self)
+    where T: PartialEq {
+        self.dedup_by(|a, b| a == b);
+    }
+
+    pub fn dedup_by_key<K: PartialEq, F: FnMut(&mut T) -> K>(&mut self, mut key: F) {
+        self.dedup_by(|a, b| key(a) == key(b));
+    }
+
+    //removes the consecutive values for which same_bucket(value, previous kept value) returns true
+    pub fn dedup_by<F: FnMut(&mut T, &mut T) -> bool>(&mut self, mut same_bucket: F) {
+        let len = self.len;
+        if len <= 1 {
+            return;
+        }
+
+        let mut gaps = Gaps { vec: self, read: 1, write: 1, len };
+
+        while gaps.read < len {
+            unsafe {
+                let current = gaps.vec.ptr.add(gaps.read);
+                let previous = gaps.vec.ptr.add(gaps.write - 1);
+                if same_bucket(&mut *current, &mut *previous) {
+                    gaps.read += 1;
+                    ptr::drop_in_place(current);
+                } else {
+                    if gaps.read != gaps.write {
+                        ptr::copy_nonoverlapping(current, gaps.vec.ptr.add(gaps.write), 1);
+                    }
+                    gaps.write += 1;
+                    gaps.read += 1;
+                }
+            }
+        }
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        assert!(len <= self.len);
+
+        //if a drop panics, the slice's drop glue still drops the rest
+        let removed = ptr::slice_from_raw_parts_mut(unsafe { self.ptr.add(len) }, self.len - len);
         self.len = len;
+        unsafe {
+            ptr::drop_in_place(removed);
+        }
     }
 
-    pub fn drain<'a, R>(&'a mut self, range: R) -> MyDrain<'a, T>
+    pub fn drain<'a, R>(&'a mut self, range: R) -> MyDrain<'a, T, A>
     where R: RangeBounds<usize> {
-        let len = self.len;
+        let (start, end) = Self::range_of(range, self.len);
+
+        let tail = self.len - end;
+        self.len = start;
+
+        MyDrain { vec: self, index: start, back: end, end, tail }
+    }
+}
+
+//returning capacity
+impl<T, A: MyAllocator> MyVec<T, A> {
+    pub fn shrink_to_fit(&mut self) {
+        self.shrink_to(0);
+    }
+
+    //the capacity becomes at least max(len, min_capacity), the allocator may still leave some slack
+    //if the allocator can't shrink the buffer, it's kept as it is
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        let _ = self.try_shrink_to(min_capacity);
+    }
+}
+
+//conversions
+impl<T, A: MyAllocator> MyVec<T, A> {
+    //the buffer and the allocator are never freed
+    pub fn leak<'a>(self) -> &'a mut [T]
+    where A: 'a {
+        let vec = ManuallyDrop::new(self);
+        unsafe {
+            core::slice::from_raw_parts_mut(vec.ptr, vec.len)
+        }
+    }
+
+    //the buffer is shrunk to len first, so the box can free it with the layout of the slice
+    pub fn into_boxed_slice(mut self) -> MyBox<[T], A> {
+        if let Err(e) = self.try_shrink_to(self.len) {
+            panic!("{}", e);
+        }
+
+        let vec = ManuallyDrop::new(self);
+        unsafe {
+            MyBox::from_raw_in(ptr::slice_from_raw_parts_mut(vec.ptr, vec.len), ptr::read(&vec.alloc))
+        }
+    }
+}
+
+//local helper functions
+impl<T, A: MyAllocator> MyVec<T, A> {
+    fn try_shrink_to(&mut self, min_capacity: usize) -> Result<(), AllocError> {
+        let target = self.len.max(min_capacity);
+        if target >= self.cap || size_of::<T>() == 0 {
+            return Ok(());
+        }
+
+        if target == 0 {
+            unsafe {
+                self.free_buffer();
+            }
+            self.ptr = NonNull::dangling().as_ptr();
+            self.cap = 0;
+            return Ok(());
+        }
+
+        let new_layout = Self::layout(target);
+        let new_ptr = unsafe {
+            self.alloc.shrink(NonNull::new_unchecked(self.ptr as *mut u8), Self::layout(self.cap), new_layout)?
+        };
+
+        self.ptr = new_ptr.as_ptr() as *mut T;
+        self.cap = unsafe {
+            Self::granted_cap(&self.alloc, new_ptr, new_layout, target)
+        };
+        Ok(())
+    }
+
+    //the start and the end of a range of the values, panics if it's out of bounds
+    fn range_of<R: RangeBounds<usize>>(range: R, len: usize) -> (usize, usize) {
         let start = match range.start_bound() {
-            std::ops::Bound::Included(&s) => s,
-            std::ops::Bound::Excluded(&s) => s + 1,
-            std::ops::Bound::Unbounded => 0,
+            Bound::Included(&s) => s,
+            Bound::Excluded(&s) => s.checked_add(1).expect("range start overflow"),
+            Bound::Unbounded => 0,
         };
         let end = match range.end_bound() {
-            std::ops::Bound::Included(&e) => e + 1,
-            std::ops::Bound::Excluded(&e) => e,
-            std::ops::Bound::Unbounded => len,
+            Bound::Included(&e) => e.checked_add(1).expect("range end overflow"),
+            Bound::Excluded(&e) => e,
+            Bound::Unbounded => len,
         };
 
-        assert!(start <= end && end <= len);
+        assert!(start <= end && end <= len, "range out of bounds");
+        (start, end)
+    }
 
-        let tail = self.len - end;
-        self.len = start;
+    const EMPTY_CAP: usize = if size_of::<T>() == 0 { usize::MAX } else { 0 };
 
-        MyDrain { vec: self, index: start, end, tail }
+    //whether a vec with this capacity has a buffer from the allocator
+    fn owns_buffer(cap: usize) -> bool {
+        cap != 0 && size_of::<T>() != 0
+    }
+
+    fn layout(cap: usize) -> Layout {
+        Layout::array::<T>(cap).expect("capacity overflow")
     }
-}
 
-//local helper functions
-impl<T> MyVec<T> {
     fn reallocate(&mut self, to: Option<usize>) {
-        if self.cap == 0 {
-            self.cap = 4;
-            self.ptr = my_alloc(4 * size_of::<T>(), align_of::<T>()) as *mut T;
-            return;
+        if let Err(e) = self.try_reallocate(to) {
+            panic!("{}", e);
         }
+    }
 
+    fn try_reallocate(&mut self, to: Option<usize>) -> Result<(), AllocError> {
         let mut new_cap = {
-            if self.cap <= 16 {
+            if self.cap == 0 {
+                4
+            } else if self.cap <= 16 {
                 self.cap * 2
             } else {
                 self.cap + self.cap / 2
@@ -229,104 +569,273 @@ impl<T> MyVec<T> {
             new_cap = c;
         }
 
-        let new_ptr = my_alloc(new_cap * size_of::<T>(), align_of::<T>()) as *mut T;
+        self.try_grow_to(new_cap)
+    }
 
-        unsafe {
-            ptr::copy_nonoverlapping(self.ptr, new_ptr, self.cap);
-            my_free(self.ptr);
+    //moves the values into a buffer for new_cap values, the whole old buffer is copied (see MyDrain::move_tail)
+    fn try_grow_to(&mut self, new_cap: usize) -> Result<(), AllocError> {
+        //zero sized values always fit, only usize::MAX of them can't be exceeded
+        if size_of::<T>() == 0 {
+            return Err(AllocError);
         }
 
-        self.ptr = new_ptr;
-        self.cap = new_cap;
+        let new_layout = Layout::array::<T>(new_cap).map_err(|_| AllocError)?;
+
+        let new_ptr = if self.cap == 0 {
+            self.alloc.allocate(new_layout)?
+        } else {
+            unsafe {
+                self.alloc.grow(NonNull::new_unchecked(self.ptr as *mut u8), Self::layout(self.cap), new_layout)?
+            }
+        };
+
+        self.ptr = new_ptr.as_ptr() as *mut T;
+        self.cap = unsafe {
+            Self::granted_cap(&self.alloc, new_ptr, new_layout, new_cap)
+        };
+        Ok(())
     }
-}
 
-//access
-impl<T> Index<usize> for MyVec<T>  {
-    type Output = T;
+    //the allocator may hand out more bytes than asked for, they become extra capacity for free
+    //SAFETY: ptr must have been allocated by alloc with layout
+    unsafe fn granted_cap(alloc: &A, ptr: NonNull<u8>, layout: Layout, requested: usize) -> usize {
+        if size_of::<T>() == 0 {
+            return requested;
+        }
 
-    fn index(&self, index: usize) -> &Self::Output {
-        assert!(index < self.len);
+        let usable = unsafe {
+            alloc.usable_size(ptr, layout)
+        };
+        (usable / size_of::<T>()).max(requested)
+    }
 
-        unsafe {
-            & *self.ptr.add(index)
+    //frees the buffer without touching the values
+    unsafe fn free_buffer(&mut self) {
+        if Self::owns_buffer(self.cap) {
+            unsafe {
+                self.alloc.deallocate(NonNull::new_unchecked(self.ptr as *mut u8), Self::layout(self.cap));
+            }
         }
     }
 }
 
+//access, index can be a position or a range
+impl<T, A: MyAllocator, I: SliceIndex<[T]>> Index<I> for MyVec<T, A>  {
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &Self::Output {
+        &self.as_slice()[index]
+    }
+}
+
 
 //mutable access
-impl<T> IndexMut<usize> for MyVec<T>  {
-    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
-        assert!(index < self.len);
+impl<T, A: MyAllocator, I: SliceIndex<[T]>> IndexMut<I> for MyVec<T, A>  {
+    fn index_mut(&mut self, index: I) -> &mut Self::Output {
+        &mut self.as_slice_mut()[index]
+    }
+}
 
-        unsafe {
-            &mut *self.ptr.add(index)
+//a vec can be used wherever a slice is expected
+impl<T, A: MyAllocator> Deref for MyVec<T, A> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl<T, A: MyAllocator> DerefMut for MyVec<T, A> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_slice_mut()
+    }
+}
+
+impl<T, A: MyAllocator> AsRef<[T]> for MyVec<T, A> {
+    fn as_ref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, A: MyAllocator> AsMut<[T]> for MyVec<T, A> {
+    fn as_mut(&mut self) -> &mut [T] {
+        self.as_slice_mut()
+    }
+}
+
+//the hash, equality and ordering are the slice's, so a &[T] can look up a MyVec key in a map
+impl<T, A: MyAllocator> Borrow<[T]> for MyVec<T, A> {
+    fn borrow(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, A: MyAllocator> BorrowMut<[T]> for MyVec<T, A> {
+    fn borrow_mut(&mut self) -> &mut [T] {
+        self.as_slice_mut()
+    }
+}
+
+//comparisons
+impl<T: PartialEq<U>, U, A: MyAllocator, B: MyAllocator> PartialEq<MyVec<U, B>> for MyVec<T, A> {
+    fn eq(&self, other: &MyVec<U, B>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: PartialEq<U>, U, A: MyAllocator> PartialEq<[U]> for MyVec<T, A> {
+    fn eq(&self, other: &[U]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl<T: PartialEq<U>, U, A: MyAllocator> PartialEq<&[U]> for MyVec<T, A> {
+    fn eq(&self, other: &&[U]) -> bool {
+        self.as_slice() == *other
+    }
+}
+
+impl<T: PartialEq<U>, U, A: MyAllocator, const N: usize> PartialEq<[U; N]> for MyVec<T, A> {
+    fn eq(&self, other: &[U; N]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl<T: Eq, A: MyAllocator> Eq for MyVec<T, A> {}
+
+//lexicographic, like slices
+impl<T: PartialOrd, A: MyAllocator> PartialOrd for MyVec<T, A> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.as_slice().partial_cmp(other.as_slice())
+    }
+}
+
+impl<T: Ord, A: MyAllocator> Ord for MyVec<T, A> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_slice().cmp(other.as_slice())
+    }
+}
+
+impl<T: Hash, A: MyAllocator> Hash for MyVec<T, A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state);
+    }
+}
+
+//conversions
+impl<T: Clone> From<&[T]> for MyVec<T> {
+    fn from(slice: &[T]) -> Self {
+        MyVec::from_slice(slice)
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for MyVec<T> {
+    fn from(array: [T; N]) -> Self {
+        let mut v = MyVec::with_capacity(N);
+        for value in array {
+            v.push(value);
         }
+        v
+    }
+}
+
+impl<T, A: MyAllocator + Default> FromIterator<T> for MyVec<T, A> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut v = MyVec::new_in(A::default());
+        v.extend(iter);
+        v
+    }
+}
+
+//reserves the lower bound of the size hint up front
+impl<T, A: MyAllocator> Extend<T> for MyVec<T, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<'a, T: Copy + 'a, A: MyAllocator> Extend<&'a T> for MyVec<T, A> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
     }
 }
 
 //iterator implementations
-impl<T> IntoIterator for MyVec<T> {
+impl<T, A: MyAllocator> IntoIterator for MyVec<T, A> {
     type Item = T;
 
-    type IntoIter = MyVecIntoIter<T>;
+    type IntoIter = MyVecIntoIter<T, A>;
 
     fn into_iter(self) -> Self::IntoIter {
-        let ptr = self.ptr;
-        let index = 0;
-        let len = self.len;
-
-        std::mem::forget(self);
+        let vec = ManuallyDrop::new(self);
 
         MyVecIntoIter {
-            ptr,
-            index,
-            len,
+            ptr: vec.ptr,
+            index: 0,
+            end: vec.len,
+            cap: vec.cap,
+            alloc: unsafe { ptr::read(&vec.alloc) },
         }
     }
 }
 
-impl<'a, T> IntoIterator for &'a MyVec<T> {
+impl<'a, T, A: MyAllocator> IntoIterator for &'a MyVec<T, A> {
     type Item = &'a T;
 
-    type IntoIter = MyVecIter<'a, T>;
+    type IntoIter = MyVecIter<'a, T, A>;
 
     fn into_iter(self) -> Self::IntoIter {
         MyVecIter {
             vec: self,
-            index: 0
+            index: 0,
+            end: self.len,
         }
     }
 }
 
-impl<'a, T> IntoIterator for &'a mut MyVec<T> {
+impl<'a, T, A: MyAllocator> IntoIterator for &'a mut MyVec<T, A> {
     type Item = &'a mut T;
 
-    type IntoIter = MyVecIterMut<'a, T>;
+    type IntoIter = MyVecIterMut<'a, T, A>;
 
     fn into_iter(self) -> Self::IntoIter {
+        let end = self.len;
         MyVecIterMut {
             vec: self,
-            index: 0
+            index: 0,
+            end,
         }
     }
 }
 
 //free memory when vec goes out of scope
-impl<T> Drop for MyVec<T> {
+impl<T, A: MyAllocator> Drop for MyVec<T, A> {
     fn drop(&mut self) {
-        unsafe {
-            for i in 0..self.len {
-                ptr::drop_in_place(self.ptr.add(i));
+        struct FreeBuffer<'a, T, A: MyAllocator>(&'a mut MyVec<T, A>);
+
+        impl<'a, T, A: MyAllocator> Drop for FreeBuffer<'a, T, A> {
+            fn drop(&mut self) {
+                unsafe {
+                    self.0.free_buffer();
+                }
             }
-            my_free(self.ptr);
+        }
+
+        //the buffer is freed by the guard, even if a value's drop panics
+        let values: *mut [T] = self.as_slice_mut();
+        let _guard = FreeBuffer(self);
+        unsafe {
+            ptr::drop_in_place(values);
         }
     }
 }
 
-impl<T: Debug> Debug for MyVec<T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl<T: Debug, A: MyAllocator> Debug for MyVec<T, A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         //f.debug_struct("MyVec").field("ptr", &self.ptr).field("len", &self.len).field("cap", &self.cap).finish()
         let mut list = f.debug_list();
         for item in self {
@@ -336,9 +845,9 @@ impl<T: Debug> Debug for MyVec<T> {
     }
 }
 
-impl<T: Clone> Clone for MyVec<T> {
+impl<T: Clone, A: MyAllocator + Clone> Clone for MyVec<T, A> {
     fn clone(&self) -> Self {
-        let mut out = MyVec::with_capacity(self.cap);
+        let mut out = MyVec::with_capacity_in(self.cap, self.alloc.clone());
         for item in self {
             out.push(item.clone());
         }
@@ -346,18 +855,54 @@ impl<T: Clone> Clone for MyVec<T> {
     }
 }
 
+//creates a MyVec on the global heap, the same forms as vec![]:
+//my_vec![], my_vec![value; n] (value has to be Clone), my_vec![a, b, c]
+#[macro_export]
+macro_rules! my_vec {
+    () => {
+        $crate::MyVec::new()
+    };
+    ($value:expr; $n:expr) => {{
+        let mut v = $crate::MyVec::new();
+        v.resize($n, $value);
+        v
+    }};
+    ($($x:expr),+ $(,)?) => {
+        $crate::MyVec::from([$($x),+])
+    };
+}
+
 //ITERATORS
-pub struct MyVecIntoIter<T> {
+//every iterator yields the values in [index, end), from the front with next and from the back with next_back
+
+pub struct MyVecIntoIter<T, A: MyAllocator = GlobalHeap> {
     ptr: *mut T,
     index: usize,
-    len: usize,
+    end: usize,
+    cap: usize,
+    alloc: A,
+}
+
+impl<T, A: MyAllocator> MyVecIntoIter<T, A> {
+    //the values which weren't yielded yet
+    pub fn as_slice(&self) -> &[T] {
+        unsafe {
+            core::slice::from_raw_parts(self.ptr.add(self.index), self.end - self.index)
+        }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe {
+            core::slice::from_raw_parts_mut(self.ptr.add(self.index), self.end - self.index)
+        }
+    }
 }
 
-impl<T> Iterator for MyVecIntoIter<T> {
+impl<T, A: MyAllocator> Iterator for MyVecIntoIter<T, A> {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.index < self.len {
+        if self.index < self.end {
             let item = unsafe {
                 let ptr = self.ptr.add(self.index);
                 ptr::read(ptr)
@@ -370,32 +915,78 @@ impl<T> Iterator for MyVecIntoIter<T> {
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let remaining = self.len.saturating_sub(self.index);
+        let remaining = self.end - self.index;
         (remaining, Some(remaining))
     }
 }
 
-impl<T> Drop for MyVecIntoIter<T> {
+impl<T, A: MyAllocator> DoubleEndedIterator for MyVecIntoIter<T, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.index < self.end {
+            self.end -= 1;
+            unsafe {
+                Some(ptr::read(self.ptr.add(self.end)))
+            }
+        } else {
+            None
+        }
+    }
+}
+
+impl<T, A: MyAllocator> ExactSizeIterator for MyVecIntoIter<T, A> {}
+
+impl<T, A: MyAllocator> FusedIterator for MyVecIntoIter<T, A> {}
+
+impl<T, A: MyAllocator> Drop for MyVecIntoIter<T, A> {
     fn drop(&mut self) {
-        unsafe {
-            for i in self.index..self.len {
-                ptr::drop_in_place(self.ptr.add(i));
+        struct FreeBuffer<'a, T, A: MyAllocator>(&'a mut MyVecIntoIter<T, A>);
+
+        impl<'a, T, A: MyAllocator> Drop for FreeBuffer<'a, T, A> {
+            fn drop(&mut self) {
+                let iter = &mut *self.0;
+                if MyVec::<T, A>::owns_buffer(iter.cap) {
+                    unsafe {
+                        iter.alloc.deallocate(NonNull::new_unchecked(iter.ptr as *mut u8), MyVec::<T, A>::layout(iter.cap));
+                    }
+                }
             }
-            my_free(self.ptr);
+        }
+
+        //the buffer is freed by the guard, even if a value's drop panics
+        let remaining: *mut [T] = self.as_mut_slice();
+        self.index = self.end;
+        let _guard = FreeBuffer(self);
+        unsafe {
+            ptr::drop_in_place(remaining);
         }
     }
 }
 
-pub struct MyVecIter<'a, T> {
-    vec: &'a MyVec<T>,
+pub struct MyVecIter<'a, T, A: MyAllocator = GlobalHeap> {
+    vec: &'a MyVec<T, A>,
     index: usize,
+    end: usize,
+}
+
+impl<'a, T, A: MyAllocator> MyVecIter<'a, T, A> {
+    //the values which weren't yielded yet, borrowed for as long as the vec
+    pub fn as_slice(&self) -> &'a [T] {
+        &self.vec.as_slice()[self.index..self.end]
+    }
+}
+
+//derive would require A: Clone
+impl<'a, T, A: MyAllocator> Clone for MyVecIter<'a, T, A> {
+    fn clone(&self) -> Self {
+        MyVecIter { vec: self.vec, index: self.index, end: self.end }
+    }
 }
 
-impl<'a, T> Iterator for MyVecIter<'a, T> {
+impl<'a, T, A: MyAllocator> Iterator for MyVecIter<'a, T, A> {
     type Item = &'a T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.index < self.vec.len {
+        if self.index < self.end {
             let out = unsafe {
                 & *self.vec.ptr.add(self.index)
             };
@@ -407,21 +998,48 @@ impl<'a, T> Iterator for MyVecIter<'a, T> {
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let remaining = self.vec.len.saturating_sub(self.index);
+        let remaining = self.end - self.index;
         (remaining, Some(remaining))
     }
 }
 
-pub struct MyVecIterMut<'a, T> {
-    vec: &'a mut MyVec<T>,
+impl<'a, T, A: MyAllocator> DoubleEndedIterator for MyVecIter<'a, T, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.index < self.end {
+            self.end -= 1;
+            unsafe {
+                Some(& *self.vec.ptr.add(self.end))
+            }
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T, A: MyAllocator> ExactSizeIterator for MyVecIter<'a, T, A> {}
+
+impl<'a, T, A: MyAllocator> FusedIterator for MyVecIter<'a, T, A> {}
+
+pub struct MyVecIterMut<'a, T, A: MyAllocator = GlobalHeap> {
+    vec: &'a mut MyVec<T, A>,
     index: usize,
+    end: usize,
+}
+
+impl<'a, T, A: MyAllocator> MyVecIterMut<'a, T, A> {
+    //the values which weren't yielded yet, the yielded ones may be borrowed mutably, so they're left out
+    pub fn as_slice(&self) -> &[T] {
+        unsafe {
+            core::slice::from_raw_parts(self.vec.ptr.add(self.index), self.end - self.index)
+        }
+    }
 }
 
-impl<'a, T> Iterator for MyVecIterMut<'a, T> {
+impl<'a, T, A: MyAllocator> Iterator for MyVecIterMut<'a, T, A> {
     type Item = &'a mut T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.index < self.vec.len {
+        if self.index < self.end {
             let out = unsafe {
                 &mut *self.vec.ptr.add(self.index)
             };
@@ -433,23 +1051,51 @@ impl<'a, T> Iterator for MyVecIterMut<'a, T> {
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let remaining = self.vec.len.saturating_sub(self.index);
+        let remaining = self.end - self.index;
         (remaining, Some(remaining))
     }
 }
 
-pub struct MyDrain<'a, T> {
-    vec: &'a mut MyVec<T>,
+impl<'a, T, A: MyAllocator> DoubleEndedIterator for MyVecIterMut<'a, T, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.index < self.end {
+            self.end -= 1;
+            unsafe {
+                Some(&mut *self.vec.ptr.add(self.end))
+            }
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T, A: MyAllocator> ExactSizeIterator for MyVecIterMut<'a, T, A> {}
+
+impl<'a, T, A: MyAllocator> FusedIterator for MyVecIterMut<'a, T, A> {}
+
+//the drained values are [index, back), end is where the tail starts, it stays in place while iterating
+pub struct MyDrain<'a, T, A: MyAllocator = GlobalHeap> {
+    vec: &'a mut MyVec<T, A>,
     index: usize,
+    back: usize,
     end: usize,
     tail: usize,
 }
 
-impl<'a, T> Iterator for MyDrain<'a, T>  {
+impl<'a, T, A: MyAllocator> MyDrain<'a, T, A> {
+    //the values which weren't yielded yet
+    pub fn as_slice(&self) -> &[T] {
+        unsafe {
+            core::slice::from_raw_parts(self.vec.ptr.add(self.index), self.back - self.index)
+        }
+    }
+}
+
+impl<'a, T, A: MyAllocator> Iterator for MyDrain<'a, T, A>  {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.index < self.end {
+        if self.index < self.back {
             let out = unsafe {
                 ptr::read(self.vec.ptr.add(self.index))
             };
@@ -461,25 +1107,133 @@ impl<'a, T> Iterator for MyDrain<'a, T>  {
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let remaining = self.end.saturating_sub(self.index);
+        let remaining = self.back - self.index;
         (remaining, Some(remaining))
     }
 }
 
-impl<'a, T> Drop for MyDrain<'a, T> {
-    fn drop(&mut self) {
-        for i in self.index..self.end {
+impl<'a, T, A: MyAllocator> DoubleEndedIterator for MyDrain<'a, T, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.index < self.back {
+            self.back -= 1;
             unsafe {
-                ptr::drop_in_place(self.vec.ptr.add(i));
+                Some(ptr::read(self.vec.ptr.add(self.back)))
+            }
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T, A: MyAllocator> ExactSizeIterator for MyDrain<'a, T, A> {}
+
+impl<'a, T, A: MyAllocator> FusedIterator for MyDrain<'a, T, A> {}
+
+impl<'a, T, A: MyAllocator> Drop for MyDrain<'a, T, A> {
+    fn drop(&mut self) {
+        struct MoveTail<'r, 'a, T, A: MyAllocator>(&'r mut MyDrain<'a, T, A>);
+
+        impl<'r, 'a, T, A: MyAllocator> Drop for MoveTail<'r, 'a, T, A> {
+            fn drop(&mut self) {
+                let drain = &mut *self.0;
+                if drain.end != drain.vec.len {
+                    unsafe {
+                        ptr::copy(drain.vec.ptr.add(drain.end), drain.vec.ptr.add(drain.vec.len), drain.tail);
+                    }
+                }
+
+                drain.vec.len += drain.tail;
             }
         }
 
-        if self.end != self.vec.len { 
+        //the tail is put back by the guard, even if a drained value's drop panics
+        let remaining = ptr::slice_from_raw_parts_mut(unsafe { self.vec.ptr.add(self.index) }, self.back - self.index);
+        self.index = self.back;
+        let _guard = MoveTail(self);
+        unsafe {
+            ptr::drop_in_place(remaining);
+        }
+    }
+}
+
+impl<'a, T, A: MyAllocator> MyDrain<'a, T, A> {
+    //makes room for extra values between the filled part and the tail
+    fn move_tail(&mut self, extra: usize) {
+        let needed = (self.end + self.tail).checked_add(extra).expect("capacity overflow");
+        if needed > self.vec.cap {
+            self.vec.reallocate(Some(needed));
+        }
+
+        unsafe {
+            ptr::copy(self.vec.ptr.add(self.end), self.vec.ptr.add(self.end + extra), self.tail);
+        }
+        self.end += extra;
+    }
+}
+
+pub struct MySplice<'a, I: Iterator, A: MyAllocator = GlobalHeap> {
+    drain: MyDrain<'a, I::Item, A>,
+    replace_with: I,
+}
+
+impl<'a, I: Iterator, A: MyAllocator> Iterator for MySplice<'a, I, A> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.drain.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.drain.size_hint()
+    }
+}
+
+//the removed values which weren't taken are dropped, than the new values fill the gap,
+//if there are more of them, the tail is moved to make room, the drain puts the tail back in place
+impl<'a, I: Iterator, A: MyAllocator> Drop for MySplice<'a, I, A> {
+    fn drop(&mut self) {
+        self.drain.by_ref().for_each(drop);
+
+        loop {
+            let drain = &mut self.drain;
+            while drain.vec.len < drain.end {
+                let Some(value) = self.replace_with.next() else {
+                    return;
+                };
+                unsafe {
+                    ptr::write(drain.vec.ptr.add(drain.vec.len), value);
+                }
+                drain.vec.len += 1;
+            }
+
+            let Some(value) = self.replace_with.next() else {
+                return;
+            };
+            drain.move_tail(self.replace_with.size_hint().0.saturating_add(1));
             unsafe {
-                ptr::copy(self.vec.ptr.add(self.end), self.vec.ptr.add(self.vec.len), self.tail);
-            } 
+                ptr::write(drain.vec.ptr.add(drain.vec.len), value);
+            }
+            drain.vec.len += 1;
         }
-        
-        self.vec.len += self.tail;
     }
-}
\ No newline at end of file
+}
+
+//used by retain and dedup: the values in [write, read) are already moved or dropped,
+//when it's dropped (also if the closure panics) the unchecked values are moved down to close the gap
+struct Gaps<'a, T, A: MyAllocator> {
+    vec: &'a mut MyVec<T, A>,
+    read: usize,
+    write: usize,
+    len: usize,
+}
+
+impl<'a, T, A: MyAllocator> Drop for Gaps<'a, T, A> {
+    fn drop(&mut self) {
+        unsafe {
+            if self.read != self.write {
+                ptr::copy(self.vec.ptr.add(self.read), self.vec.ptr.add(self.write), self.len - self.read);
+            }
+        }
+        self.vec.len = self.write + self.len - self.read;
+    }
+}
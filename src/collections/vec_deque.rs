@@ -0,0 +1,547 @@
+use core::{fmt::Debug, iter::FusedIterator, mem::{ManuallyDrop, MaybeUninit}, ops::{Index, IndexMut, Range}, ptr, slice};
+
+use crate::allocator::{AllocError, GlobalHeap, MyAllocator};
+use crate::collections::vec::MyVec;
+
+/*
+    MyVecDeque layout:
+    the buffer is a MyVec whose len is always 0, so it only owns the memory (and grows it the same way),
+    the values are len slots starting at head, wrapping around to the start of the buffer:
+    [ back part | free | front part ]  or  [ free | values | free ]
+ */
+pub struct MyVecDeque<T, A: MyAllocator = GlobalHeap> {
+    buf: MyVec<T, A>,
+    head: usize,
+    len: usize,
+}
+
+//constructors on the global heap
+impl<T> MyVecDeque<T> {
+    pub fn new() -> MyVecDeque<T> {
+        MyVecDeque::new_in(GlobalHeap)
+    }
+
+    pub fn with_capacity(capacity: usize) -> MyVecDeque<T> {
+        MyVecDeque::with_capacity_in(capacity, GlobalHeap)
+    }
+}
+
+//constructors, getters
+impl<T, A: MyAllocator> MyVecDeque<T, A> {
+    pub fn new_in(alloc: A) -> MyVecDeque<T, A> {
+        MyVecDeque { buf: MyVec::new_in(alloc), head: 0, len: 0 }
+    }
+
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> MyVecDeque<T, A> {
+        MyVecDeque { buf: MyVec::with_capacity_in(capacity, alloc), head: 0, len: 0 }
+    }
+
+    pub fn allocator(&self) -> &A {
+        self.buf.allocator()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    //index 0 is the front
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.len {
+            unsafe {
+                Some(&*self.buf.as_ptr().add(self.to_physical(index)))
+            }
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index < self.len {
+            let physical = self.to_physical(index);
+            unsafe {
+                Some(&mut *self.buf.as_mut_ptr().add(physical))
+            }
+        } else {
+            None
+        }
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len.wrapping_sub(1))
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(0)
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(self.len.wrapping_sub(1))
+    }
+
+    pub fn contains(&self, value: &T) -> bool
+    where T: PartialEq {
+        let (front, back) = self.as_slices();
+        front.contains(value) || back.contains(value)
+    }
+
+    //the values in order: the front part and the part which wrapped around to the start of the buffer
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let (front, back) = self.slice_ranges();
+        let ptr = self.buf.as_ptr();
+        unsafe {
+            (slice::from_raw_parts(ptr.add(front.start), front.len()), slice::from_raw_parts(ptr.add(back.start), back.len()))
+        }
+    }
+
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let (front, back) = self.slice_ranges();
+        let ptr = self.buf.as_mut_ptr();
+        unsafe {
+            (slice::from_raw_parts_mut(ptr.add(front.start), front.len()), slice::from_raw_parts_mut(ptr.add(back.start), back.len()))
+        }
+    }
+
+    pub fn iter(&self) -> MyVecDequeIter<'_, T> {
+        let (front, back) = self.as_slices();
+        MyVecDequeIter { front: front.iter(), back: back.iter() }
+    }
+
+    pub fn iter_mut(&mut self) -> MyVecDequeIterMut<'_, T> {
+        let (front, back) = self.as_mut_slices();
+        MyVecDequeIterMut { front: front.iter_mut(), back: back.iter_mut() }
+    }
+}
+
+impl<T, A: MyAllocator + Default> Default for MyVecDeque<T, A> {
+    fn default() -> Self {
+        MyVecDeque::new_in(A::default())
+    }
+}
+
+//adding values
+impl<T, A: MyAllocator> MyVecDeque<T, A> {
+    pub fn reserve(&mut self, additional: usize) {
+        if let Err(e) = self.try_reserve(additional) {
+            panic!("{}", e);
+        }
+    }
+
+    //on failure the deque is left unchanged
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        let needed = self.len.checked_add(additional).ok_or(AllocError)?;
+        let old_cap = self.capacity();
+        if needed > old_cap {
+            //the buffer's len is 0, so it reserves needed values in total
+            self.buf.try_reserve(needed)?;
+            unsafe {
+                self.handle_capacity_increase(old_cap);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn push_back(&mut self, value: T) {
+        if self.len == self.capacity() {
+            self.reserve(1);
+        }
+
+        let physical = self.to_physical(self.len);
+        unsafe {
+            ptr::write(self.buf.as_mut_ptr().add(physical), value);
+        }
+        self.len += 1;
+    }
+
+    pub fn push_front(&mut self, value: T) {
+        if self.len == self.capacity() {
+            self.reserve(1);
+        }
+
+        self.head = self.wrap_sub(self.head, 1);
+        unsafe {
+            ptr::write(self.buf.as_mut_ptr().add(self.head), value);
+        }
+        self.len += 1;
+    }
+}
+
+//removing values
+impl<T, A: MyAllocator> MyVecDeque<T, A> {
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let value = unsafe {
+            ptr::read(self.buf.as_ptr().add(self.head))
+        };
+        self.head = self.to_physical(1);
+        self.len -= 1;
+        Some(value)
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.len -= 1;
+        unsafe {
+            Some(ptr::read(self.buf.as_ptr().add(self.to_physical(self.len))))
+        }
+    }
+
+    //keeps the first len values, the rest are dropped from the back
+    //every value is removed before it's dropped, so a panicking drop leaves the deque valid
+    pub fn truncate(&mut self, len: usize) {
+        while self.len > len {
+            drop(self.pop_back());
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+        self.head = 0;
+    }
+}
+
+//reordering values
+impl<T, A: MyAllocator> MyVecDeque<T, A> {
+    //moves the values so they don't wrap around, the order stays the same
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        if size_of::<T>() == 0 {
+            self.head = 0;
+        } else if self.head > self.capacity() - self.len {
+            //the free slots are uninitialized, as MaybeUninit the whole buffer can be rotated
+            let cap = self.capacity();
+            let buffer = unsafe {
+                slice::from_raw_parts_mut(self.buf.as_mut_ptr() as *mut MaybeUninit<T>, cap)
+            };
+            buffer.rotate_left(self.head);
+            self.head = 0;
+        }
+
+        self.as_mut_slices().0
+    }
+
+    //the first n values are moved to the back
+    pub fn rotate_left(&mut self, n: usize) {
+        assert!(n <= self.len, "rotate_left by more than len");
+
+        //with a full buffer it's enough to move the head
+        if self.len == self.capacity() {
+            self.head = self.to_physical(n);
+            return;
+        }
+        self.make_contiguous().rotate_left(n);
+    }
+
+    //the last n values are moved to the front
+    pub fn rotate_right(&mut self, n: usize) {
+        assert!(n <= self.len, "rotate_right by more than len");
+
+        self.rotate_left(self.len - n);
+    }
+
+    pub fn swap(&mut self, i: usize, j: usize) {
+        assert!(i < self.len && j < self.len, "swap index out of bounds");
+
+        let (i, j) = (self.to_physical(i), self.to_physical(j));
+        unsafe {
+            ptr::swap(self.buf.as_mut_ptr().add(i), self.buf.as_mut_ptr().add(j));
+        }
+    }
+}
+
+//local helper functions
+impl<T, A: MyAllocator> MyVecDeque<T, A> {
+    fn wrap(index: usize, cap: usize) -> usize {
+        if index >= cap { index - cap } else { index }
+    }
+
+    //the slot of the value at index
+    fn to_physical(&self, index: usize) -> usize {
+        Self::wrap(self.head.wrapping_add(index), self.capacity())
+    }
+
+    fn wrap_sub(&self, index: usize, sub: usize) -> usize {
+        Self::wrap(index.wrapping_sub(sub).wrapping_add(self.capacity()), self.capacity())
+    }
+
+    //the slots of the front part and of the part which wrapped around
+    fn slice_ranges(&self) -> (Range<usize>, Range<usize>) {
+        if self.len == 0 {
+            return (0..0, 0..0);
+        }
+
+        let head_len = self.capacity() - self.head;
+        if self.len <= head_len {
+            (self.head..self.head + self.len, 0..0)
+        } else {
+            (self.head..self.capacity(), 0..self.len - head_len)
+        }
+    }
+
+    //the buffer grew from old_cap (its old content was copied as it was),
+    //if the values wrapped around, the shorter part is moved so they only wrap around the new capacity
+    unsafe fn handle_capacity_increase(&mut self, old_cap: usize) {
+        let new_cap = self.capacity();
+        if self.head <= old_cap - self.len {
+            return;
+        }
+
+        let head_len = old_cap - self.head;
+        let tail_len = self.len - head_len;
+        let ptr = self.buf.as_mut_ptr();
+        unsafe {
+            if tail_len < head_len && tail_len <= new_cap - old_cap {
+                //the wrapped part goes right after the old end
+                ptr::copy_nonoverlapping(ptr, ptr.add(old_cap), tail_len);
+            } else {
+                //the front part goes to the end of the new buffer
+                let new_head = new_cap - head_len;
+                ptr::copy(ptr.add(self.head), ptr.add(new_head), head_len);
+                self.head = new_head;
+            }
+        }
+    }
+}
+
+//access
+impl<T, A: MyAllocator> Index<usize> for MyVecDeque<T, A> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).expect("index out of bounds")
+    }
+}
+
+impl<T, A: MyAllocator> IndexMut<usize> for MyVecDeque<T, A> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.get_mut(index).expect("index out of bounds")
+    }
+}
+
+//conversions, the buffer is reused
+impl<T, A: MyAllocator> From<MyVec<T, A>> for MyVecDeque<T, A> {
+    fn from(mut vec: MyVec<T, A>) -> Self {
+        let len = vec.len();
+        unsafe {
+            vec.set_len(0);
+        }
+        MyVecDeque { buf: vec, head: 0, len }
+    }
+}
+
+//the values are moved to the start of the buffer
+impl<T, A: MyAllocator> From<MyVecDeque<T, A>> for MyVec<T, A> {
+    fn from(mut deque: MyVecDeque<T, A>) -> Self {
+        deque.make_contiguous();
+
+        let deque = ManuallyDrop::new(deque);
+        let mut vec = unsafe {
+            ptr::read(&deque.buf)
+        };
+        unsafe {
+            if deque.head != 0 {
+                ptr::copy(vec.as_ptr().add(deque.head), vec.as_mut_ptr(), deque.len);
+            }
+            vec.set_len(deque.len);
+        }
+        vec
+    }
+}
+
+impl<T, A: MyAllocator + Default> FromIterator<T> for MyVecDeque<T, A> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut deque = MyVecDeque::new_in(A::default());
+        deque.extend(iter);
+        deque
+    }
+}
+
+impl<T, A: MyAllocator> Extend<T> for MyVecDeque<T, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for value in iter {
+            self.push_back(value);
+        }
+    }
+}
+
+//iterator implementations
+impl<T, A: MyAllocator> IntoIterator for MyVecDeque<T, A> {
+    type Item = T;
+
+    type IntoIter = MyVecDequeIntoIter<T, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        MyVecDequeIntoIter { deque: self }
+    }
+}
+
+impl<'a, T, A: MyAllocator> IntoIterator for &'a MyVecDeque<T, A> {
+    type Item = &'a T;
+
+    type IntoIter = MyVecDequeIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, A: MyAllocator> IntoIterator for &'a mut MyVecDeque<T, A> {
+    type Item = &'a mut T;
+
+    type IntoIter = MyVecDequeIterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+//the buffer frees itself, only the values are dropped here
+impl<T, A: MyAllocator> Drop for MyVecDeque<T, A> {
+    fn drop(&mut self) {
+        struct DropSlice<T>(*mut [T]);
+
+        impl<T> Drop for DropSlice<T> {
+            fn drop(&mut self) {
+                unsafe {
+                    ptr::drop_in_place(self.0);
+                }
+            }
+        }
+
+        //the back part is dropped by the guard, even if a value of the front part panics
+        let (front, back) = self.as_mut_slices();
+        let (front, back) = (front as *mut [T], back as *mut [T]);
+        self.len = 0;
+        let _back = DropSlice(back);
+        unsafe {
+            ptr::drop_in_place(front);
+        }
+    }
+}
+
+impl<T: Debug, A: MyAllocator> Debug for MyVecDeque<T, A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Clone, A: MyAllocator + Clone> Clone for MyVecDeque<T, A> {
+    fn clone(&self) -> Self {
+        let mut out = MyVecDeque::with_capacity_in(self.len, self.allocator().clone());
+        for value in self {
+            out.push_back(value.clone());
+        }
+        out
+    }
+}
+
+impl<T: PartialEq, A: MyAllocator> PartialEq for MyVecDeque<T, A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq, A: MyAllocator> Eq for MyVecDeque<T, A> {}
+
+//ITERATORS
+#[derive(Clone)]
+pub struct MyVecDequeIter<'a, T> {
+    front: slice::Iter<'a, T>,
+    back: slice::Iter<'a, T>,
+}
+
+impl<'a, T> Iterator for MyVecDequeIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.front.len() + self.back.len();
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for MyVecDequeIter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+
+impl<'a, T> ExactSizeIterator for MyVecDequeIter<'a, T> {}
+
+impl<'a, T> FusedIterator for MyVecDequeIter<'a, T> {}
+
+pub struct MyVecDequeIterMut<'a, T> {
+    front: slice::IterMut<'a, T>,
+    back: slice::IterMut<'a, T>,
+}
+
+impl<'a, T> Iterator for MyVecDequeIterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.front.len() + self.back.len();
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for MyVecDequeIterMut<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+
+impl<'a, T> ExactSizeIterator for MyVecDequeIterMut<'a, T> {}
+
+impl<'a, T> FusedIterator for MyVecDequeIterMut<'a, T> {}
+
+//pops from the deque, the values which weren't taken are dropped with it
+pub struct MyVecDequeIntoIter<T, A: MyAllocator = GlobalHeap> {
+    deque: MyVecDeque<T, A>,
+}
+
+impl<T, A: MyAllocator> Iterator for MyVecDequeIntoIter<T, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.deque.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.deque.len, Some(self.deque.len))
+    }
+}
+
+impl<T, A: MyAllocator> DoubleEndedIterator for MyVecDequeIntoIter<T, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.deque.pop_back()
+    }
+}
+
+impl<T, A: MyAllocator> ExactSizeIterator for MyVecDequeIntoIter<T, A> {}
+
+impl<T, A: MyAllocator> FusedIterator for MyVecDequeIntoIter<T, A> {}
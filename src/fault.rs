@@ -0,0 +1,136 @@
+use core::{alloc::Layout, ptr::NonNull};
+
+use crate::allocator::{AllocError, GlobalHeap, MyAllocator};
+use crate::smart_pointers::mutex::MyMutex;
+
+//decides which allocations should fail, used for testing out of memory paths
+//a grow or shrink is an allocation of the new size, like allocate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FaultPolicy {
+    //only the nth allocation fails (counting from 1)
+    FailNth(usize),
+    //every allocation fails with probability p, the sequence is reproducible for the same seed
+    Probability { p: f64, seed: u64 },
+    //allocations fail once the sum of the requested bytes would go over the budget,
+    //it's the total of every successful request, freed bytes don't give anything back
+    ByteBudget(usize),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FaultStats {
+    pub attempts: usize,
+    pub failures: usize,
+    pub bytes_allocated: usize,
+}
+
+pub(crate) struct FaultState {
+    policy: FaultPolicy,
+    rng: u64,
+    stats: FaultStats,
+}
+
+impl FaultState {
+    pub(crate) const fn new(policy: FaultPolicy) -> FaultState {
+        let rng = match policy {
+            //xorshift can't leave the 0 state
+            FaultPolicy::Probability { seed, .. } => if seed == 0 { 0x9E37_79B9_7F4A_7C15 } else { seed },
+            _ => 0,
+        };
+
+        FaultState { policy, rng, stats: FaultStats { attempts: 0, failures: 0, bytes_allocated: 0 } }
+    }
+
+    pub(crate) fn stats(&self) -> FaultStats {
+        self.stats
+    }
+
+    //registers an allocation attempt of size bytes, returns true if it has to fail
+    pub(crate) fn should_fail(&mut self, size: usize) -> bool {
+        self.stats.attempts += 1;
+
+        let fail = match self.policy {
+            FaultPolicy::FailNth(n) => self.stats.attempts == n,
+            FaultPolicy::Probability { p, .. } => self.next_f64() < p,
+            FaultPolicy::ByteBudget(budget) => self.stats.bytes_allocated + size > budget,
+        };
+
+        if fail {
+            self.stats.failures += 1;
+        } else {
+            self.stats.bytes_allocated += size;
+        }
+
+        fail
+    }
+
+    //xorshift64*, only the upper 53 bits are used for the float
+    fn next_f64(&mut self) -> f64 {
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        let value = self.rng.wrapping_mul(0x2545_F491_4F6C_DD1D);
+        (value >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+//wraps another allocator and makes some of its allocations fail according to the policy
+pub struct FaultInjectingAllocator<A: MyAllocator = GlobalHeap> {
+    state: MyMutex<FaultState>,
+    inner: A,
+}
+
+impl FaultInjectingAllocator {
+    pub fn new(policy: FaultPolicy) -> FaultInjectingAllocator {
+        FaultInjectingAllocator::with_inner(policy, GlobalHeap)
+    }
+}
+
+impl<A: MyAllocator> FaultInjectingAllocator<A> {
+    pub fn with_inner(policy: FaultPolicy, inner: A) -> FaultInjectingAllocator<A> {
+        FaultInjectingAllocator { state: MyMutex::new(FaultState::new(policy)), inner }
+    }
+
+    pub fn stats(&self) -> FaultStats {
+        self.state.lock().stats()
+    }
+}
+
+unsafe impl<A: MyAllocator> MyAllocator for FaultInjectingAllocator<A> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        if self.state.lock().should_fail(layout.size()) {
+            return Err(AllocError);
+        }
+        self.inner.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe {
+            self.inner.deallocate(ptr, layout)
+        }
+    }
+
+    unsafe fn usable_size(&self, ptr: NonNull<u8>, layout: Layout) -> usize {
+        unsafe {
+            self.inner.usable_size(ptr, layout)
+        }
+    }
+
+    //forwarded, so the inner allocator can resize in place, a failure leaves the block as it was
+    unsafe fn grow(&self, ptr: NonNull<u8>, old_layout: Layout, new_layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        if self.state.lock().should_fail(new_layout.size()) {
+            return Err(AllocError);
+        }
+        unsafe {
+            self.inner.grow(ptr, old_layout, new_layout)
+        }
+    }
+
+    unsafe fn shrink(&self, ptr: NonNull<u8>, old_layout: Layout, new_layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        if self.state.lock().should_fail(new_layout.size()) {
+            return Err(AllocError);
+        }
+        unsafe {
+            self.inner.shrink(ptr, old_layout, new_layout)
+        }
+    }
+}
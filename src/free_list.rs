@@ -0,0 +1,139 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::tag::SIZE_MASK;
+
+//the block layouts are described in manager.rs, the shared heap uses the same ones
+pub(crate) const HEADER_SIZE: usize = size_of::<usize>() * 2;
+pub(crate) const PADDED: usize = 1;
+//the link of the last free block
+pub(crate) const NONE: usize = usize::MAX;
+
+const WORD: usize = size_of::<usize>();
+
+//the blocks of a heap and its free list, used by the global heap and the shared memory heap
+//a link (to the next free block or to the first byte of a block) is stored as origin + the offset of the word from base:
+//the global heap stores addresses (origin is the address of the heap), the shared heap offsets (origin is 0),
+//so a link is aligned like the word it points to
+//every change of the free list is published by a single release store of a link, after the words it links to are written,
+//and links are read with acquire, so if the shared heap's owner dies in the middle, the process which takes the lock over
+//sees either the old or the new list with all of its words, the block which was allocated or freed may be lost
+#[derive(Clone, Copy)]
+pub(crate) struct Blocks {
+    base: *mut u8,
+    origin: usize,
+}
+
+impl Blocks {
+    pub(crate) const fn new(base: *mut u8, origin: usize) -> Blocks {
+        Blocks { base, origin }
+    }
+
+    //SAFETY: link must be a link into the blocks
+    pub(crate) unsafe fn word(self, link: usize) -> *mut usize {
+        unsafe {
+            self.base.add(link - self.origin) as *mut usize
+        }
+    }
+
+    //first fit: takes size bytes with alignment (at least a word) from the first free block they fit into,
+    //returns the link of the user data, None if no free block is large enough
+    //SAFETY: first_free must point to the link of the first free block
+    pub(crate) unsafe fn alloc(self, first_free: *mut usize, size: usize, alignment: usize) -> Option<usize> {
+        let data = size.checked_next_multiple_of(WORD)?;
+
+        //link is the word which holds the link of the currently inspected free block
+        let mut link = first_free;
+
+        loop {
+            let current = unsafe {
+                load_link(link)
+            };
+            if current == NONE {
+                return None;
+            }
+
+            let (current_size, next) = unsafe {
+                (*self.word(current), load_link(self.word(current + WORD)))
+            };
+
+            let front_pad = (alignment - (current + HEADER_SIZE) % alignment) % alignment;
+            let mut new_size = data.checked_add(front_pad + HEADER_SIZE)?;
+
+            if new_size > current_size {
+                link = unsafe {
+                    self.word(current + WORD)
+                };
+                continue;
+            }
+
+            //the remainder is too small for a free block, it becomes end padding
+            if current_size - new_size <= HEADER_SIZE {
+                new_size = current_size;
+                unsafe {
+                    publish_link(link, next);
+                }
+            } else {
+                let new_free = current + new_size;
+                unsafe {
+                    *self.word(new_free) = current_size - new_size;
+                    *self.word(new_free + WORD) = next;
+                    publish_link(link, new_free);
+                }
+            }
+
+            //the block is off the list, so its header can overwrite the free block's words
+            //the size is also in the first word, so the heap can be walked block by block
+            let header = current + front_pad;
+            unsafe {
+                if front_pad != 0 {
+                    mark_front_pad(self.word(current), front_pad, new_size);
+                }
+                *self.word(header) = new_size;
+                *self.word(header + WORD) = current;
+            }
+
+            return Some(header + HEADER_SIZE);
+        }
+    }
+
+    //puts the block of the user data at data to the front of the free list,
+    //returns the size word of its header (the tag is in its high bits)
+    //SAFETY: data must be the link of the user data of an allocated block, first_free must point to the link of the first free block
+    pub(crate) unsafe fn free(self, first_free: *mut usize, data: usize) -> usize {
+        unsafe {
+            let header = data - HEADER_SIZE;
+            let size = *self.word(header);
+            let first_byte = *self.word(header + WORD);
+
+            *self.word(first_byte) = size & SIZE_MASK;
+            *self.word(first_byte + WORD) = load_link(first_free);
+            publish_link(first_free, first_byte);
+
+            size
+        }
+    }
+}
+
+//SAFETY: link must point to a word of a link, aligned to a word
+unsafe fn load_link(link: *mut usize) -> usize {
+    unsafe {
+        AtomicUsize::from_ptr(link).load(Ordering::Acquire)
+    }
+}
+
+//SAFETY: link must point to a word of a link, aligned to a word
+unsafe fn publish_link(link: *mut usize, value: usize) {
+    unsafe {
+        AtomicUsize::from_ptr(link).store(value, Ordering::Release);
+    }
+}
+
+//SAFETY: block must point to the first byte of an allocated block
+pub(crate) unsafe fn mark_front_pad(block: *mut usize, front_pad: usize, size: usize) {
+    unsafe {
+        *block = size | PADDED;
+        if front_pad > size_of::<usize>() {
+            *block.add(1) = front_pad | PADDED;
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use core::{fmt::Display, marker::PhantomData, ops::{Deref, DerefMut}, ptr};
+
+use crate::manager::{handle_alloc, handle_free, handle_pin, handle_unpin};
+
+pub(crate) const MAX_HANDLES: usize = 64;
+
+//ptr: points to the user data of the block, null if the slot is unused
+//pins: while it's not 0 the block can't be moved by compact
+#[derive(Clone, Copy)]
+pub(crate) struct HandleSlot {
+    pub(crate) ptr: *mut u8,
+    pub(crate) alignment: usize,
+    pub(crate) pins: usize,
+}
+
+pub(crate) struct HandleTable {
+    pub(crate) slots: [HandleSlot; MAX_HANDLES],
+}
+
+impl HandleTable {
+    pub(crate) const fn new() -> HandleTable {
+        HandleTable { slots: [HandleSlot { ptr: ptr::null_mut(), alignment: 0, pins: 0 }; MAX_HANDLES] }
+    }
+
+    pub(crate) fn free_slot(&self) -> Option<usize> {
+        self.slots.iter().position(|slot| slot.ptr.is_null())
+    }
+}
+
+//a value on the heap which can be moved by compact, it can only be accessed through borrow/borrow_mut
+//which pin the value in place until the returned guard is dropped
+pub struct MyHandle<T> {
+    index: usize,
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for MyHandle<T> {}
+unsafe impl<T: Sync> Sync for MyHandle<T> {}
+
+impl<T> MyHandle<T> {
+    pub fn new(value: T) -> MyHandle<T> {
+        let index = match handle_alloc(size_of::<T>(), align_of::<T>()) {
+            Some(index) => index,
+            None => panic!("unable to allocate handle, not enough free space or handle slots"),
+        };
+
+        unsafe {
+            let ptr = handle_pin(index) as *mut T;
+            ptr::write(ptr, value);
+            handle_unpin(index);
+        }
+
+        MyHandle { index, _marker: PhantomData }
+    }
+
+    //the slot index of the handle, it stays valid across snapshot and restore
+    pub fn into_raw(self) -> usize {
+        let index = self.index;
+        core::mem::forget(self);
+        index
+    }
+
+    /// # Safety
+    /// index must come from into_raw of a MyHandle<T> which wasn't turned back into a handle yet
+    pub unsafe fn from_raw(index: usize) -> MyHandle<T> {
+        MyHandle { index, _marker: PhantomData }
+    }
+
+    pub fn borrow(&'_ self) -> MyHandleRef<'_, T> {
+        let ptr = handle_pin(self.index) as *const T;
+        MyHandleRef { handle: self, ptr }
+    }
+
+    pub fn borrow_mut(&'_ mut self) -> MyHandleRefMut<'_, T> {
+        let ptr = handle_pin(self.index) as *mut T;
+        MyHandleRefMut { handle: self, ptr }
+    }
+}
+
+impl<T> Drop for MyHandle<T> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::drop_in_place(handle_pin(self.index) as *mut T);
+            handle_free(self.index);
+        }
+    }
+}
+
+pub struct MyHandleRef<'a, T> {
+    handle: &'a MyHandle<T>,
+    ptr: *const T,
+}
+
+impl<'a, T> Deref for MyHandleRef<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe {
+            & *self.ptr
+        }
+    }
+}
+
+impl<'a, T> Drop for MyHandleRef<'a, T> {
+    fn drop(&mut self) {
+        handle_unpin(self.handle.index);
+    }
+}
+
+impl<'a, T: Display> Display for MyHandleRef<'a, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", &**self)
+    }
+}
+
+pub struct MyHandleRefMut<'a, T> {
+    handle: &'a mut MyHandle<T>,
+    ptr: *mut T,
+}
+
+impl<'a, T> Deref for MyHandleRefMut<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe {
+            & *self.ptr
+        }
+    }
+}
+
+impl<'a, T> DerefMut for MyHandleRefMut<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe {
+            &mut *self.ptr
+        }
+    }
+}
+
+impl<'a, T> Drop for MyHandleRefMut<'a, T> {
+    fn drop(&mut self) {
+        handle_unpin(self.handle.index);
+    }
+}
+
+impl<'a, T: Display> Display for MyHandleRefMut<'a, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", &**self)
+    }
+}
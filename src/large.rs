@@ -0,0 +1,208 @@
+use core::ptr;
+
+pub(crate) const PAGE_SIZE: usize = 512;
+pub(crate) const PAGES: usize = 128;
+const LARGE_LEN: usize = PAGE_SIZE * PAGES;
+
+//allocations of at least this many bytes go to the large region by default (see set_large_threshold)
+pub(crate) const DEFAULT_THRESHOLD: usize = 1024;
+
+static mut LARGE_HEAP: LargeArray = LargeArray([0; LARGE_LEN]);
+
+//the region is aligned to the OS pages (which are multiples of PAGE_SIZE), so trim can release its free pages
+#[repr(align(4096))]
+#[allow(dead_code)]
+struct LargeArray([u8; LARGE_LEN]);
+
+/*
+    Large region layout:
+    PAGES pages of PAGE_SIZE bytes, every allocation is a run of whole pages,
+    the user data starts at the first byte of the run, so there is no header in the region,
+    the runs are tracked by LargeTable instead (which lives in the manager, under its lock)
+ */
+//pages: length of the run starting at this page, 0 if no run starts here
+//tag: the tag of the allocation which starts at this page
+#[derive(Clone, Copy)]
+pub(crate) struct LargeRun {
+    pub(crate) pages: u16,
+    pub(crate) tag: u16,
+}
+
+//allocations: live runs, used_bytes: their pages in bytes
+//largest_free: the largest allocation the region can serve right now
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LargeStats {
+    pub allocations: usize,
+    pub used_bytes: usize,
+    pub free_bytes: usize,
+    pub largest_free: usize,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct LargeTable {
+    pub(crate) runs: [LargeRun; PAGES],
+}
+
+impl LargeTable {
+    pub(crate) const fn new() -> LargeTable {
+        LargeTable { runs: [LargeRun { pages: 0, tag: 0 }; PAGES] }
+    }
+
+    pub(crate) fn base() -> *mut u8 {
+        &raw mut LARGE_HEAP as *mut u8
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn len() -> usize {
+        LARGE_LEN
+    }
+
+    pub(crate) fn contains(ptr: *const u8) -> bool {
+        let base = Self::base() as usize;
+        (base..base + LARGE_LEN).contains(&(ptr as usize))
+    }
+
+    //first fit over the pages, returns null if there is no free run long enough
+    pub(crate) fn alloc(&mut self, size: usize, alignment: usize, tag: u16) -> *mut u8 {
+        let pages = size.div_ceil(PAGE_SIZE).max(1);
+        if pages > PAGES {
+            return ptr::null_mut();
+        }
+
+        let mut start = 0;
+        let mut page = 0;
+        while page < PAGES {
+            let run = self.runs[page].pages as usize;
+            if run != 0 {
+                page += run;
+                start = page;
+                continue;
+            }
+
+            //a run can only start on a page with the required alignment
+            if start == page && !(Self::page_ptr(page) as usize).is_multiple_of(alignment) {
+                page += 1;
+                start = page;
+                continue;
+            }
+
+            page += 1;
+            if page - start == pages {
+                self.runs[start] = LargeRun { pages: pages as u16, tag };
+                return Self::page_ptr(start);
+            }
+        }
+
+        ptr::null_mut()
+    }
+
+    //returns the bytes and the tag of the freed run
+    //SAFETY: ptr must be given by alloc and not freed yet
+    pub(crate) unsafe fn free(&mut self, ptr: *mut u8) -> (usize, u16) {
+        let page = (ptr as usize - Self::base() as usize) / PAGE_SIZE;
+        let run = self.runs[page];
+        debug_assert!(run.pages != 0, "freeing a large allocation which is not allocated");
+
+        self.runs[page] = LargeRun { pages: 0, tag: 0 };
+        (run.pages as usize * PAGE_SIZE, run.tag)
+    }
+
+    pub(crate) fn usable_size(&self, ptr: *const u8) -> usize {
+        let page = (ptr as usize - Self::base() as usize) / PAGE_SIZE;
+        self.runs[page].pages as usize * PAGE_SIZE
+    }
+
+    //cuts the unused pages off the end of the run, returns the released bytes and the tag of the run
+    pub(crate) fn shrink(&mut self, ptr: *const u8, new_size: usize) -> (usize, u16) {
+        let page = (ptr as usize - Self::base() as usize) / PAGE_SIZE;
+        let run = &mut self.runs[page];
+        let pages = (new_size.div_ceil(PAGE_SIZE).max(1) as u16).min(run.pages);
+
+        let released = (run.pages - pages) as usize * PAGE_SIZE;
+        run.pages = pages;
+        (released, run.tag)
+    }
+
+    //calls f with the start and the end of every sequence of free pages
+    #[cfg(all(feature = "std", target_os = "linux", target_pointer_width = "64"))]
+    pub(crate) fn for_each_free(&self, mut f: impl FnMut(*mut u8, *mut u8)) {
+        let mut page = 0;
+        while page < PAGES {
+            let run = self.runs[page].pages as usize;
+            if run != 0 {
+                page += run;
+                continue;
+            }
+
+            let start = page;
+            while page < PAGES && self.runs[page].pages == 0 {
+                page += 1;
+            }
+            f(Self::page_ptr(start), Self::page_ptr(page));
+        }
+    }
+
+    //calls f with the first page, the first byte and the run of every allocation
+    pub(crate) fn for_each_run(&self, mut f: impl FnMut(usize, *mut u8, LargeRun)) {
+        let mut page = 0;
+        while page < PAGES {
+            let run = self.runs[page];
+            if run.pages == 0 {
+                page += 1;
+            } else {
+                f(page, Self::page_ptr(page), run);
+                page += run.pages as usize;
+            }
+        }
+    }
+
+    pub(crate) fn stats(&self) -> LargeStats {
+        let mut stats = LargeStats { allocations: 0, used_bytes: 0, free_bytes: 0, largest_free: 0 };
+        let mut free_run = 0;
+        let mut page = 0;
+        while page < PAGES {
+            let run = self.runs[page].pages as usize;
+            if run == 0 {
+                free_run += 1;
+                stats.free_bytes += PAGE_SIZE;
+                stats.largest_free = stats.largest_free.max(free_run * PAGE_SIZE);
+                page += 1;
+            } else {
+                free_run = 0;
+                stats.allocations += 1;
+                stats.used_bytes += run * PAGE_SIZE;
+                page += run;
+            }
+        }
+        stats
+    }
+
+    //the first page where the table is broken and why, used by validate_heap
+    pub(crate) fn check(&self, tags: usize) -> Option<(usize, &'static str)> {
+        let mut page = 0;
+        while page < PAGES {
+            let run = self.runs[page].pages as usize;
+            if run == 0 {
+                page += 1;
+                continue;
+            }
+            if self.runs[page].tag as usize >= tags {
+                return Some((page, "large allocation has an unregistered tag"));
+            }
+            if page + run > PAGES {
+                return Some((page, "large allocation runs past the end of the large region"));
+            }
+            if let Some(inner) = (page + 1..page + run).find(|&inner| self.runs[inner].pages != 0) {
+                return Some((inner, "large allocation starts inside another one"));
+            }
+            page += run;
+        }
+        None
+    }
+
+    fn page_ptr(page: usize) -> *mut u8 {
+        unsafe {
+            Self::base().add(page * PAGE_SIZE)
+        }
+    }
+}
@@ -1,16 +1,71 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
 mod manager;
+mod free_list;
+mod allocator;
+mod fault;
+mod budget;
+mod tag;
+mod handle;
+mod large;
+mod checkpoint;
+#[cfg(all(feature = "std", target_os = "linux", target_pointer_width = "64"))]
+mod os;
+#[cfg(feature = "std")]
+mod snapshot;
+#[cfg(feature = "std")]
+mod shared;
 mod collections;
 mod smart_pointers;
+mod pool;
 
 
 //manager
+#[cfg(feature = "std")]
 pub use manager::debug_free;
+pub use manager::debug_free_to;
 pub use manager::my_alloc;
 pub use manager::my_free;
+pub use manager::my_try_alloc;
+pub use manager::{my_try_alloc_usable, my_usable_size};
+pub use manager::{my_shrink, trim};
+pub use manager::set_fault_policy;
+pub use manager::fault_stats;
+pub use manager::register_tag;
+pub use manager::usage_by_tag;
+pub use manager::compact;
+pub use manager::{heap_stats, HeapStats};
+pub use manager::{validate_heap, HeapCorruption};
+pub use manager::{large_stats, set_large_threshold};
+#[cfg(feature = "std")]
+pub use manager::{restore, snapshot};
+
+//allocator
+pub use allocator::{AllocError, GlobalHeap, MyAllocator};
+pub use fault::{FaultInjectingAllocator, FaultPolicy, FaultStats};
+pub use budget::{Budget, BudgetMode, BudgetStats};
+pub use tag::{with_tag, Tag, TagUsage, TooManyTags};
+pub use handle::{MyHandle, MyHandleRef, MyHandleRefMut};
+pub use large::LargeStats;
+pub use checkpoint::{HeapCheckpoint, HeapDiff, Leak};
+#[cfg(feature = "std")]
+pub use shared::{SharedHeap, REGION_ALIGN};
 
 //collections
 pub use collections::string::MyString;
 pub use collections::vec::*;
+pub use collections::vec_deque::*;
+pub use collections::hash_map::*;
+pub use collections::hash_set::*;
+pub use collections::btree_map::*;
+pub use collections::btree_set::*;
+pub use collections::binary_heap::*;
+pub use collections::linked_list::*;
+#[cfg(feature = "std")]
+pub use collections::shared_vec::SharedVec;
+
+//pool
+pub use pool::{MyPool, PoolBox, PoolStats};
 
 //refcell
 pub use smart_pointers::*;
\ No newline at end of file
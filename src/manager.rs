@@ -1,6 +1,23 @@
-use core::sync::atomic::{AtomicBool, Ordering};
+use core::{fmt::{self, Write}, ptr, sync::atomic::{AtomicBool, Ordering}};
+#[cfg(feature = "std")]
+use std::{io, path::Path};
+
+use crate::checkpoint::{HeapDiff, Leak, LiveBlocks, LARGE_MAP_LEN};
+use crate::collections::vec::MyVec;
+use crate::free_list::{mark_front_pad, Blocks, HEADER_SIZE, PADDED};
+use crate::fault::{FaultPolicy, FaultState, FaultStats};
+use crate::handle::{HandleTable, MAX_HANDLES};
+use crate::large::{LargeStats, LargeTable, DEFAULT_THRESHOLD, PAGE_SIZE, PAGES};
+#[cfg(all(feature = "std", target_os = "linux", target_pointer_width = "64"))]
+use crate::os;
+#[cfg(feature = "std")]
+use crate::large::LargeRun;
+#[cfg(feature = "std")]
+use crate::snapshot::{invalid, HeapImage};
+use crate::tag::{current_tag, Tag, TagTable, TagUsage, TooManyTags, SIZE_MASK, TAG_SHIFT};
+#[cfg(feature = "std")]
+use crate::tag::MAX_TAGS;
 
-const HEADER_SIZE: usize = size_of::<usize>() * 2;
 const LEN: usize = 8192;
 
 static mut HEAP: AlignedArray = AlignedArray::new();
@@ -28,26 +45,368 @@ fn lock() -> Guard {
     Guard
 }
 
+#[cfg(feature = "std")]
 pub fn debug_free() {
+    //prints straight to stdout, so nothing is allocated while the manager is locked
+    struct Stdout;
+
+    impl Write for Stdout {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            std::print!("{}", s);
+            Ok(())
+        }
+    }
+
+    let _ = debug_free_to(&mut Stdout);
+}
+
+//same as debug_free, but writes to any sink (e.g. a uart without std)
+//the sink must not allocate on this heap, because the manager is locked while writing
+pub fn debug_free_to(out: &mut dyn Write) -> fmt::Result {
     let _guard = lock();
     unsafe {
-        (* &raw const MANAGER).debug_free();
+        let manager = &raw const MANAGER;
+        (*manager).debug_free(out)
     }
 }
 
 pub fn my_alloc(size: usize, alignment: usize) -> *mut u8 {
+    let ptr = my_try_alloc(size, alignment);
+    if ptr.is_null() {
+        panic!("unable to allocate, not enough free space");
+    }
+    ptr
+}
+
+//same as my_alloc, but returns a null ptr instead of panicking when there is not enough free space
+pub fn my_try_alloc(size: usize, alignment: usize) -> *mut u8 {
+    let tag = current_tag();
+    let _guard = lock();
+    unsafe {
+        let manager = &raw mut MANAGER;
+        (*manager).alloc(size, alignment, tag)
+    }
+}
+
+//same as my_try_alloc, but also returns the usable size of the block, which can be more than size
+//(the end padding and a remainder too small for a free block belong to the block too), (null, 0) on failure
+pub fn my_try_alloc_usable(size: usize, alignment: usize) -> (*mut u8, usize) {
+    let tag = current_tag();
+    let _guard = lock();
+    unsafe {
+        let manager = &raw mut MANAGER;
+        let ptr = (*manager).alloc(size, alignment, tag);
+        if ptr.is_null() {
+            return (ptr, 0);
+        }
+        (ptr, (*manager).usable_size(ptr))
+    }
+}
+
+/// # Safety
+/// ptr must be given by an allocation of the global heap and not freed yet
+pub unsafe fn my_usable_size<T>(ptr: *const T) -> usize {
+    let _guard = lock();
+    unsafe {
+        let manager = &raw const MANAGER;
+        (*manager).usable_size(ptr as *const u8)
+    }
+}
+
+//shrinks the block in place to at least new_size bytes, the cut off tail becomes a free block
+//if it's large enough for one (a run of the large region gives back its unused pages), returns the new usable size
+/// # Safety
+/// ptr must be given by an allocation of the global heap and not freed yet,
+/// new_size must not be more than its usable size
+pub unsafe fn my_shrink<T>(ptr: *mut T, new_size: usize) -> usize {
+    let _guard = lock();
+    unsafe {
+        let manager = &raw mut MANAGER;
+        (*manager).shrink(ptr as *mut u8, new_size)
+    }
+}
+
+//gives the memory of the OS pages which are entirely inside free space back to the OS,
+//they are mapped again (zeroed or with their initial bytes) when they're touched next time
+//only does something on 64 bit linux with std, returns the released bytes
+pub fn trim() -> usize {
+    let _guard = lock();
+    unsafe {
+        let manager = &raw const MANAGER;
+        (*manager).trim()
+    }
+}
+
+//makes the allocations of the global heap fail according to the policy, None turns it off
+//note: my_alloc panics on these failures, my_try_alloc returns null
+pub fn set_fault_policy(policy: Option<FaultPolicy>) {
+    let _guard = lock();
+    unsafe {
+        let manager = &raw mut MANAGER;
+        (*manager).fault = policy.map(FaultState::new);
+    }
+}
+
+pub fn fault_stats() -> Option<FaultStats> {
     let _guard = lock();
     unsafe {
-        (* &raw mut MANAGER).alloc(size, alignment)
+        let manager = &raw const MANAGER;
+        (*manager).fault.as_ref().map(FaultState::stats)
     }
 }
 
-//SAFETY:
-//can only free ptr's given upon allocation
+//registers a new tag for with_tag, the name shows up in usage_by_tag
+//fails once MAX_TAGS (64, the untagged one included) are registered
+pub fn register_tag(name: &'static str) -> Result<Tag, TooManyTags> {
+    let _guard = lock();
+    unsafe {
+        let manager = &raw mut MANAGER;
+        (*manager).tags.register(name)
+    }
+}
+
+//the registered name equal to name, so a restore can use it instead of leaking a copy
+#[cfg(feature = "std")]
+pub(crate) fn registered_tag_name(name: &str) -> Option<&'static str> {
+    let _guard = lock();
+    unsafe {
+        let manager = &raw const MANAGER;
+        (*manager).tags.find(name)
+    }
+}
+
+//live bytes and allocation count of every registered tag (the first one is the untagged usage)
+pub fn usage_by_tag() -> MyVec<TagUsage> {
+    //copy the table, so the output vec is allocated after the lock is released
+    let tags = {
+        let _guard = lock();
+        unsafe {
+            let manager = &raw const MANAGER;
+            (*manager).tags
+        }
+    };
+
+    let mut out = MyVec::with_capacity(tags.len());
+    for i in 0..tags.len() {
+        out.push(tags.usage(i));
+    }
+    out
+}
+
+//free space of the global heap, fragmentation can be estimated as 1 - largest_free / free_bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeapStats {
+    pub free_bytes: usize,
+    pub free_blocks: usize,
+    pub largest_free: usize,
+}
+
+pub fn heap_stats() -> HeapStats {
+    let _guard = lock();
+    let mut stats = HeapStats { free_bytes: 0, free_blocks: 0, largest_free: 0 };
+    unsafe {
+        let manager = &raw const MANAGER;
+        let mut current = (*manager).first_free;
+        while current as usize != usize::MAX {
+            stats.free_bytes += *current;
+            stats.free_blocks += 1;
+            stats.largest_free = stats.largest_free.max(*current);
+            current = *current.add(1) as *mut usize;
+        }
+    }
+    stats
+}
+
+//what validate_heap found wrong, offset is the byte offset of the broken block from the start of its region
+//(the heap, or the large region if the reason says so)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeapCorruption {
+    pub offset: usize,
+    pub reason: &'static str,
+}
+
+impl fmt::Display for HeapCorruption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "heap corrupted at offset {}: {}", self.offset, self.reason)
+    }
+}
+
+//checks the free list and walks every block of the heap, used by the tests and the fuzz target
+pub fn validate_heap() -> Result<(), HeapCorruption> {
+    let _guard = lock();
+    unsafe {
+        let manager = &raw const MANAGER;
+        (*manager).validate()
+    }
+}
+
+//allocations of at least threshold bytes are served from the large region, so they don't fragment the heap
+//usize::MAX turns the large region off
+pub fn set_large_threshold(threshold: usize) {
+    let _guard = lock();
+    unsafe {
+        let manager = &raw mut MANAGER;
+        (*manager).large_threshold = threshold;
+    }
+}
+
+pub fn large_stats() -> LargeStats {
+    let _guard = lock();
+    unsafe {
+        let manager = &raw const MANAGER;
+        (*manager).large.stats()
+    }
+}
+
+//the live blocks for HeapCheckpoint::new
+pub(crate) fn checkpoint_blocks() -> LiveBlocks {
+    let _guard = lock();
+    unsafe {
+        let manager = &raw const MANAGER;
+        (*manager).live_blocks()
+    }
+}
+
+//the blocks alive now which weren't in old, for HeapCheckpoint::diff
+pub(crate) fn diff_blocks(old: &LiveBlocks) -> HeapDiff {
+    let _guard = lock();
+    unsafe {
+        let manager = &raw const MANAGER;
+        (*manager).diff(old)
+    }
+}
+
+//allocates a relocatable block, returns the index of its handle slot
+pub(crate) fn handle_alloc(size: usize, alignment: usize) -> Option<usize> {
+    let tag = current_tag();
+    let _guard = lock();
+    unsafe {
+        let manager = &raw mut MANAGER;
+        let index = (*manager).handles.free_slot()?;
+
+        let ptr = (*manager).alloc(size, alignment, tag);
+        if ptr.is_null() {
+            return None;
+        }
+
+        (*manager).handles.slots[index].ptr = ptr;
+        (*manager).handles.slots[index].alignment = alignment;
+        (*manager).handles.slots[index].pins = 0;
+        Some(index)
+    }
+}
+
+//the block can't be moved until handle_unpin is called, returns its current address
+pub(crate) fn handle_pin(index: usize) -> *mut u8 {
+    let _guard = lock();
+    unsafe {
+        let manager = &raw mut MANAGER;
+        let slot = &mut (*manager).handles.slots[index];
+        slot.pins += 1;
+        slot.ptr
+    }
+}
+
+pub(crate) fn handle_unpin(index: usize) {
+    let _guard = lock();
+    unsafe {
+        let manager = &raw mut MANAGER;
+        (*manager).handles.slots[index].pins -= 1;
+    }
+}
+
+//SAFETY: index must be a slot given by handle_alloc, the value must be already dropped
+pub(crate) unsafe fn handle_free(index: usize) {
+    let _guard = lock();
+    unsafe {
+        let manager = &raw mut MANAGER;
+        let ptr = (*manager).handles.slots[index].ptr;
+        (*manager).free(ptr);
+        (*manager).handles.slots[index].ptr = ptr::null_mut();
+    }
+}
+
+//slides the unpinned handle blocks together and merges the free blocks between them
+//returns the size of the largest free block after compaction
+pub fn compact() -> usize {
+    let _guard = lock();
+    unsafe {
+        let manager = &raw mut MANAGER;
+        (*manager).compact()
+    }
+}
+
+//saves the whole heap and the manager's state to a file (see snapshot.rs for the format)
+#[cfg(feature = "std")]
+pub fn snapshot(path: impl AsRef<Path>) -> io::Result<()> {
+    //copy the state under the lock, but write the file after releasing it
+    let image = {
+        let _guard = lock();
+        unsafe {
+            let manager = &raw const MANAGER;
+            (*manager).image()
+        }
+    };
+
+    image.write_to(path.as_ref())
+}
+
+//replaces the heap and the manager's state with a snapshot, even if the heap is at a different address now
+//the free list, the blocks' back pointers and the handles are relocated, pointers inside the user data are not
+//the blocks, the large runs and the handles of the file are checked like validate_heap does before anything is replaced,
+//a truncated or corrupt file is an InvalidData error and leaves the heap as it was
+/// # Safety
+/// every value allocated before the restore is invalid after it, so there shouldn't be any alive,
+/// and only the manager's own bookkeeping is checked: the bytes of the values come from the file as they are,
+/// so they have to be valid for whatever types they are read as afterwards
+#[cfg(feature = "std")]
+pub unsafe fn restore(path: impl AsRef<Path>) -> io::Result<()> {
+    let image = HeapImage::read_from(path.as_ref())?;
+
+    if image.heap.len() != LEN {
+        return Err(invalid("snapshot was made with a different heap size"));
+    }
+    if image.large.len() != LargeTable::len() || image.large_runs.len() != PAGES {
+        return Err(invalid("snapshot was made with a different large region size"));
+    }
+    if image.handles.len() != MAX_HANDLES || image.tags.is_empty() || image.tags.len() > MAX_TAGS {
+        return Err(invalid("snapshot was made with different manager limits"));
+    }
+    check_image(&image).map_err(|corruption| invalid(&format!("corrupt snapshot, {}", corruption)))?;
+
+    let _guard = lock();
+    unsafe {
+        let manager = &raw mut MANAGER;
+        (*manager).restore(&image);
+    }
+    Ok(())
+}
+
+//the checks of validate_heap on an image, in the addresses it had when it was saved
+#[cfg(feature = "std")]
+fn check_image(image: &HeapImage) -> Result<(), HeapCorruption> {
+    let words: Vec<usize> = image.heap.chunks_exact(size_of::<usize>())
+        .map(|word| usize::from_ne_bytes(word.try_into().unwrap()))
+        .collect();
+    let data_map = check_heap(&words, image.base, image.first_free, image.tags.len())?;
+
+    let mut large = LargeTable::new();
+    for (run, &(pages, tag)) in large.runs.iter_mut().zip(&image.large_runs) {
+        *run = LargeRun { pages, tag };
+    }
+    check_large(&large, image.tags.len())?;
+
+    let handles = image.handles.iter().map(|&(ptr, alignment, _)| (ptr, alignment));
+    check_handles(handles, &data_map, image.base, &large, image.large_base)
+}
+
+/// # Safety
+/// can only free ptr's given upon allocation
 pub unsafe fn my_free<T>(ptr: *mut T) {
     let _guard = lock();
     unsafe {
-        (* &raw mut MANAGER).free(ptr);
+        let manager = &raw mut MANAGER;
+        (*manager).free(ptr);
     }
 }
 
@@ -59,7 +418,7 @@ struct AlignedArray([u8; LEN]);
 
 impl AlignedArray {
     const fn new() -> AlignedArray {
-        assert!(LEN > HEADER_SIZE);
+        const { assert!(LEN > HEADER_SIZE) };
         
         let mut bytes = [0u8; LEN];
 
@@ -93,162 +452,631 @@ impl AlignedArray {
         2. *mut usize: points to the first byte of the allocated block
     3. USER DATA (bytes)
     4. END PADDING: max HEADER_SIZE bytes, so no bytes will be lost forever
+    Note: the first word of the block is always the size (it's either the HEADER or the FRONT PADDING)
+    if there is FRONT PADDING, its first word has the PADDED bit set,
+    and if it's longer than a word, the second word is its length in bytes also with the PADDED bit set
  */
 //Note: the last free block's ptr as usize == USIZE::MAX
+const WORDS: usize = LEN / size_of::<usize>();
+pub(crate) const FREE_MAP_LEN: usize = WORDS.div_ceil(64);
+
+//SAFETY for the block helpers: block must point to the first byte of an allocated block
+//the size of a block, works for free blocks too
+unsafe fn block_size(block: *mut usize) -> usize {
+    unsafe {
+        *block & SIZE_MASK & !PADDED
+    }
+}
+
+unsafe fn block_header(block: *mut usize) -> *mut usize {
+    unsafe {
+        if *block & PADDED == 0 {
+            block
+        } else if *block.add(1) & PADDED == 0 {
+            block.add(1)
+        } else {
+            (block as *mut u8).add(*block.add(1) & !PADDED) as *mut usize
+        }
+    }
+}
+
+//the blocks of HEAP, their links are addresses
+fn heap_blocks() -> Blocks {
+    Blocks::new(&raw mut HEAP as *mut u8, &raw mut HEAP as usize)
+}
+
+fn is_free(free_map: &[u64; FREE_MAP_LEN], block: *mut usize) -> bool {
+    let word = (block as usize - &raw const HEAP as usize) / size_of::<usize>();
+    free_map[word / 64] & (1 << (word % 64)) != 0
+}
+
+//the checks of validate_heap on the words of a heap, which is (or was, for a snapshot) at base,
+//the free list and the back pointers are absolute addresses, so they are checked against base,
+//returns a bit for every word where the user data of an allocated block starts
+fn check_heap(words: &[usize], base: usize, first_free: usize, tags: usize) -> Result<[u64; FREE_MAP_LEN + 1], HeapCorruption> {
+    const WORD: usize = size_of::<usize>();
+    debug_assert_eq!(words.len(), WORDS);
+    let corrupted = |offset: usize, reason| Err(HeapCorruption { offset, reason });
+
+    //every free block has to be inside the heap and the list can't be longer than the number of words
+    let mut free_map = [0u64; FREE_MAP_LEN];
+    let mut free_blocks = 0;
+    let mut current = first_free;
+    while current != usize::MAX {
+        let at = current.wrapping_sub(base);
+        if at > LEN - HEADER_SIZE || !at.is_multiple_of(WORD) {
+            return corrupted(at, "free list points outside the heap");
+        }
+
+        let size = words[at / WORD];
+        if size < HEADER_SIZE || !size.is_multiple_of(WORD) || size > LEN - at {
+            return corrupted(at, "free block has an invalid size");
+        }
+
+        free_blocks += 1;
+        if free_blocks > WORDS {
+            return corrupted(at, "free list has a cycle");
+        }
+        free_map[at / WORD / 64] |= 1 << (at / WORD % 64);
+        current = words[at / WORD + 1];
+    }
+
+    //the blocks have to cover the whole heap, each free block of the list has to start one of them
+    //one bit more, the data of an empty block can start at the end of the heap
+    let mut data_map = [0u64; FREE_MAP_LEN + 1];
+    let mut walked_free = 0;
+    let mut at = 0;
+    while at < LEN {
+        let first = words[at / WORD];
+        let size = first & SIZE_MASK & !PADDED;
+        if size < HEADER_SIZE || !size.is_multiple_of(WORD) || size > LEN - at {
+            return corrupted(at, "block has an invalid size");
+        }
+
+        if free_map[at / WORD / 64] & (1 << (at / WORD % 64)) != 0 {
+            walked_free += 1;
+        } else {
+            //the same as block_header
+            let header = if first & PADDED == 0 {
+                Some(at)
+            } else if words[at / WORD + 1] & PADDED == 0 {
+                Some(at + WORD)
+            } else {
+                at.checked_add(words[at / WORD + 1] & !PADDED)
+            };
+            let header = match header {
+                Some(header) if header.is_multiple_of(WORD) && header.checked_add(HEADER_SIZE).is_some_and(|end| end <= at + size) => header,
+                _ => return corrupted(at, "front padding points outside the block"),
+            };
+
+            let (size_word, first_byte) = (words[header / WORD], words[header / WORD + 1]);
+            if size_word & SIZE_MASK != size {
+                return corrupted(at, "header size doesn't match the block size");
+            }
+            if first_byte.wrapping_sub(base) != at {
+                return corrupted(at, "header doesn't point to the first byte of the block");
+            }
+            if (size_word >> TAG_SHIFT) >= tags {
+                return corrupted(at, "block has an unregistered tag");
+            }
+
+            let data = header / WORD + 2;
+            data_map[data / 64] |= 1 << (data % 64);
+        }
+
+        at += size;
+    }
+
+    if walked_free != free_blocks {
+        return corrupted(LEN, "free list has blocks which overlap other blocks");
+    }
+
+    Ok(data_map)
+}
+
+fn check_large(large: &LargeTable, tags: usize) -> Result<(), HeapCorruption> {
+    match large.check(tags) {
+        Some((page, reason)) => Err(HeapCorruption { offset: page * PAGE_SIZE, reason }),
+        None => Ok(()),
+    }
+}
+
+//every used handle has to point to the user data of an allocated block (see check_heap), or to the start of a large run
+fn check_handles(handles: impl Iterator<Item = (usize, usize)>, data_map: &[u64; FREE_MAP_LEN + 1], base: usize, large: &LargeTable, large_base: usize) -> Result<(), HeapCorruption> {
+    for (ptr, alignment) in handles {
+        if ptr == 0 {
+            continue;
+        }
+
+        let at = ptr.wrapping_sub(base);
+        let large_at = ptr.wrapping_sub(large_base);
+        //the large region first, as restore relocates them in this order
+        let valid = if large_at < PAGES * PAGE_SIZE {
+            large_at.is_multiple_of(PAGE_SIZE) && large.runs[large_at / PAGE_SIZE].pages != 0
+        } else {
+            at <= LEN && at.is_multiple_of(size_of::<usize>()) && data_map[at / size_of::<usize>() / 64] & (1 << (at / size_of::<usize>() % 64)) != 0
+        };
+        if !valid || !alignment.is_power_of_two() || !ptr.is_multiple_of(alignment) {
+            let offset = if large_at < PAGES * PAGE_SIZE { large_at } else { at };
+            return Err(HeapCorruption { offset, reason: "handle doesn't point to an allocation" });
+        }
+    }
+
+    Ok(())
+}
+//Note: the upper 16 bits of the allocated block's size hold its tag (see tag.rs)
+//fault: if set, allocations can be made to fail on purpose (see set_fault_policy)
+//tags: names and live usage of the registered tags
+//handles: the current address of every relocatable block (see compact)
+//large: the runs of the large region, allocations of at least large_threshold bytes are served from it
 struct Manager {
     first_free: *mut usize,
+    fault: Option<FaultState>,
+    tags: TagTable,
+    handles: HandleTable,
+    large: LargeTable,
+    large_threshold: usize,
 }
 
 impl Manager {
     const fn new() -> Manager {
         let first_free = &raw mut HEAP as *mut usize;
 
-        Manager { first_free }
+        Manager {
+            first_free,
+            fault: None,
+            tags: TagTable::new(),
+            handles: HandleTable::new(),
+            large: LargeTable::new(),
+            large_threshold: DEFAULT_THRESHOLD,
+        }
     }
 
     //fn to debug free space, used for testing
-    fn debug_free(&self) {
-        println!("\ndebugging free sequences");
-        println!("HEADER_SIZE = {}", HEADER_SIZE);
+    fn debug_free(&self, out: &mut dyn Write) -> fmt::Result {
+        writeln!(out, "\ndebugging free sequences")?;
+        writeln!(out, "HEADER_SIZE = {}", HEADER_SIZE)?;
         let mut free_space = 0;
         let mut current = self.first_free;
         let mut i = 1;
-        loop {
+        while current as usize != usize::MAX {
             let len = unsafe {
                 & *current
             };
-            println!("{}. free sequence len = {} bytes", i, len);
+            writeln!(out, "{}. free sequence len = {} bytes", i, len)?;
             free_space += len;
 
             current = unsafe {
                 *current.add(1) as *mut usize
             };
             i += 1;
+        }
+        writeln!(out, "end\n")?;
+        writeln!(out, "free space: {}", free_space)?;
+
+        let large = self.large.stats();
+        writeln!(out, "large region: {} allocations, {} bytes free, largest free run = {} bytes", large.allocations, large.free_bytes, large.largest_free)
+    }
+
+    unsafe fn alloc(&mut self, size: usize, alignment: usize, tag: u16) -> *mut u8 {
+        if let Some(fault) = &mut self.fault && fault.should_fail(size) {
+            return ptr::null_mut();
+        }
+
+        //the large region has no headers, its table keeps the tag
+        if size >= self.large_threshold {
+            let ptr = self.large.alloc(size, alignment, tag);
+            if !ptr.is_null() {
+                self.tags.record_alloc(tag, size.div_ceil(PAGE_SIZE).max(1) * PAGE_SIZE);
+            }
+            return ptr;
+        }
 
-            if current as usize == usize::MAX {
-                println!("end\n");
-                break;
+        let ptr = unsafe {
+            self.alloc_block(size, alignment)
+        };
+
+        //mark the block with the tag and account for it
+        if !ptr.is_null() {
+            unsafe {
+                let ptr_to_size = (ptr as *mut usize).sub(2);
+                self.tags.record_alloc(tag, *ptr_to_size);
+                *ptr_to_size |= (tag as usize) << TAG_SHIFT;
             }
         }
-        println!("free space: {}", free_space);
+
+        ptr
     }
 
-    unsafe fn alloc(&mut self, size: usize, alignment: usize) -> *mut u8 {
-        //current_free is &mut to the pointer which points to the size (which is the first 4 bytes) of the currently inspected free block
-        let mut current_free = &mut self.first_free;
+    unsafe fn alloc_block(&mut self, size: usize, alignment: usize) -> *mut u8 {
+        let blocks = heap_blocks();
+        let first_free = &raw mut self.first_free as *mut usize;
 
-        let alignment = alignment.max(8);
+        unsafe {
+            match blocks.alloc(first_free, size, alignment.max(8)) {
+                Some(data) => blocks.word(data) as *mut u8,
+                None => ptr::null_mut(),
+            }
+        }
+    }
 
-        let end_pad = (size_of::<usize>() - size % size_of::<usize>()) % size_of::<usize>();
+    //the bytes from ptr to the end of its block (or its run in the large region)
+    unsafe fn usable_size(&self, ptr: *const u8) -> usize {
+        if LargeTable::contains(ptr) {
+            return self.large.usable_size(ptr);
+        }
 
-        loop {
-            //copy the pointee of current_size, so I don't need to dereference it later on each time I need the value
-            let current_size = unsafe {
-                **current_free
-            };
+        unsafe {
+            let size = *(ptr as *const usize).sub(2) & SIZE_MASK;
+            let first_byte = *(ptr as *const usize).sub(1);
+            first_byte + size - ptr as usize
+        }
+    }
 
-            let mut front_pad = unsafe {
-                alignment - (current_free.add(2) as usize % alignment)
-            };
+    unsafe fn shrink(&mut self, ptr: *mut u8, new_size: usize) -> usize {
+        if LargeTable::contains(ptr) {
+            let (released, tag) = self.large.shrink(ptr, new_size);
+            self.tags.record_shrink(tag, released);
+            return self.large.usable_size(ptr);
+        }
 
-            if front_pad == alignment {
-                front_pad = 0;
-            }
+        unsafe {
+            let header = (ptr as *mut usize).sub(2);
+            let first_byte = *header.add(1) as *mut usize;
+            let size = *header & SIZE_MASK;
 
-            let new_size = front_pad + HEADER_SIZE + size + end_pad;
+            let new_block_size = ptr as usize - first_byte as usize + new_size.next_multiple_of(size_of::<usize>());
+            debug_assert!(new_block_size <= size, "shrinking a block to more than its size");
 
-            //if it's (new_size) is larger, than check the next free block
-            if new_size > current_size {
-                current_free = unsafe {
-                    &mut *(current_free.add(1) as *mut *mut usize)
-                };
+            //the same rule as in alloc_block: a remainder of at most HEADER_SIZE stays in the block
+            if new_block_size + HEADER_SIZE < size {
+                let tag = (*header >> TAG_SHIFT) as u16;
+                self.tags.record_shrink(tag, size - new_block_size);
 
-                //if this is the last block (ptr to the next free block as usize is the len of the bytes array), than panic
-                if *current_free as usize == usize::MAX {
-                    panic!("unable to allocate, not enough free space");
+                *header = (*header & !SIZE_MASK) | new_block_size;
+                if header != first_byte {
+                    *first_byte = new_block_size | PADDED;
                 }
+
+                let tail = (first_byte as *mut u8).add(new_block_size) as *mut usize;
+                *tail = size - new_block_size;
+                *tail.add(1) = self.first_free as usize;
+                self.first_free = tail;
             }
-            //if the differrence between the current size and the new size is not greater than the header
-            //than also append those extra bytes to the end of the new allocated block
-            //bc it couldn't be used as a new free block, where later on new data could be allocated
-            else if current_size - new_size <= HEADER_SIZE {
-                let next_free = unsafe {
-                    *current_free.add(1) as *mut usize
-                };
 
-                let front_pad = front_pad / 8;
-                if front_pad != 0 {
-                    unsafe {
-                        *current_free.add(front_pad) = current_size;
-                    }
-                }
+            self.usable_size(ptr)
+        }
+    }
 
+    fn trim(&self) -> usize {
+        #[cfg(all(feature = "std", target_os = "linux", target_pointer_width = "64"))]
+        {
+            let mut released = 0;
+
+            //only the bytes after the header of a free block are unused
+            let mut current = self.first_free;
+            while current as usize != usize::MAX {
                 unsafe {
-                    *current_free.add(front_pad + 1) = *current_free as usize;
+                    released += os::release(current.add(2) as *mut u8, (current as *mut u8).add(*current));
+                    current = *current.add(1) as *mut usize;
+                }
+            }
+
+            self.large.for_each_free(|start, end| released += os::release(start, end));
+            released
+        }
+
+        #[cfg(not(all(feature = "std", target_os = "linux", target_pointer_width = "64")))]
+        0
+    }
+
+    fn free<T>(&mut self, src: *mut T) {
+        if LargeTable::contains(src as *const u8) {
+            let (size, tag) = unsafe {
+                self.large.free(src as *mut u8)
+            };
+            self.tags.record_free(tag, size);
+            return;
+        }
+
+        let size = unsafe {
+            heap_blocks().free(&raw mut self.first_free as *mut usize, src as usize)
+        };
+        self.tags.record_free((size >> TAG_SHIFT) as u16, size & SIZE_MASK);
+    }
+
+
+    //calls f with every allocated block: whether it's in the large region, the index of its first word (or page) and the block
+    fn for_each_allocated(&self, mut f: impl FnMut(bool, usize, Leak)) {
+        let heap = &raw mut HEAP as *mut usize;
+        let free_map = self.free_map();
+
+        let mut word = 0;
+        while word < WORDS {
+            unsafe {
+                let block = heap.add(word);
+                let size = block_size(block);
+                if !is_free(&free_map, block) {
+                    let header = block_header(block);
+                    let tag = Tag::from_id((*header >> TAG_SHIFT) as u16);
+                    f(false, word, Leak { ptr: header.add(2) as *mut u8, size, tag });
                 }
+                word += size / size_of::<usize>();
+            }
+        }
+
+        self.large.for_each_run(|page, ptr, run| {
+            f(true, page, Leak { ptr, size: run.pages as usize * PAGE_SIZE, tag: Tag::from_id(run.tag) });
+        });
+    }
+
+    fn live_blocks(&self) -> LiveBlocks {
+        let mut blocks = LiveBlocks { heap: [0; FREE_MAP_LEN], large: [0; LARGE_MAP_LEN], count: 0, bytes: 0 };
+        for i in 0..self.tags.len() {
+            let usage = self.tags.usage(i);
+            blocks.count += usage.count;
+            blocks.bytes += usage.bytes;
+        }
+
+        self.for_each_allocated(|large, index, _| {
+            let map: &mut [u64] = if large { &mut blocks.large } else { &mut blocks.heap };
+            map[index / 64] |= 1 << (index % 64);
+        });
+        blocks
+    }
+
+    fn diff(&self, old: &LiveBlocks) -> HeapDiff {
+        let now = self.live_blocks();
+        let mut diff = HeapDiff::new(now.count as isize - old.count as isize, now.bytes as isize - old.bytes as isize);
+
+        self.for_each_allocated(|large, index, leak| {
+            let map: &[u64] = if large { &old.large } else { &old.heap };
+            if map[index / 64] & (1 << (index % 64)) == 0 {
+                diff.record(leak);
+            }
+        });
+        diff
+    }
+
+    //a bit for every word of the heap, set if a free block starts there
+    fn free_map(&self) -> [u64; FREE_MAP_LEN] {
+        let heap = &raw const HEAP as usize;
+
+        let mut free_map = [0u64; FREE_MAP_LEN];
+        let mut current = self.first_free;
+        while current as usize != usize::MAX {
+            let word = (current as usize - heap) / size_of::<usize>();
+            free_map[word / 64] |= 1 << (word % 64);
+            current = unsafe {
+                *current.add(1) as *mut usize
+            };
+        }
+        free_map
+    }
 
-                let ptr = unsafe {
-                    current_free.add(2 + front_pad) as *mut u8
+    fn validate(&self) -> Result<(), HeapCorruption> {
+        let words = unsafe {
+            core::slice::from_raw_parts(&raw const HEAP as *const usize, WORDS)
+        };
+        let data_map = check_heap(words, words.as_ptr() as usize, self.first_free as usize, self.tags.len())?;
+        check_large(&self.large, self.tags.len())?;
+
+        let handles = self.handles.slots.iter().map(|slot| (slot.ptr as usize, slot.alignment));
+        check_handles(handles, &data_map, words.as_ptr() as usize, &self.large, LargeTable::base() as usize)
+    }
+
+    //walks the heap from the start, every block is either free (it's in the free list)
+    //or allocated (its first word is its size), the unpinned handle blocks are moved down to dest,
+    //the gaps before the blocks that can't move become the new free blocks, in address order
+    unsafe fn compact(&mut self) -> usize {
+        let heap = &raw mut HEAP as *mut usize;
+        let end = unsafe {
+            heap.add(WORDS)
+        };
+
+        let free_map = self.free_map();
+
+        //the first byte of the block of each movable handle, the ones in the large region stay where they are
+        let mut movable = [ptr::null_mut::<usize>(); MAX_HANDLES];
+        for (i, slot) in self.handles.slots.iter().enumerate() {
+            if !slot.ptr.is_null() && slot.pins == 0 && !LargeTable::contains(slot.ptr) {
+                movable[i] = unsafe {
+                    *(slot.ptr as *mut usize).sub(1) as *mut usize
                 };
+            }
+        }
+
+        let mut free_tail: *mut *mut usize = &mut self.first_free;
+        let mut largest = 0;
+        //the header of the allocated block which ends at dest (moved or not), it gets the leftover bytes
+        //which are too few for a free block, it's only null at the start of the heap,
+        //where the gap is made of whole free blocks and so is never too small
+        let mut last_block: *mut usize = ptr::null_mut();
+        let mut dest = heap;
+        let mut block = heap;
 
-                *current_free = next_free;
+        while block < end {
+            let size = unsafe {
+                block_size(block)
+            };
+            let next_block = unsafe {
+                (block as *mut u8).add(size) as *mut usize
+            };
 
-                return ptr;
-            } 
-            //if the diffrence is larger, than modify the current block
-            else {
-                //create a ptr to the place where i should slide the current free block
-                let new_free = unsafe {
-                    (*current_free as *mut u8).add(new_size) as *mut usize
+            if is_free(&free_map, block) {
+                block = next_block;
+                continue;
+            }
+
+            if let Some(index) = movable.iter().position(|&start| start == block) {
+                let slot = &mut self.handles.slots[index];
+                let header = unsafe {
+                    (slot.ptr as *mut usize).sub(2)
+                };
+                let size_word = unsafe {
+                    *header
                 };
+                let data_len = size - (header as usize - block as usize) - HEADER_SIZE;
+
+                let alignment = slot.alignment.max(8);
+                let front_pad = (alignment - (dest as usize + HEADER_SIZE) % alignment) % alignment;
+                let new_size = front_pad + HEADER_SIZE + data_len;
 
-                //set the size the slided free block
-                //set the slided free block's ptr to point the next free block
                 unsafe {
-                    *new_free = **current_free - new_size;
-                    *new_free.add(1) = *current_free.add(1);
-                }
+                    let new_header = (dest as *mut u8).add(front_pad) as *mut usize;
+                    let new_ptr = new_header.add(2) as *mut u8;
+
+                    //the new place of the data is never after the old one, so ptr::copy is enough
+                    ptr::copy(slot.ptr, new_ptr, data_len);
+
+                    if front_pad != 0 {
+                        mark_front_pad(dest, front_pad, new_size);
+                    }
+                    *new_header = (size_word & !SIZE_MASK) | new_size;
+                    *new_header.add(1) = dest as usize;
 
-                //for allocated block set size
-                //for allocated block set ptr to the first byte (as *mut usize, ill need it as *mut usize for freeing)
-                let front_pad = front_pad / 8;
+                    let tag = (size_word >> TAG_SHIFT) as u16;
+                    self.tags.record_free(tag, size);
+                    self.tags.record_alloc(tag, new_size);
+
+                    slot.ptr = new_ptr;
+                    last_block = new_header;
+                    dest = (dest as *mut u8).add(new_size) as *mut usize;
+                }
+            } else {
                 unsafe {
-                    *current_free.add(front_pad) = new_size;
-                    *current_free.add(front_pad + 1) = *current_free as usize
+                    self.close_gap(dest, block, &mut free_tail, &mut largest, last_block);
+                    last_block = block_header(block);
                 }
+                dest = next_block;
+            }
 
-                //create the output ptr
-                let ptr = unsafe {
-                    current_free.add(front_pad + 2) as *mut u8
-                };
+            block = next_block;
+        }
+
+        unsafe {
+            self.close_gap(dest, end, &mut free_tail, &mut largest, last_block);
+            *free_tail = usize::MAX as *mut usize;
+        }
+
+        largest
+    }
 
-                //update the ptr to the current free block inside the previus free block to point to valid position
-                *current_free = new_free;
+    //turns the bytes between from and to into a free block and appends it to the free list
+    //if there are too few of them (less than a header), they become the end padding of the block before
+    unsafe fn close_gap(&mut self, from: *mut usize, to: *mut usize, free_tail: &mut *mut *mut usize, largest: &mut usize, last_block: *mut usize) {
+        let gap = to as usize - from as usize;
 
-                return ptr;
+        if gap == 0 {
+            return;
+        }
+
+        if gap < HEADER_SIZE {
+            debug_assert!(!last_block.is_null());
+            unsafe {
+                let size = *last_block & SIZE_MASK;
+                let tag = (*last_block >> TAG_SHIFT) as u16;
+                let first_byte = *last_block.add(1) as *mut usize;
+
+                *last_block += gap;
+                if first_byte != last_block {
+                    *first_byte += gap;
+                }
+
+                self.tags.record_free(tag, size);
+                self.tags.record_alloc(tag, size + gap);
             }
+            return;
         }
-    }
 
-    fn free<T>(&mut self, src: *mut T) {
-        //sets the new free block's next free_block idx to the current first_free block's idx
-        let ptr = src as *mut usize;
+        unsafe {
+            *from = gap;
+            **free_tail = from;
+            *free_tail = from.add(1) as *mut *mut usize;
+        }
+        *largest = (*largest).max(gap);
+    }
 
-        let ptr_to_first_byte = unsafe {
-            *ptr.sub(1) as *mut usize
+    #[cfg(feature = "std")]
+    fn image(&self) -> HeapImage {
+        let heap = unsafe {
+            core::slice::from_raw_parts(&raw const HEAP as *const u8, LEN).to_vec()
         };
+        let large = unsafe {
+            core::slice::from_raw_parts(LargeTable::base(), LargeTable::len()).to_vec()
+        };
+
+        HeapImage {
+            base: &raw const HEAP as usize,
+            first_free: self.first_free as usize,
+            handles: self.handles.slots.iter().map(|slot| (slot.ptr as usize, slot.alignment, slot.pins)).collect(),
+            tags: (0..self.tags.len()).map(|i| self.tags.usage(i)).collect(),
+            heap,
+            large_base: LargeTable::base() as usize,
+            large_runs: self.large.runs.iter().map(|run| (run.pages, run.tag)).collect(),
+            large,
+        }
+    }
+
+    //copies the image into the heap, than moves every absolute address by the difference of the bases
+    #[cfg(feature = "std")]
+    unsafe fn restore(&mut self, image: &HeapImage) {
+        let heap = &raw mut HEAP as *mut usize;
+        let delta = (heap as usize).wrapping_sub(image.base);
+        let relocate = |address: usize| address.wrapping_add(delta);
+
+        unsafe {
+            ptr::copy_nonoverlapping(image.heap.as_ptr(), heap as *mut u8, LEN);
+        }
+
+        //the free list
+        let mut current = &mut self.first_free;
+        *current = image.first_free as *mut usize;
+        while *current as usize != usize::MAX {
+            *current = relocate(*current as usize) as *mut usize;
+            current = unsafe {
+                &mut *(current.add(1) as *mut *mut usize)
+            };
+        }
 
-        let ptr_to_size = unsafe {
-            ptr.sub(2)
+        //the back pointers of the allocated blocks
+        let free_map = self.free_map();
+        let end = unsafe {
+            heap.add(WORDS)
         };
+        let mut block = heap;
+        while block < end {
+            unsafe {
+                if !is_free(&free_map, block) {
+                    let header = block_header(block);
+                    *header.add(1) = relocate(*header.add(1));
+                }
+                block = (block as *mut u8).add(block_size(block)) as *mut usize;
+            }
+        }
 
+        //the large region has no absolute addresses inside, only the handles into it have to be moved
         unsafe {
-            *ptr_to_first_byte = *ptr_to_size;
-            *ptr_to_first_byte.add(1) = self.first_free as usize;
+            ptr::copy_nonoverlapping(image.large.as_ptr(), LargeTable::base(), LargeTable::len());
         }
+        for (run, &(pages, tag)) in self.large.runs.iter_mut().zip(&image.large_runs) {
+            *run = LargeRun { pages, tag };
+        }
+        let large_delta = (LargeTable::base() as usize).wrapping_sub(image.large_base);
+        let in_large = |address: usize| (image.large_base..image.large_base + LargeTable::len()).contains(&address);
 
-        //sets the new free block's idx as the first_free block
-        self.first_free = ptr_to_first_byte;
-    }
+        for (slot, &(ptr, alignment, pins)) in self.handles.slots.iter_mut().zip(&image.handles) {
+            slot.ptr = if ptr == 0 {
+                ptr::null_mut()
+            } else if in_large(ptr) {
+                ptr.wrapping_add(large_delta) as *mut u8
+            } else {
+                relocate(ptr) as *mut u8
+            };
+            slot.alignment = alignment;
+            slot.pins = pins;
+        }
 
+        self.tags = TagTable::from_usages(&image.tags);
+    }
 }
\ No newline at end of file
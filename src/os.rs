@@ -0,0 +1,32 @@
+use core::ffi::{c_int, c_long, c_void};
+
+const MADV_DONTNEED: c_int = 4;
+const SC_PAGESIZE: c_int = 30;
+
+unsafe extern "C" {
+    fn madvise(addr: *mut c_void, len: usize, advice: c_int) -> c_int;
+    fn sysconf(name: c_int) -> c_long;
+}
+
+//drops the physical memory of the OS pages which are entirely between start and end,
+//the next access maps them again, returns the released bytes
+pub(crate) fn release(start: *mut u8, end: *mut u8) -> usize {
+    let page = unsafe {
+        sysconf(SC_PAGESIZE)
+    };
+    if page <= 0 {
+        return 0;
+    }
+    let page = page as usize;
+
+    let first = (start as usize).next_multiple_of(page);
+    let last = end as usize / page * page;
+    if first >= last {
+        return 0;
+    }
+
+    let result = unsafe {
+        madvise(first as *mut c_void, last - first, MADV_DONTNEED)
+    };
+    if result == 0 { last - first } else { 0 }
+}
@@ -0,0 +1,201 @@
+use core::{cell::UnsafeCell, fmt::Display, marker::PhantomData, ops::{Deref, DerefMut}, ptr};
+
+use crate::manager::{my_alloc, my_free};
+
+const DEFAULT_CHUNK_SLOTS: usize = 16;
+
+/*
+    Chunk layout:
+    1. *mut u8: points to the next chunk (null for the last one)
+    2. FRONT PADDING: so the first slot has the required alignment
+    3. SLOTS: slots_per_chunk * slot_size bytes
+
+    a free slot stores the ptr to the next free slot in its first bytes,
+    so the free list doesn't need any extra memory
+ */
+pub struct MyPool<T> {
+    state: UnsafeCell<PoolState>,
+    _marker: PhantomData<T>,
+}
+
+struct PoolState {
+    first_free: *mut u8,
+    first_chunk: *mut u8,
+    slots_per_chunk: usize,
+    //FIRST_SLOT + slots_per_chunk * SLOT_SIZE, checked once so add_chunk can't wrap around
+    chunk_size: usize,
+    in_use: usize,
+    chunks: usize,
+    high_water_mark: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolStats {
+    pub in_use: usize,
+    pub chunks: usize,
+    pub high_water_mark: usize,
+}
+
+//the pool itself can be moved to another thread, but handing out slots through &self is not synchronized
+unsafe impl<T: Send> Send for MyPool<T> {}
+
+impl<T> MyPool<T> {
+    pub fn new() -> MyPool<T> {
+        MyPool::with_chunk_slots(DEFAULT_CHUNK_SLOTS)
+    }
+
+    pub fn with_chunk_slots(slots_per_chunk: usize) -> MyPool<T> {
+        assert!(slots_per_chunk > 0);
+        let chunk_size = Self::SLOT_SIZE.checked_mul(slots_per_chunk)
+            .and_then(|slots| slots.checked_add(Self::FIRST_SLOT))
+            .expect("capacity overflow");
+
+        let state = PoolState {
+            first_free: ptr::null_mut(),
+            first_chunk: ptr::null_mut(),
+            slots_per_chunk,
+            chunk_size,
+            in_use: 0,
+            chunks: 0,
+            high_water_mark: 0,
+        };
+
+        MyPool { state: UnsafeCell::new(state), _marker: PhantomData }
+    }
+
+    pub fn alloc(&'_ self, value: T) -> PoolBox<'_, T> {
+        let state = unsafe {
+            &mut *self.state.get()
+        };
+
+        if state.first_free.is_null() {
+            Self::add_chunk(state);
+        }
+
+        let slot = state.first_free;
+        unsafe {
+            state.first_free = *(slot as *mut *mut u8);
+            ptr::write(slot as *mut T, value);
+        }
+
+        state.in_use += 1;
+        state.high_water_mark = state.high_water_mark.max(state.in_use);
+
+        PoolBox { pool: self, ptr: slot as *mut T }
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        let state = unsafe {
+            & *self.state.get()
+        };
+
+        PoolStats { in_use: state.in_use, chunks: state.chunks, high_water_mark: state.high_water_mark }
+    }
+}
+
+//local helper functions
+impl<T> MyPool<T> {
+    //a slot has to be able to hold either a T or the ptr to the next free slot
+    const SLOT_ALIGN: usize = if align_of::<T>() > align_of::<*mut u8>() { align_of::<T>() } else { align_of::<*mut u8>() };
+    const SLOT_SIZE: usize = {
+        let size = if size_of::<T>() > size_of::<*mut u8>() { size_of::<T>() } else { size_of::<*mut u8>() };
+        size.next_multiple_of(Self::SLOT_ALIGN)
+    };
+    const FIRST_SLOT: usize = size_of::<*mut u8>().next_multiple_of(Self::SLOT_ALIGN);
+
+    fn add_chunk(state: &mut PoolState) {
+        let chunk = my_alloc(state.chunk_size, Self::SLOT_ALIGN);
+
+        unsafe {
+            *(chunk as *mut *mut u8) = state.first_chunk;
+
+            //link the new slots in front of the free list, the last one points to the old head
+            let first_slot = chunk.add(Self::FIRST_SLOT);
+            for i in 0..state.slots_per_chunk {
+                let slot = first_slot.add(i * Self::SLOT_SIZE);
+                let next = if i + 1 == state.slots_per_chunk {
+                    state.first_free
+                } else {
+                    slot.add(Self::SLOT_SIZE)
+                };
+                *(slot as *mut *mut u8) = next;
+            }
+
+            state.first_free = first_slot;
+        }
+
+        state.first_chunk = chunk;
+        state.chunks += 1;
+    }
+
+    //SAFETY: ptr must be a slot handed out by this pool, its value already dropped
+    unsafe fn release(&self, ptr: *mut T) {
+        let state = unsafe {
+            &mut *self.state.get()
+        };
+
+        unsafe {
+            *(ptr as *mut *mut u8) = state.first_free;
+        }
+        state.first_free = ptr as *mut u8;
+        state.in_use -= 1;
+    }
+}
+
+impl<T> Default for MyPool<T> {
+    fn default() -> Self {
+        MyPool::new()
+    }
+}
+
+//every PoolBox borrows the pool, so when the pool drops all slots are already free
+impl<T> Drop for MyPool<T> {
+    fn drop(&mut self) {
+        let mut chunk = self.state.get_mut().first_chunk;
+        while !chunk.is_null() {
+            unsafe {
+                let next = *(chunk as *mut *mut u8);
+                my_free(chunk);
+                chunk = next;
+            }
+        }
+    }
+}
+
+pub struct PoolBox<'a, T> {
+    pool: &'a MyPool<T>,
+    ptr: *mut T,
+}
+
+impl<'a, T> Deref for PoolBox<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe {
+            & *self.ptr
+        }
+    }
+}
+
+impl<'a, T> DerefMut for PoolBox<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe {
+            &mut *self.ptr
+        }
+    }
+}
+
+impl<'a, T: Display> Display for PoolBox<'a, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", &**self)
+    }
+}
+
+impl<'a, T> Drop for PoolBox<'a, T> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::drop_in_place(self.ptr);
+            self.pool.release(self.ptr);
+        }
+    }
+}
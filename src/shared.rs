@@ -0,0 +1,297 @@
+use core::{ptr, sync::atomic::{AtomicU32, Ordering}};
+use std::io;
+#[cfg(all(target_os = "linux", target_pointer_width = "64"))]
+use std::{fs::{File, OpenOptions}, path::Path};
+
+use crate::free_list::{Blocks, HEADER_SIZE, NONE};
+
+const MAGIC: u64 = u64::from_ne_bytes(*b"MYSHHEAP");
+//the lock is taken over from a dead owner, after this many failed tries the owner is checked
+const SPINS_BEFORE_CHECK: u32 = 1 << 12;
+
+//the base of the region has to be aligned to this, so offsets and addresses have the same alignment in every process,
+//which is also the largest alignment alloc can give
+pub const REGION_ALIGN: usize = 64;
+
+/*
+    Region layout:
+    1. RegionHeader
+    2. BLOCKS: same as the blocks of the global heap (see manager.rs, free_list.rs), but every link is an offset from the base of the region:
+        free block HEADER: size, offset of the next free block (NONE for the last one)
+        allocated block HEADER: size, offset of the first byte of the block
+ */
+#[repr(C)]
+struct RegionHeader {
+    magic: u64,
+    //0 if unlocked, else the id of the process holding the lock
+    lock: AtomicU32,
+    len: usize,
+    first_free: usize,
+    root: usize,
+}
+
+const DATA_START: usize = size_of::<RegionHeader>().next_multiple_of(REGION_ALIGN);
+
+//a heap inside a memory region which may be mapped at different addresses in different processes
+//every allocation is identified by its offset from the start of the region
+pub struct SharedHeap {
+    base: *mut u8,
+    len: usize,
+    mapped: bool,
+}
+
+unsafe impl Send for SharedHeap {}
+unsafe impl Sync for SharedHeap {}
+
+//unlocks the region when it goes out of scope, also on panic
+struct RegionGuard<'a> {
+    lock: &'a AtomicU32,
+}
+
+impl<'a> Drop for RegionGuard<'a> {
+    fn drop(&mut self) {
+        self.lock.store(0, Ordering::Release);
+    }
+}
+
+impl SharedHeap {
+    /// # Safety
+    /// base must be valid for len bytes for the lifetime of the heap and aligned to REGION_ALIGN,
+    /// the region is formatted, so nothing may use it as a heap at the same time
+    pub unsafe fn init(base: *mut u8, len: usize) -> SharedHeap {
+        assert!((base as usize).is_multiple_of(REGION_ALIGN), "shared heap region is not aligned");
+        assert!(len >= DATA_START + HEADER_SIZE * 2, "shared heap region is too small");
+
+        //the blocks must be multiples of a word
+        let len = len - len % size_of::<usize>();
+
+        unsafe {
+            ptr::write(base as *mut RegionHeader, RegionHeader {
+                magic: MAGIC,
+                lock: AtomicU32::new(0),
+                len,
+                first_free: DATA_START,
+                root: NONE,
+            });
+
+            let first = base.add(DATA_START) as *mut usize;
+            *first = len - DATA_START;
+            *first.add(1) = NONE;
+        }
+
+        SharedHeap { base, len, mapped: false }
+    }
+
+    /// # Safety
+    /// base must be valid for len bytes for the lifetime of the heap and aligned to REGION_ALIGN,
+    /// and it must be a region formatted by init (possibly in another process)
+    pub unsafe fn attach(base: *mut u8, len: usize) -> io::Result<SharedHeap> {
+        if !(base as usize).is_multiple_of(REGION_ALIGN) || len < DATA_START {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "shared heap region is not aligned or too small"));
+        }
+
+        let header = unsafe {
+            & *(base as *const RegionHeader)
+        };
+        if header.magic != MAGIC || header.len > len {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a shared heap region"));
+        }
+
+        Ok(SharedHeap { base, len: header.len, mapped: false })
+    }
+
+    //creates a file of len bytes (e.g. in /dev/shm), maps it and formats it as a heap
+    #[cfg(all(target_os = "linux", target_pointer_width = "64"))]
+    pub fn create(path: impl AsRef<Path>, len: usize) -> io::Result<SharedHeap> {
+        let file = OpenOptions::new().read(true).write(true).create_new(true).open(path)?;
+        file.set_len(len as u64)?;
+
+        let base = sys::map(&file, len)?;
+        let mut heap = unsafe {
+            SharedHeap::init(base, len)
+        };
+        heap.mapped = true;
+        Ok(heap)
+    }
+
+    //maps a file formatted by create, possibly at a different address than in the other processes
+    #[cfg(all(target_os = "linux", target_pointer_width = "64"))]
+    pub fn open(path: impl AsRef<Path>) -> io::Result<SharedHeap> {
+        let file: File = OpenOptions::new().read(true).write(true).open(path)?;
+        let len = file.metadata()?.len() as usize;
+
+        let base = sys::map(&file, len)?;
+        match unsafe { SharedHeap::attach(base, len) } {
+            Ok(mut heap) => {
+                heap.mapped = true;
+                Ok(heap)
+            }
+            Err(e) => {
+                sys::unmap(base, len);
+                Err(e)
+            }
+        }
+    }
+
+    //returns the offset of the allocated bytes, None if there is not enough free space
+    //or alignment is larger than REGION_ALIGN (an offset can't be aligned to more in every process)
+    pub fn alloc(&self, size: usize, alignment: usize) -> Option<usize> {
+        if alignment > REGION_ALIGN {
+            return None;
+        }
+
+        let _guard = self.lock();
+        unsafe {
+            self.blocks().alloc(&raw mut (*self.header()).first_free, size, alignment.max(8))
+        }
+    }
+
+    /// # Safety
+    /// offset must be given by alloc of this region (in any process) and not freed yet
+    pub unsafe fn free(&self, offset: usize) {
+        let _guard = self.lock();
+        unsafe {
+            self.blocks().free(&raw mut (*self.header()).first_free, offset);
+        }
+    }
+
+    //the address of an offset in this process
+    pub fn at<T>(&self, offset: usize) -> *mut T {
+        assert!(offset < self.len);
+        unsafe {
+            self.base.add(offset) as *mut T
+        }
+    }
+
+    //the root is an offset every process can find, e.g. the offset of the shared data structure
+    pub fn root(&self) -> Option<usize> {
+        let _guard = self.lock();
+        let root = unsafe {
+            (*self.header()).root
+        };
+        if root == NONE { None } else { Some(root) }
+    }
+
+    pub fn set_root(&self, offset: Option<usize>) {
+        let _guard = self.lock();
+        unsafe {
+            (*self.header()).root = offset.unwrap_or(NONE);
+        }
+    }
+
+    pub fn free_space(&self) -> usize {
+        let _guard = self.lock();
+        let mut free_space = 0;
+        unsafe {
+            let mut current = (*self.header()).first_free;
+            while current != NONE {
+                free_space += *self.word(current);
+                current = *self.word(current + size_of::<usize>());
+            }
+        }
+        free_space
+    }
+}
+
+//local helper functions
+impl SharedHeap {
+    fn header(&self) -> *mut RegionHeader {
+        self.base as *mut RegionHeader
+    }
+
+    fn blocks(&self) -> Blocks {
+        Blocks::new(self.base, 0)
+    }
+
+    unsafe fn word(&self, offset: usize) -> *mut usize {
+        unsafe {
+            self.blocks().word(offset)
+        }
+    }
+
+    //the lock lives in the region, so it's shared by every process which mapped it
+    //if the process holding it died, it's taken over: the free list is only changed by single release stores
+    //(see free_list.rs), so it's intact, at most the block which was allocated or freed is lost
+    //note: a process id may be reused, so a new process with the dead owner's id keeps the lock
+    fn lock(&'_ self) -> RegionGuard<'_> {
+        let lock = unsafe {
+            &(*self.header()).lock
+        };
+        let id = std::process::id();
+
+        let mut spins = 0u32;
+        loop {
+            match lock.compare_exchange_weak(0, id, Ordering::Acquire, Ordering::Relaxed) {
+                Ok(_) => break,
+                Err(owner) => {
+                    spins = spins.wrapping_add(1);
+                    if spins.is_multiple_of(SPINS_BEFORE_CHECK) && owner != 0 && owner != id && !sys::is_alive(owner)
+                        && lock.compare_exchange(owner, id, Ordering::Acquire, Ordering::Relaxed).is_ok() {
+                        break;
+                    }
+                    core::hint::spin_loop();
+                }
+            }
+        }
+        RegionGuard { lock }
+    }
+}
+
+impl Drop for SharedHeap {
+    fn drop(&mut self) {
+        #[cfg(all(target_os = "linux", target_pointer_width = "64"))]
+        if self.mapped {
+            sys::unmap(self.base, self.len);
+        }
+    }
+}
+
+#[cfg(all(target_os = "linux", target_pointer_width = "64"))]
+mod sys {
+    use core::ffi::{c_int, c_void};
+    use std::{fs::File, io, os::fd::AsRawFd};
+
+    const PROT_READ: c_int = 1;
+    const PROT_WRITE: c_int = 2;
+    const MAP_SHARED: c_int = 1;
+    const EPERM: i32 = 1;
+
+    unsafe extern "C" {
+        fn mmap(addr: *mut c_void, len: usize, prot: c_int, flags: c_int, fd: c_int, offset: i64) -> *mut c_void;
+        fn munmap(addr: *mut c_void, len: usize) -> c_int;
+        fn kill(pid: c_int, sig: c_int) -> c_int;
+    }
+
+    //mmap returns page aligned addresses, so the region is always aligned to REGION_ALIGN
+    pub(super) fn map(file: &File, len: usize) -> io::Result<*mut u8> {
+        let ptr = unsafe {
+            mmap(core::ptr::null_mut(), len, PROT_READ | PROT_WRITE, MAP_SHARED, file.as_raw_fd(), 0)
+        };
+        if ptr as isize == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(ptr as *mut u8)
+    }
+
+    pub(super) fn unmap(base: *mut u8, len: usize) {
+        unsafe {
+            munmap(base as *mut c_void, len);
+        }
+    }
+
+    //kill with no signal only checks if the process exists, EPERM means it exists, but belongs to someone else
+    pub(super) fn is_alive(id: u32) -> bool {
+        let result = unsafe {
+            kill(id as c_int, 0)
+        };
+        result == 0 || io::Error::last_os_error().raw_os_error() == Some(EPERM)
+    }
+}
+
+//without a way to check the owner, it's assumed to be alive
+#[cfg(not(all(target_os = "linux", target_pointer_width = "64")))]
+mod sys {
+    pub(super) fn is_alive(_id: u32) -> bool {
+        true
+    }
+}
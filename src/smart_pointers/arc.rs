@@ -1,14 +1,18 @@
 use core::sync::atomic::{self, AtomicUsize, Ordering};
 use core::ptr;
-use std::fmt::Display;
-use std::ops::Deref;
+use core::fmt::Display;
+use core::ops::Deref;
 
-use crate::manager::{my_alloc, my_free};
+use core::alloc::Layout;
+use core::ptr::NonNull;
 
+use crate::allocator::{allocate_or_panic, GlobalHeap, MyAllocator};
 
 
-pub struct MyArc<T> {
+
+pub struct MyArc<T, A: MyAllocator + Clone = GlobalHeap> {
     inner: *mut MyArcInner<T>,
+    alloc: A,
 }
 
 struct MyArcInner<T> {
@@ -17,12 +21,18 @@ struct MyArcInner<T> {
     weak_count: AtomicUsize,
 }
 
-unsafe impl<T: Send + Sync> Send for MyArc<T> {} 
-unsafe impl<T: Send + Sync> Sync for MyArc<T> {} 
+unsafe impl<T: Send + Sync, A: MyAllocator + Clone + Send + Sync> Send for MyArc<T, A> {} 
+unsafe impl<T: Send + Sync, A: MyAllocator + Clone + Send + Sync> Sync for MyArc<T, A> {} 
 
 impl<T> MyArc<T> {
     pub fn new(value: T) -> MyArc<T> {
-        let inner = my_alloc(size_of::<MyArcInner<T>>(), align_of::<MyArcInner<T>>()) as *mut MyArcInner<T>;
+        MyArc::new_in(value, GlobalHeap)
+    }
+}
+
+impl<T, A: MyAllocator + Clone> MyArc<T, A> {
+    pub fn new_in(value: T, alloc: A) -> MyArc<T, A> {
+        let inner = allocate_or_panic(&alloc, Layout::new::<MyArcInner<T>>()) as *mut MyArcInner<T>;
 
         let inner_value = MyArcInner {
             value, 
@@ -34,17 +44,18 @@ impl<T> MyArc<T> {
             ptr::write(inner, inner_value);
         }
 
-        MyArc { inner }
+        MyArc { inner, alloc }
     }
 
-    pub fn downgrade(&self) -> MyWeak<T> {
+    pub fn downgrade(&self) -> MyWeak<T, A> {
         unsafe {
             (*self.inner).weak_count.fetch_add(1, Ordering::Relaxed);
         }
 
-        MyWeak { inner: self.inner }
+        MyWeak { inner: self.inner, alloc: self.alloc.clone() }
     }
 
+    #[allow(clippy::mut_from_ref)]
     unsafe fn get_inner_mut_ref(&self) -> &mut MyArcInner<T> {
         unsafe {
             &mut *self.inner
@@ -52,23 +63,23 @@ impl<T> MyArc<T> {
     }
 }
 
-impl<T> Clone for MyArc<T> {
+impl<T, A: MyAllocator + Clone> Clone for MyArc<T, A> {
     fn clone(&self) -> Self {
         unsafe {
             (*self.inner).strong_count.fetch_add(1, Ordering::Relaxed);
         }
 
-        Self { inner: self.inner }
+        Self { inner: self.inner, alloc: self.alloc.clone() }
     }
 }
 
-impl<T: Display> Display for MyArc<T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl<T: Display, A: MyAllocator + Clone> Display for MyArc<T, A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", &**self)
     }
 }
 
-impl<T> Deref for MyArc<T> {
+impl<T, A: MyAllocator + Clone> Deref for MyArc<T, A> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
@@ -78,7 +89,7 @@ impl<T> Deref for MyArc<T> {
     }
 }
 
-impl<T> Drop for MyArc<T> {
+impl<T, A: MyAllocator + Clone> Drop for MyArc<T, A> {
     fn drop(&mut self) {
         let inner = unsafe {
             self.get_inner_mut_ref()
@@ -99,17 +110,18 @@ impl<T> Drop for MyArc<T> {
         }
 
         unsafe {
-            my_free(self.inner);
+            self.alloc.deallocate(NonNull::new_unchecked(self.inner as *mut u8), Layout::new::<MyArcInner<T>>());
         }
     }
 }
 
-pub struct MyWeak<T> {
+pub struct MyWeak<T, A: MyAllocator + Clone = GlobalHeap> {
     inner: *mut MyArcInner<T>,
+    alloc: A,
 }
 
-impl<T> MyWeak<T> {
-    pub fn upgrade(&self) -> Option<MyArc<T>> {
+impl<T, A: MyAllocator + Clone> MyWeak<T, A> {
+    pub fn upgrade(&self) -> Option<MyArc<T, A>> {
         let inner = unsafe {
             &mut *self.inner
         };
@@ -124,24 +136,24 @@ impl<T> MyWeak<T> {
                 Ordering::Acquire,
                 Ordering::Relaxed
             ) {
-                Ok(_) => return Some(MyArc {inner: self.inner}),
+                Ok(_) => return Some(MyArc {inner: self.inner, alloc: self.alloc.clone()}),
                 Err(prev) => current = prev,
             }
         }
     }
 }
 
-impl<T> Clone for MyWeak<T> {
+impl<T, A: MyAllocator + Clone> Clone for MyWeak<T, A> {
     fn clone(&self) -> Self {
         unsafe {
             (*self.inner).weak_count.fetch_add(1, Ordering::Relaxed);
         }
 
-        Self { inner: self.inner }
+        Self { inner: self.inner, alloc: self.alloc.clone() }
     }
 }
 
-impl<T> Drop for MyWeak<T> {
+impl<T, A: MyAllocator + Clone> Drop for MyWeak<T, A> {
     fn drop(&mut self) {
         let inner = unsafe {
             &mut *self.inner
@@ -150,7 +162,7 @@ impl<T> Drop for MyWeak<T> {
         if inner.weak_count.fetch_sub(1, Ordering::Release) == 1 &&
         inner.strong_count.load(Ordering::Acquire) == 0 {
             unsafe {
-                my_free(self.inner);
+                self.alloc.deallocate(NonNull::new_unchecked(self.inner as *mut u8), Layout::new::<MyArcInner<T>>());
             }
         }
     }
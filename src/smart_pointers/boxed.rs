@@ -1,37 +1,78 @@
-use core::{fmt::Display, ops::{Deref, DerefMut}, ptr, marker::{Send, Sync}};
+use core::{alloc::Layout, fmt::Display, ops::{Deref, DerefMut}, ptr::{self, NonNull}, marker::{Send, Sync}};
 
-use crate::manager::{my_alloc, my_free};
+use crate::allocator::{allocate_or_panic, AllocError, GlobalHeap, MyAllocator};
 
 
 
-pub struct MyBox<T> {
+//T can be unsized (e.g. a slice from MyVec::into_boxed_slice), a zero sized value is not allocated
+pub struct MyBox<T: ?Sized, A: MyAllocator = GlobalHeap> {
     ptr: *mut T,
+    alloc: A,
 }
 
-unsafe impl<T: Send> Send for MyBox<T> {}
-unsafe impl<T: Sync> Sync for MyBox<T> {}
+unsafe impl<T: ?Sized + Send, A: MyAllocator + Send> Send for MyBox<T, A> {}
+unsafe impl<T: ?Sized + Sync, A: MyAllocator + Sync> Sync for MyBox<T, A> {}
 
 
 impl<T> MyBox<T> {
     pub fn new(value: T) -> MyBox<T> {
-        let ptr = unsafe {
-            let ptr = my_alloc(size_of::<T>(), align_of::<T>()) as *mut T;
+        MyBox::new_in(value, GlobalHeap)
+    }
+
+    pub fn try_new(value: T) -> Result<MyBox<T>, AllocError> {
+        MyBox::try_new_in(value, GlobalHeap)
+    }
+}
+
+impl<T, A: MyAllocator> MyBox<T, A> {
+    pub fn new_in(value: T, alloc: A) -> MyBox<T, A> {
+        let ptr = if size_of::<T>() == 0 {
+            NonNull::dangling().as_ptr()
+        } else {
+            allocate_or_panic(&alloc, Layout::new::<T>()) as *mut T
+        };
+        unsafe {
             ptr::write(ptr, value);
-            ptr
+        }
+
+        MyBox { ptr, alloc }
+    }
+
+    //on failure the value is dropped
+    pub fn try_new_in(value: T, alloc: A) -> Result<MyBox<T, A>, AllocError> {
+        let ptr = if size_of::<T>() == 0 {
+            NonNull::dangling().as_ptr()
+        } else {
+            alloc.allocate(Layout::new::<T>())?.as_ptr() as *mut T
         };
-        
+        unsafe {
+            ptr::write(ptr, value);
+        }
+
+        Ok(MyBox { ptr, alloc })
+    }
+}
+
+impl<T: ?Sized, A: MyAllocator> MyBox<T, A> {
+    /// # Safety
+    /// ptr must point to a valid T, allocated by alloc with Layout::for_value (or dangling if that's zero sized),
+    /// the box takes ownership of both the value and the memory
+    pub unsafe fn from_raw_in(ptr: *mut T, alloc: A) -> MyBox<T, A> {
+        MyBox { ptr, alloc }
+    }
 
-        MyBox { ptr }
+    pub fn allocator(&self) -> &A {
+        &self.alloc
     }
 }
 
-impl<T: Clone> Clone for MyBox<T> {
+impl<T: Clone, A: MyAllocator + Clone> Clone for MyBox<T, A> {
     fn clone(&self) -> Self {
-        MyBox::new((**self).clone())
+        MyBox::new_in((**self).clone(), self.alloc.clone())
     }
 }
 
-impl<T> Deref for MyBox<T> {
+impl<T: ?Sized, A: MyAllocator> Deref for MyBox<T, A> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
@@ -41,7 +82,7 @@ impl<T> Deref for MyBox<T> {
     }
 }
 
-impl<T> DerefMut for MyBox<T> {
+impl<T: ?Sized, A: MyAllocator> DerefMut for MyBox<T, A> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         unsafe {
             &mut *self.ptr
@@ -49,18 +90,21 @@ impl<T> DerefMut for MyBox<T> {
     }
 }
 
-impl<T> Display for MyBox<T>
+impl<T: ?Sized, A: MyAllocator> Display for MyBox<T, A>
 where T: Display  {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", &**self)
     }
 }
 
-impl<T> Drop for MyBox<T> {
+impl<T: ?Sized, A: MyAllocator> Drop for MyBox<T, A> {
     fn drop(&mut self) {
         unsafe {
+            let layout = Layout::for_value(&*self.ptr);
             ptr::drop_in_place(self.ptr);
-            my_free(self.ptr);
+            if layout.size() != 0 {
+                self.alloc.deallocate(NonNull::new_unchecked(self.ptr as *mut u8), layout);
+            }
         }
     }
 }
\ No newline at end of file
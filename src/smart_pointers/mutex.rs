@@ -30,7 +30,7 @@ impl<T> MyMutex<T> {
             core::hint::spin_loop();
         };
 
-        MyMutexGuard { mutex: &self, _not_send: PhantomData }
+        MyMutexGuard { mutex: self, _not_send: PhantomData }
     }
 }
 
@@ -59,7 +59,7 @@ impl<'a, T> Drop for MyMutexGuard<'a, T> {
 }
 
 impl<'a, T: Display> Display for MyMutexGuard<'a, T>  {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", &**self)
     }
 }
\ No newline at end of file
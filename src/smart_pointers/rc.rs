@@ -1,17 +1,24 @@
-use core::{fmt::Display, ops::Deref, ptr};
+use core::{alloc::Layout, fmt::Display, ops::Deref, ptr::{self, NonNull}};
 
-use crate::manager::{my_alloc, my_free};
+use crate::allocator::{allocate_or_panic, GlobalHeap, MyAllocator};
 
-pub struct MyRc<T> {
+pub struct MyRc<T, A: MyAllocator + Clone = GlobalHeap> {
     strong_ptr: *mut usize,
     weak_ptr: *mut usize,
     value_ptr: *mut T,
+    alloc: A,
 }
 
 impl<T> MyRc<T> {
     pub fn new(value: T) -> MyRc<T> {
+        MyRc::new_in(value, GlobalHeap)
+    }
+}
+
+impl<T, A: MyAllocator + Clone> MyRc<T, A> {
+    pub fn new_in(value: T, alloc: A) -> MyRc<T, A> {
         let (strong_ptr, weak_ptr, value_ptr) = unsafe {
-            let ptr = my_alloc(size_of::<(usize, usize, T)>(), align_of::<(usize, usize, T)>());
+            let ptr = allocate_or_panic(&alloc, layout::<T>());
 
             let strong_ptr = ptr as *mut usize;
             let weak_ptr = ptr.add(size_of::<usize>()) as *mut usize;
@@ -24,19 +31,24 @@ impl<T> MyRc<T> {
             (strong_ptr, weak_ptr, value_ptr)
         };
 
-        MyRc { strong_ptr, weak_ptr, value_ptr }
+        MyRc { strong_ptr, weak_ptr, value_ptr, alloc }
     }
 
-    pub fn downgrade(&self) -> MyWeak<T> {
+    pub fn downgrade(&self) -> MyWeak<T, A> {
         unsafe {
             *self.weak_ptr += 1;
         }
 
-        MyWeak { strong_ptr: self.strong_ptr, weak_ptr: self.weak_ptr, value_ptr: self.value_ptr }
+        MyWeak { strong_ptr: self.strong_ptr, weak_ptr: self.weak_ptr, value_ptr: self.value_ptr, alloc: self.alloc.clone() }
     }
 }
 
-impl<T> Deref for MyRc<T> {
+//the counters and the value share one block
+fn layout<T>() -> Layout {
+    Layout::new::<(usize, usize, T)>()
+}
+
+impl<T, A: MyAllocator + Clone> Deref for MyRc<T, A> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
@@ -46,53 +58,56 @@ impl<T> Deref for MyRc<T> {
     }
 }
 
-impl<T> Display for MyRc<T>
+impl<T, A: MyAllocator + Clone> Display for MyRc<T, A>
 where T: Display  {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", &**self)
     }
 }
 
-impl<T> Clone for MyRc<T> {    
+impl<T, A: MyAllocator + Clone> Clone for MyRc<T, A> {    
     fn clone(&self) -> Self {
         unsafe {
             *self.strong_ptr += 1;
         }
 
-        Self { strong_ptr: self.strong_ptr, weak_ptr: self.weak_ptr, value_ptr: self.value_ptr }
+        Self { strong_ptr: self.strong_ptr, weak_ptr: self.weak_ptr, value_ptr: self.value_ptr, alloc: self.alloc.clone() }
     }
 }
 
-impl<T> Drop for MyRc<T> {
+impl<T, A: MyAllocator + Clone> Drop for MyRc<T, A> {
     fn drop(&mut self) {
         unsafe {
             *self.strong_ptr -= 1;
             if *self.strong_ptr == 0 {
                 ptr::drop_in_place(self.value_ptr);
                 if *self.weak_ptr == 0 {
-                    my_free(self.strong_ptr);
+                    self.alloc.deallocate(NonNull::new_unchecked(self.strong_ptr as *mut u8), layout::<T>());
                 }
             }
         }
     }
 }
 
-pub struct MyWeak<T> {
+pub struct MyWeak<T, A: MyAllocator + Clone = GlobalHeap> {
     strong_ptr: *mut usize,
     weak_ptr: *mut usize,
     value_ptr: *mut T,
+    alloc: A,
 }
 
-impl<T> MyWeak<T> {
-    pub unsafe fn upgrade_unchecked(&self) -> MyRc<T> {
+impl<T, A: MyAllocator + Clone> MyWeak<T, A> {
+    /// # Safety
+    /// the value must still be alive (strong count is not 0)
+    pub unsafe fn upgrade_unchecked(&self) -> MyRc<T, A> {
         unsafe {
             *self.strong_ptr += 1;
         }
 
-        MyRc { strong_ptr: self.strong_ptr, weak_ptr: self.weak_ptr, value_ptr: self.value_ptr }
+        MyRc { strong_ptr: self.strong_ptr, weak_ptr: self.weak_ptr, value_ptr: self.value_ptr, alloc: self.alloc.clone() }
     }
 
-    pub fn upgrade(&self) -> Option<MyRc<T>> {
+    pub fn upgrade(&self) -> Option<MyRc<T, A>> {
         unsafe {
             if *self.strong_ptr != 0 {
                 Some(self.upgrade_unchecked())
@@ -103,22 +118,22 @@ impl<T> MyWeak<T> {
     }
 }
 
-impl<T> Clone for MyWeak<T> {
+impl<T, A: MyAllocator + Clone> Clone for MyWeak<T, A> {
     fn clone(&self) -> Self {
         unsafe {
             *self.weak_ptr += 1;
         }
 
-        Self { strong_ptr: self.strong_ptr, weak_ptr: self.weak_ptr, value_ptr: self.value_ptr }
+        Self { strong_ptr: self.strong_ptr, weak_ptr: self.weak_ptr, value_ptr: self.value_ptr, alloc: self.alloc.clone() }
     }
 }
 
-impl<T> Drop for MyWeak<T> {
+impl<T, A: MyAllocator + Clone> Drop for MyWeak<T, A> {
     fn drop(&mut self) {
         unsafe {
             *self.weak_ptr -= 1;
             if *self.strong_ptr == 0 && *self.weak_ptr == 0 {
-                my_free(self.strong_ptr);
+                self.alloc.deallocate(NonNull::new_unchecked(self.strong_ptr as *mut u8), layout::<T>());
             }
         }
     }
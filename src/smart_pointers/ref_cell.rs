@@ -28,7 +28,7 @@ impl<T> MyRefCell<T> {
 
         if *state != -1 {
             *state += 1;
-            MyRef { ref_cell: &self }
+            MyRef { ref_cell: self }
         } else {
             panic!("already mutably borrowed: BorrowMutError")
         }
@@ -41,7 +41,7 @@ impl<T> MyRefCell<T> {
 
         if *state == 0 {
             *state = -1;
-            MyRefMut { ref_cell: &self} 
+            MyRefMut { ref_cell: self} 
         } else {
             panic!("already borrowed: BorrowError")
         }
@@ -67,7 +67,7 @@ impl<'a, T> Drop for MyRef<'a, T> {
 }
 
 impl<'a, T: Display> Display for MyRef<'a, T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", &**self)
     }
 }
@@ -99,7 +99,7 @@ impl<'a, T> Drop for MyRefMut<'a, T> {
 }
 
 impl<'a, T: Display> Display for MyRefMut<'a, T> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", &**self)
     }
 }
\ No newline at end of file
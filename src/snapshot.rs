@@ -0,0 +1,182 @@
+use std::{fs, io, path::Path, sync::{Mutex, PoisonError}};
+
+use crate::manager::registered_tag_name;
+use crate::tag::{Tag, TagUsage};
+
+const MAGIC: [u8; 8] = *b"MYHEAP\0\0";
+const VERSION: u32 = 3;
+//written in the native byte order, it reads back swapped on a machine with the other one
+const BYTE_ORDER_MARK: u32 = 0x0102_0304;
+
+/*
+    Snapshot file layout (every number is little endian, except the byte order mark):
+    1. MAGIC
+    2. u32: VERSION
+    3. u32: size of usize in bytes
+    4. u32: BYTE_ORDER_MARK in the byte order of the machine which made the snapshot
+    5. u64: heap size in bytes
+    6. u64: base address of the heap when the snapshot was made
+    7. u64: the first free block (absolute address, usize::MAX if there is none)
+    8. u32: handle count, then for each handle: u64 ptr (absolute, 0 if unused), u64 alignment, u64 pins
+    9. u32: tag count, then for each tag: u16 name length, name bytes, u64 live bytes, u64 live count
+    10. the heap bytes (in native byte order, the absolute addresses inside still point into the old heap)
+    11. u64: base address of the large region when the snapshot was made
+    12. u32: page count, then for each page: u16 run length, u16 tag (see large.rs)
+    13. u64: large region size in bytes, then its bytes
+ */
+pub(crate) struct HeapImage {
+    pub(crate) base: usize,
+    pub(crate) first_free: usize,
+    pub(crate) handles: Vec<(usize, usize, usize)>,
+    pub(crate) tags: Vec<TagUsage>,
+    pub(crate) heap: Vec<u8>,
+    pub(crate) large_base: usize,
+    pub(crate) large_runs: Vec<(u16, u16)>,
+    pub(crate) large: Vec<u8>,
+}
+
+impl HeapImage {
+    pub(crate) fn write_to(&self, path: &Path) -> io::Result<()> {
+        let mut out = Vec::with_capacity(self.heap.len() + self.large.len() + 256);
+
+        out.extend_from_slice(&MAGIC);
+        out.extend_from_slice(&VERSION.to_le_bytes());
+        out.extend_from_slice(&(size_of::<usize>() as u32).to_le_bytes());
+        out.extend_from_slice(&BYTE_ORDER_MARK.to_ne_bytes());
+        out.extend_from_slice(&(self.heap.len() as u64).to_le_bytes());
+        out.extend_from_slice(&(self.base as u64).to_le_bytes());
+        out.extend_from_slice(&(self.first_free as u64).to_le_bytes());
+
+        out.extend_from_slice(&(self.handles.len() as u32).to_le_bytes());
+        for &(ptr, alignment, pins) in &self.handles {
+            out.extend_from_slice(&(ptr as u64).to_le_bytes());
+            out.extend_from_slice(&(alignment as u64).to_le_bytes());
+            out.extend_from_slice(&(pins as u64).to_le_bytes());
+        }
+
+        out.extend_from_slice(&(self.tags.len() as u32).to_le_bytes());
+        for tag in &self.tags {
+            out.extend_from_slice(&(tag.name.len() as u16).to_le_bytes());
+            out.extend_from_slice(tag.name.as_bytes());
+            out.extend_from_slice(&(tag.bytes as u64).to_le_bytes());
+            out.extend_from_slice(&(tag.count as u64).to_le_bytes());
+        }
+
+        out.extend_from_slice(&self.heap);
+
+        out.extend_from_slice(&(self.large_base as u64).to_le_bytes());
+        out.extend_from_slice(&(self.large_runs.len() as u32).to_le_bytes());
+        for &(pages, tag) in &self.large_runs {
+            out.extend_from_slice(&pages.to_le_bytes());
+            out.extend_from_slice(&tag.to_le_bytes());
+        }
+        out.extend_from_slice(&(self.large.len() as u64).to_le_bytes());
+        out.extend_from_slice(&self.large);
+
+        fs::write(path, out)
+    }
+
+    //tag names which aren't registered or read before are leaked, so they can be used as &'static str again
+    pub(crate) fn read_from(path: &Path) -> io::Result<HeapImage> {
+        let bytes = fs::read(path)?;
+        let mut reader = Reader { bytes: &bytes, pos: 0 };
+
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(invalid("not a heap snapshot"));
+        }
+        if reader.u32()? != VERSION {
+            return Err(invalid("unsupported snapshot version"));
+        }
+        if reader.u32()? as usize != size_of::<usize>() {
+            return Err(invalid("snapshot was made with a different pointer width"));
+        }
+        if u32::from_ne_bytes(reader.take(4)?.try_into().unwrap()) != BYTE_ORDER_MARK {
+            return Err(invalid("snapshot was made with a different byte order"));
+        }
+
+        let heap_len = reader.usize()?;
+        let base = reader.usize()?;
+        let first_free = reader.usize()?;
+
+        let mut handles = Vec::new();
+        for _ in 0..reader.u32()? {
+            handles.push((reader.usize()?, reader.usize()?, reader.usize()?));
+        }
+
+        let mut tags = Vec::new();
+        for i in 0..reader.u32()? {
+            let name_len = reader.u16()? as usize;
+            let name = str::from_utf8(reader.take(name_len)?).map_err(|_| invalid("tag name is not utf-8"))?;
+            let name = intern(name);
+            tags.push(TagUsage { tag: Tag::from_id(i as u16), name, bytes: reader.usize()?, count: reader.usize()? });
+        }
+
+        let heap = reader.take(heap_len)?.to_vec();
+
+        let large_base = reader.usize()?;
+        let mut large_runs = Vec::new();
+        for _ in 0..reader.u32()? {
+            large_runs.push((reader.u16()?, reader.u16()?));
+        }
+        let large_len = reader.usize()?;
+        let large = reader.take(large_len)?.to_vec();
+
+        if reader.pos != bytes.len() {
+            return Err(invalid("trailing bytes after the large region"));
+        }
+
+        Ok(HeapImage { base, first_free, handles, tags, heap, large_base, large_runs, large })
+    }
+}
+
+//the names leaked by earlier restores
+static READ_NAMES: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+//a &'static str for a tag name from a file, leaked only the first time it's seen
+fn intern(name: &str) -> &'static str {
+    if let Some(registered) = registered_tag_name(name) {
+        return registered;
+    }
+
+    let mut names = READ_NAMES.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(&known) = names.iter().find(|&&known| known == name) {
+        return known;
+    }
+    let leaked: &'static str = Box::leak(name.to_owned().into_boxed_str());
+    names.push(leaked);
+    leaked
+}
+
+pub(crate) fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        let end = self.pos.checked_add(len).filter(|&end| end <= self.bytes.len()).ok_or_else(|| invalid("snapshot is truncated"))?;
+        let out = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(out)
+    }
+
+    fn u16(&mut self) -> io::Result<u16> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn usize(&mut self) -> io::Result<usize> {
+        usize::try_from(self.u64()?).map_err(|_| invalid("value doesn't fit in usize"))
+    }
+}
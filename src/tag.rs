@@ -0,0 +1,156 @@
+#[cfg(feature = "std")]
+use core::cell::Cell;
+use core::fmt::Display;
+#[cfg(not(feature = "std"))]
+use core::sync::atomic::{AtomicU16, Ordering};
+
+pub(crate) const MAX_TAGS: usize = 64;
+
+//the tag is stored in the upper 16 bits of the allocated block's size
+pub(crate) const TAG_SHIFT: u32 = usize::BITS - 16;
+pub(crate) const SIZE_MASK: usize = (1 << TAG_SHIFT) - 1;
+
+//identifies which part of the program an allocation belongs to, created by register_tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Tag(u16);
+
+impl Tag {
+    pub const UNTAGGED: Tag = Tag(0);
+
+    pub fn id(&self) -> u16 {
+        self.0
+    }
+
+    pub(crate) fn from_id(id: u16) -> Tag {
+        Tag(id)
+    }
+}
+
+//register_tag failed, every one of the MAX_TAGS tags is taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TooManyTags;
+
+impl Display for TooManyTags {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "unable to register tag, too many tags")
+    }
+}
+
+//bytes: sum of the allocated blocks' size (including header and padding)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TagUsage {
+    pub tag: Tag,
+    pub name: &'static str,
+    pub bytes: usize,
+    pub count: usize,
+}
+
+#[cfg(feature = "std")]
+std::thread_local! {
+    static CURRENT_TAG: Cell<u16> = const { Cell::new(0) };
+}
+
+//without threads there is a single current tag for the whole program
+#[cfg(not(feature = "std"))]
+static CURRENT_TAG: AtomicU16 = AtomicU16::new(0);
+
+//the tag new allocations of this thread get
+pub(crate) fn current_tag() -> u16 {
+    #[cfg(feature = "std")]
+    return CURRENT_TAG.with(|tag| tag.get());
+    #[cfg(not(feature = "std"))]
+    return CURRENT_TAG.load(Ordering::Relaxed);
+}
+
+//sets the current tag, returns the previous one
+fn replace_tag(tag: u16) -> u16 {
+    #[cfg(feature = "std")]
+    return CURRENT_TAG.with(|current| current.replace(tag));
+    #[cfg(not(feature = "std"))]
+    return CURRENT_TAG.swap(tag, Ordering::Relaxed);
+}
+
+//restores the previous tag, even if the closure panics
+struct TagGuard(u16);
+
+impl Drop for TagGuard {
+    fn drop(&mut self) {
+        replace_tag(self.0);
+    }
+}
+
+//every allocation of this thread made inside f is attributed to tag
+pub fn with_tag<R>(tag: Tag, f: impl FnOnce() -> R) -> R {
+    let _guard = TagGuard(replace_tag(tag.0));
+    f()
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct TagTable {
+    names: [&'static str; MAX_TAGS],
+    usage: [(usize, usize); MAX_TAGS],
+    len: usize,
+}
+
+impl TagTable {
+    pub(crate) const fn new() -> TagTable {
+        let mut names = [""; MAX_TAGS];
+        names[0] = "untagged";
+
+        TagTable { names, usage: [(0, 0); MAX_TAGS], len: 1 }
+    }
+
+    pub(crate) fn register(&mut self, name: &'static str) -> Result<Tag, TooManyTags> {
+        if self.len == MAX_TAGS {
+            return Err(TooManyTags);
+        }
+
+        self.names[self.len] = name;
+        self.len += 1;
+        Ok(Tag((self.len - 1) as u16))
+    }
+
+    pub(crate) fn record_alloc(&mut self, tag: u16, bytes: usize) {
+        let usage = &mut self.usage[tag as usize];
+        usage.0 += bytes;
+        usage.1 += 1;
+    }
+
+    pub(crate) fn record_free(&mut self, tag: u16, bytes: usize) {
+        let usage = &mut self.usage[tag as usize];
+        usage.0 -= bytes;
+        usage.1 -= 1;
+    }
+
+    //a block of the tag gave back some of its bytes, the count stays the same
+    pub(crate) fn record_shrink(&mut self, tag: u16, bytes: usize) {
+        self.usage[tag as usize].0 -= bytes;
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn find(&self, name: &str) -> Option<&'static str> {
+        self.names[..self.len].iter().find(|&&registered| registered == name).copied()
+    }
+
+    //rebuilds the table from saved usages (see restore)
+    #[cfg(feature = "std")]
+    pub(crate) fn from_usages(usages: &[TagUsage]) -> TagTable {
+        assert!(!usages.is_empty() && usages.len() <= MAX_TAGS);
+
+        let mut table = TagTable::new();
+        for (i, usage) in usages.iter().enumerate() {
+            table.names[i] = usage.name;
+            table.usage[i] = (usage.bytes, usage.count);
+        }
+        table.len = usages.len();
+        table
+    }
+
+    pub(crate) fn usage(&self, i: usize) -> TagUsage {
+        TagUsage { tag: Tag(i as u16), name: self.names[i], bytes: self.usage[i].0, count: self.usage[i].1 }
+    }
+}
@@ -0,0 +1,89 @@
+//MyAllocator tests: the default grow and shrink, the &A impl and the containers' new_in
+
+mod support {
+    pub mod counting;
+    pub mod serial;
+}
+
+use std::alloc::Layout;
+
+use memory_manager::arc::MyArc;
+use memory_manager::boxed::MyBox;
+use memory_manager::rc::MyRc;
+use memory_manager::{HeapCheckpoint, MyAllocator, MyVec};
+use support::counting::Counting;
+use support::serial::heap;
+
+#[test]
+fn default_grow_and_shrink_move_the_values() {
+    let counting = Counting::default();
+
+    let mut v = MyVec::new_in(&counting);
+    for i in 0..100u32 {
+        v.push(i);
+    }
+    //every grow allocated a new block and freed the old one
+    assert!(counting.allocations.get() > 1);
+    assert_eq!(counting.live(), 1);
+    assert_eq!(counting.live_bytes.get(), v.capacity() * size_of::<u32>());
+    assert!(v.iter().copied().eq(0..100));
+    drop(v);
+    assert_eq!((counting.live(), counting.live_bytes.get()), (0, 0));
+
+    let old = Layout::array::<u32>(8).unwrap();
+    let new = Layout::array::<u32>(2).unwrap();
+    unsafe {
+        let ptr = counting.allocate(old).unwrap().cast::<u32>();
+        for i in 0..8 {
+            ptr.add(i).write(i as u32);
+        }
+        let ptr = counting.shrink(ptr.cast(), old, new).unwrap().cast::<u32>();
+        assert_eq!((ptr.read(), ptr.add(1).read()), (0, 1));
+        assert_eq!((counting.live(), counting.live_bytes.get()), (1, 8));
+        counting.deallocate(ptr.cast(), new);
+    }
+    assert_eq!((counting.live(), counting.live_bytes.get()), (0, 0));
+}
+
+#[test]
+fn the_global_heap_shrinks_in_place() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    let mut v: MyVec<u64> = MyVec::with_capacity(64);
+    v.extend([1, 2, 3]);
+    let ptr = v.as_ptr();
+    v.shrink_to_fit();
+    assert_eq!(v.as_ptr(), ptr);
+    assert!(v.capacity() < 64);
+    assert_eq!(v, [1, 2, 3]);
+
+    drop(v);
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn containers_share_an_allocator_by_reference() {
+    let counting = Counting::default();
+
+    let mut v = MyVec::new_in(&counting);
+    for i in 1..=3u8 {
+        v.push(i);
+    }
+    let b = MyBox::new_in(7u64, &counting);
+    let rc = MyRc::new_in(1u32, &counting);
+    let arc = MyArc::new_in(5u16, &counting);
+    assert_eq!(counting.live(), 4);
+
+    let rc2 = rc.clone();
+    let weak = arc.downgrade();
+    assert_eq!((v.len(), *b, *rc2, weak.upgrade().map(|a| *a)), (3, 7, 1, Some(5)));
+    assert_eq!(counting.live(), 4);
+
+    drop((v, b, rc, rc2));
+    assert_eq!(counting.live(), 1);
+    drop(arc);
+    assert!(weak.upgrade().is_none());
+    drop(weak);
+    assert_eq!((counting.live(), counting.live_bytes.get()), (0, 0));
+}
@@ -0,0 +1,171 @@
+//MyBinaryHeap tests, checked against std's BinaryHeap
+
+mod support {
+    pub mod serial;
+    pub mod rng;
+}
+
+use std::cell::Cell;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use memory_manager::{my_vec, HeapCheckpoint, MyBinaryHeap, MyPeekMut, MyVec};
+use support::serial::heap;
+use support::rng::Rng;
+
+#[test]
+fn random_operations_match_std() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+    let mut rng = Rng(0x4EA9);
+
+    let mut mine = MyBinaryHeap::new();
+    let mut std = BinaryHeap::new();
+    for _ in 0..4000 {
+        match rng.below(8) {
+            0..=3 => {
+                let value = rng.below(1000);
+                mine.push(value);
+                std.push(value);
+            }
+            4..=5 => assert_eq!(mine.pop(), std.pop()),
+            6 => {
+                //lowers or raises the top, the guard puts it back in place
+                let delta = rng.below(500);
+                if let (Some(mut a), Some(mut b)) = (mine.peek_mut(), std.peek_mut()) {
+                    *a = a.saturating_sub(delta);
+                    *b = b.saturating_sub(delta);
+                }
+            }
+            _ => {
+                if let (Some(a), Some(b)) = (mine.peek_mut(), std.peek_mut()) {
+                    assert_eq!(MyPeekMut::pop(a), std::collections::binary_heap::PeekMut::pop(b));
+                }
+            }
+        }
+        assert_eq!(mine.len(), std.len());
+        assert_eq!(mine.peek(), std.peek());
+    }
+
+    let sorted = mine.clone().into_sorted_vec();
+    assert_eq!(sorted, std.clone().into_sorted_vec().as_slice());
+    let mut popped = Vec::new();
+    while let Some(value) = mine.pop() {
+        popped.push(value);
+    }
+    assert!(popped.iter().rev().eq(sorted.iter()));
+    drop((mine, sorted));
+
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn heapify_in_place() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+    let mut rng = Rng(0xF1F0);
+
+    for len in [0, 1, 2, 3, 7, 8, 100] {
+        let values: MyVec<usize> = (0..len).map(|_| rng.below(50)).collect();
+        let ptr = values.as_ptr();
+        let allocations = checkpoint.diff().allocations;
+
+        let heap = MyBinaryHeap::from(values);
+        let data = heap.as_slice();
+        assert!((1..data.len()).all(|i| data[(i - 1) / 2] >= data[i]), "{:?}", data);
+        assert_eq!(checkpoint.diff().allocations, allocations);
+
+        let sorted = heap.into_sorted_vec();
+        assert!(sorted.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(sorted.as_ptr(), ptr);
+    }
+
+    let mut heap: MyBinaryHeap<i32> = (1..=10).collect();
+    let mut other = MyBinaryHeap::from([20, -1]);
+    heap.append(&mut other);
+    assert!(other.is_empty());
+    heap.retain(|v| v % 2 == 0);
+    assert_eq!(heap.peek(), Some(&20));
+    assert_eq!(heap.drain().count(), 6);
+    assert!(heap.is_empty());
+    drop((heap, other));
+
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn reverse_makes_a_min_heap() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    //a scheduler queue, the earliest deadline comes out first
+    let mut queue = MyBinaryHeap::new();
+    for (deadline, task) in [(30, "flush"), (10, "poll"), (20, "tick"), (10, "accept")] {
+        queue.push(Reverse((deadline, my_vec![task])));
+    }
+    let order: Vec<&str> = std::iter::from_fn(|| queue.pop()).map(|Reverse((_, task))| task[0]).collect();
+    assert_eq!(order, ["accept", "poll", "tick", "flush"]);
+    drop(queue);
+
+    let mut heap = MyBinaryHeap::from([Reverse(5), Reverse(1), Reverse(3)]);
+    if let Some(mut top) = heap.peek_mut() {
+        top.0 = 4;
+    }
+    assert_eq!(heap.into_sorted_vec().iter().map(|r| r.0).collect::<Vec<_>>(), [5, 4, 3]);
+
+    checkpoint.assert_no_leaks();
+}
+
+//compares like its value, but panics once the shared fuse runs out
+struct Fused<'a> {
+    value: u32,
+    fuse: &'a Cell<u32>,
+}
+
+impl PartialEq for Fused<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Fused<'_> {}
+
+impl PartialOrd for Fused<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Fused<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let left = self.fuse.get();
+        assert!(left > 0, "fuse ran out");
+        self.fuse.set(left - 1);
+        self.value.cmp(&other.value)
+    }
+}
+
+#[test]
+fn panicking_comparisons_keep_every_value() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+    let fuse = Cell::new(u32::MAX);
+
+    let mut heap: MyBinaryHeap<Fused> = (0..50).map(|value| Fused { value: value * 7 % 50, fuse: &fuse }).collect();
+    for stop in [3, 1, 5, 0, 2] {
+        fuse.set(stop);
+        let len = heap.len();
+        let _ = catch_unwind(AssertUnwindSafe(|| heap.push(Fused { value: 100 + stop, fuse: &fuse })));
+        let _ = catch_unwind(AssertUnwindSafe(|| heap.pop()));
+        fuse.set(u32::MAX);
+        //the panics may leave the order broken, but no value is lost or duplicated
+        let mut values: Vec<u32> = heap.iter().map(|f| f.value).collect();
+        values.sort();
+        values.dedup();
+        assert!(values.len() == heap.len() && heap.len() + 1 >= len);
+    }
+
+    drop(heap);
+    checkpoint.assert_no_leaks();
+}
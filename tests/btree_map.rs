@@ -0,0 +1,305 @@
+//MyBTreeMap and MyBTreeSet tests, checked against std's BTreeMap and BTreeSet
+//the nodes live in the 8 KiB heap, so the maps stay at a few hundred keys,
+//the heap doesn't merge the blocks other tests freed on its own, so every test starts with compact()
+
+mod support {
+    pub mod serial;
+    pub mod rng;
+}
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Bound;
+use std::panic::{self, AssertUnwindSafe};
+
+use memory_manager::{compact, FaultInjectingAllocator, FaultPolicy, HeapCheckpoint, MyBTreeMap, MyBTreeSet, MyVec};
+use support::serial::heap;
+use support::rng::Rng;
+
+//one end of a random range of the keys
+fn bound(rng: &mut Rng, keys: usize) -> Bound<u32> {
+    let key = rng.below(keys) as u32;
+    match rng.below(3) {
+        0 => Bound::Included(key),
+        1 => Bound::Excluded(key),
+        _ => Bound::Unbounded,
+    }
+}
+
+fn assert_same(mine: &MyBTreeMap<u32, u32>, std: &BTreeMap<u32, u32>) {
+    assert_eq!(mine.len(), std.len());
+    assert!(mine.iter().eq(std.iter()), "{:?} != {:?}", mine, std);
+    assert!(mine.iter().rev().eq(std.iter().rev()));
+    assert_eq!(mine.first_key_value(), std.first_key_value());
+    assert_eq!(mine.last_key_value(), std.last_key_value());
+}
+
+//the bounds std would panic on
+fn valid(start: Bound<u32>, end: Bound<u32>) -> bool {
+    match (start, end) {
+        (Bound::Excluded(s), Bound::Excluded(e)) => s < e,
+        (Bound::Included(s) | Bound::Excluded(s), Bound::Included(e) | Bound::Excluded(e)) => s <= e,
+        _ => true,
+    }
+}
+
+#[test]
+fn random_operations_match_std() {
+    let _heap = heap();
+    compact();
+    let checkpoint = HeapCheckpoint::new();
+
+    for (seed, keys) in [(0xB7EE, 200), (0x5EED, 40), (0xACE, 12)] {
+        let mut rng = Rng(seed);
+        let mut mine = MyBTreeMap::new();
+        let mut std = BTreeMap::new();
+        for i in 0..4000u32 {
+            let key = rng.below(keys) as u32;
+            match rng.below(10) {
+                0..=3 => assert_eq!(mine.insert(key, i), std.insert(key, i)),
+                4..=5 => assert_eq!(mine.remove(&key), std.remove(&key)),
+                6 => assert_eq!(mine.pop_first(), std.pop_first()),
+                7 => assert_eq!(mine.pop_last(), std.pop_last()),
+                8 => {
+                    assert_eq!(mine.get(&key), std.get(&key));
+                    if let Some(v) = mine.get_mut(&key) {
+                        *v += 1;
+                        *std.get_mut(&key).unwrap() += 1;
+                    }
+                }
+                _ => {
+                    let (start, end) = (bound(&mut rng, keys), bound(&mut rng, keys));
+                    if valid(start, end) {
+                        assert!(mine.range((start, end)).eq(std.range((start, end))));
+                        assert!(mine.range((start, end)).rev().eq(std.range((start, end)).rev()));
+                    }
+                }
+            }
+            if i % 8 == 0 {
+                assert_same(&mine, &std);
+            }
+        }
+        assert_same(&mine, &std);
+    }
+
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn iterating_from_both_ends() {
+    let _heap = heap();
+    compact();
+    let checkpoint = HeapCheckpoint::new();
+    let mut rng = Rng(0xD0E);
+
+    let mut mine: MyBTreeMap<u32, u32> = (0..150).map(|i| (i * 3, i)).collect();
+    let std: BTreeMap<u32, u32> = (0..150).map(|i| (i * 3, i)).collect();
+
+    //the fronts and backs meet in every kind of node
+    for _ in 0..50 {
+        let (mut a, mut b) = (mine.iter(), std.iter());
+        loop {
+            let (x, y) = if rng.below(2) == 0 { (a.next(), b.next()) } else { (a.next_back(), b.next_back()) };
+            assert_eq!(x, y);
+            assert_eq!(a.len(), b.len());
+            if x.is_none() {
+                break;
+            }
+        }
+
+        let (start, end) = (bound(&mut rng, 450), bound(&mut rng, 450));
+        if valid(start, end) {
+            let (mut a, mut b) = (mine.range((start, end)), std.range((start, end)));
+            loop {
+                let (x, y) = if rng.below(2) == 0 { (a.next(), b.next()) } else { (a.next_back(), b.next_back()) };
+                assert_eq!(x, y);
+                if x.is_none() {
+                    assert_eq!((a.next(), a.next_back()), (None, None));
+                    break;
+                }
+            }
+        }
+    }
+
+    for (k, v) in mine.range_mut(100..200) {
+        *v = *k;
+    }
+    for v in mine.values_mut().rev().take(10) {
+        *v = 0;
+    }
+    assert!(mine.range(100..200).all(|(k, v)| k == v));
+    assert_eq!(mine.values().rev().take(10).sum::<u32>(), 0);
+    assert_eq!(mine.keys().next_back(), Some(&447));
+    assert_eq!(mine.range(..=1).count() + mine.range(2..).count(), 150);
+    assert_eq!(mine.range((Bound::Excluded(3), Bound::Included(3))).next(), None);
+
+    let mut iter = mine.into_iter();
+    assert_eq!((iter.next(), iter.next_back(), iter.len()), (Some((0, 0)), Some((447, 0)), 148));
+    drop(iter);
+
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+#[should_panic(expected = "range start is greater than range end")]
+fn backwards_range_panics() {
+    let map: MyBTreeMap<u32, u32> = MyBTreeMap::new();
+    let (start, end) = (5, 1);
+    map.range(start..end);
+}
+
+#[test]
+fn split_off_and_append() {
+    let _heap = heap();
+    compact();
+    let checkpoint = HeapCheckpoint::new();
+
+    for at in [0, 1, 57, 98, 99, 100, 500] {
+        let mut mine: MyBTreeMap<u32, u32> = (0..100).map(|i| (i * 2, i)).collect();
+        let mut std: BTreeMap<u32, u32> = (0..100).map(|i| (i * 2, i)).collect();
+        let mut mine_tail = mine.split_off(&at);
+        let mut std_tail = std.split_off(&at);
+        assert_same(&mine, &std);
+        assert_same(&mine_tail, &std_tail);
+
+        //overlapping keys, the appended values win
+        for i in 0..20 {
+            mine_tail.insert(i * 5, 1000);
+            std_tail.insert(i * 5, 1000);
+        }
+        mine.append(&mut mine_tail);
+        std.append(&mut std_tail);
+        assert!(mine_tail.is_empty());
+        assert_same(&mine, &std);
+    }
+
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn values_on_the_heap_are_dropped() {
+    let _heap = heap();
+    compact();
+    let checkpoint = HeapCheckpoint::new();
+
+    let mut map = MyBTreeMap::new();
+    for i in 0..20u32 {
+        map.insert(i, MyVec::from([i]));
+    }
+    map.insert(3, MyVec::from([0]));
+    assert_eq!(map[&3], [0]);
+    map.remove(&4);
+    let copy = map.clone();
+    assert_eq!(copy, map);
+    assert_eq!(format!("{:?}", copy.range(..2).collect::<Vec<_>>()), "[(0, [0]), (1, [1])]");
+
+    let mut iter = copy.into_iter();
+    iter.next();
+    drop(iter);
+    map.clear();
+    assert!(map.is_empty() && map.first_key_value().is_none());
+    map.insert(1, MyVec::new());
+    drop(map);
+
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn a_failed_insert_leaves_the_map_alone() {
+    let _heap = heap();
+    compact();
+    let checkpoint = HeapCheckpoint::new();
+
+    //every allocation fails once in turn, 150 keys take 19 nodes, so the later ones split inner nodes and the root
+    for n in 1..=20 {
+        let alloc = FaultInjectingAllocator::new(FaultPolicy::FailNth(n));
+        let mut mine = MyBTreeMap::new_in(&alloc);
+        let mut std = BTreeMap::new();
+        for i in 0..150u32 {
+            let key = i * 7 % 150;
+            if panic::catch_unwind(AssertUnwindSafe(|| mine.insert(key, i))).is_ok() {
+                std.insert(key, i);
+            }
+            assert_eq!(mine.len(), std.len());
+            assert!(mine.iter().eq(std.iter()), "{:?} != {:?}", mine, std);
+        }
+        assert!(mine.len() >= 149);
+    }
+
+    checkpoint.assert_no_leaks();
+}
+
+//the even keys in the first map, the odd ones in the second
+fn two_maps(alloc: &FaultInjectingAllocator) -> (MyBTreeMap<u32, u32, &FaultInjectingAllocator>, MyBTreeMap<u32, u32, &FaultInjectingAllocator>) {
+    let (mut mine, mut other) = (MyBTreeMap::new_in(alloc), MyBTreeMap::new_in(alloc));
+    for i in 0..60 {
+        mine.insert(i * 2, i);
+        other.insert(i * 2 + 1, i);
+    }
+    (mine, other)
+}
+
+#[test]
+fn an_append_out_of_memory_leaves_the_entries_split() {
+    let _heap = heap();
+    compact();
+    let checkpoint = HeapCheckpoint::new();
+
+    //FailNth(0) never fails, it only counts the allocations of the two maps
+    let counter = FaultInjectingAllocator::new(FaultPolicy::FailNth(0));
+    drop(two_maps(&counter));
+    let attempts = counter.stats().attempts;
+
+    //the first allocation of the append fails, when self's leaves are full
+    let alloc = FaultInjectingAllocator::new(FaultPolicy::FailNth(attempts + 1));
+    let (mut mine, mut other) = two_maps(&alloc);
+    assert!(panic::catch_unwind(AssertUnwindSafe(|| mine.append(&mut other))).is_err());
+
+    //the odd keys below the rest of other were moved, except for the one which was dropped
+    let first_left = *other.first_key_value().unwrap().0;
+    let moved = mine.keys().filter(|&&k| k % 2 == 1).count();
+    assert!(moved > 0);
+    assert_eq!(moved + 1 + other.len(), 60);
+    assert!(mine.keys().filter(|&&k| k % 2 == 1).all(|&k| k < first_left));
+    assert!(other.keys().copied().eq((first_left..120).step_by(2)));
+    assert_eq!(mine.len(), 60 + moved);
+    drop((mine, other));
+
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn btree_set() {
+    let _heap = heap();
+    compact();
+    let checkpoint = HeapCheckpoint::new();
+    let mut rng = Rng(0x5E7);
+
+    let mut mine = MyBTreeSet::new();
+    let mut std = BTreeSet::new();
+    for _ in 0..2000 {
+        let value = rng.below(150);
+        if rng.below(3) == 0 {
+            assert_eq!(mine.remove(&value), std.remove(&value));
+        } else {
+            assert_eq!(mine.insert(value), std.insert(value));
+        }
+    }
+    assert!(mine.iter().eq(std.iter()));
+    assert_eq!((mine.first(), mine.last()), (std.first(), std.last()));
+    assert!(mine.range(20..=40).rev().eq(std.range(20..=40).rev()));
+
+    let mut mine_tail = mine.split_off(&75);
+    let std_tail = std.split_off(&75);
+    assert!(mine_tail.iter().eq(std_tail.iter()));
+    assert_eq!(mine_tail.pop_last(), std_tail.last().copied());
+    mine.append(&mut mine_tail);
+
+    let words: MyBTreeSet<MyVec<u8>> = MyBTreeSet::from([MyVec::from(&b"b"[..]), MyVec::from(&b"a"[..])]);
+    assert_eq!(words.first().map(|w| w.as_slice()), Some(&b"a"[..]));
+    assert!(words.contains(&b"b"[..]));
+    assert_eq!(words.clone(), words);
+    assert_eq!(words.into_iter().next_back().map(|w| w.len()), Some(1));
+
+    drop(mine);
+    checkpoint.assert_no_leaks();
+}
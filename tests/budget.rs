@@ -0,0 +1,64 @@
+//Budget tests: the two modes, the accounting and budgets as the allocator of containers
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use memory_manager::boxed::MyBox;
+use memory_manager::{AllocError, Budget, BudgetMode, BudgetStats, MyString, MyVec};
+
+#[test]
+fn reject_mode_fails_over_the_limit() {
+    let budget = Budget::new("parser", 64, BudgetMode::Reject);
+    assert_eq!(budget.name(), "parser");
+    let mut v: MyVec<u32, _> = MyVec::new_in(&budget);
+    v.try_reserve(16).unwrap();
+    for i in 0..16 {
+        v.push(i);
+    }
+    //the next capacity would be 32 values
+    assert_eq!(v.try_reserve(1), Err(AllocError));
+    assert_eq!(v.len(), 16);
+    assert!(catch_unwind(AssertUnwindSafe(|| MyBox::new_in(1u8, &budget))).is_err());
+    assert_eq!(budget.stats(), BudgetStats { current: 64, peak: 64, limit: 64, over_limit: 2 });
+
+    //freed bytes can be used again
+    drop(v);
+    let b = MyBox::new_in([0u8; 64], &budget);
+    assert_eq!(budget.current(), 64);
+    drop(b);
+    assert_eq!(budget.current(), 0);
+}
+
+#[test]
+fn report_mode_counts_but_allows() {
+    let budget = Budget::new("cache", 16, BudgetMode::Report);
+    let mut s = MyString::from_str_in("0123456789", &budget);
+    assert_eq!(budget.stats().over_limit, 0);
+    s.push_str("0123456789");
+    assert_eq!(s.as_str(), "01234567890123456789");
+    let stats = budget.stats();
+    assert!(stats.current >= 20 && stats.current > stats.limit);
+    assert_eq!(stats.over_limit, 1);
+
+    drop(s);
+    assert_eq!(budget.stats().current, 0);
+}
+
+#[test]
+fn peak_covers_a_grow() {
+    let budget = Budget::new("scratch", 1024, BudgetMode::Reject);
+    //the budget only sees the requested sizes, so the capacities are exact
+    let mut v: MyVec<u64, _> = MyVec::with_capacity_in(2, &budget);
+    assert_eq!((v.capacity(), budget.current()), (2, 16));
+    v.push(1);
+    v.push(2);
+    v.try_reserve(2).unwrap();
+    //the new buffer is allocated before the old one is freed
+    assert_eq!((v.capacity(), budget.current(), budget.peak()), (4, 32, 48));
+
+    let boxed = MyBox::new_in(7u32, &budget);
+    let name = MyString::from_str_in("name", &budget);
+    assert_eq!(budget.current(), 32 + 4 + 4);
+
+    drop((v, boxed, name));
+    assert_eq!((budget.current(), budget.peak()), (0, 48));
+}
@@ -0,0 +1,80 @@
+//HeapCheckpoint on the containers' drop logic
+
+mod support {
+    pub mod serial;
+}
+
+use std::cell::RefCell;
+
+use memory_manager::rc::MyRc;
+use memory_manager::{my_alloc, my_free, HeapCheckpoint, MyString, MyVec};
+use support::serial::heap;
+
+#[test]
+fn dropped_containers_leave_nothing_behind() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    {
+        let mut v = MyVec::new();
+        for i in 0..100 {
+            v.push(MyString::from_str(&i.to_string()));
+        }
+        v.truncate(10);
+        v.shrink_to_fit();
+
+        let rc = MyRc::new(v);
+        let _clone = rc.clone();
+    }
+
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn live_allocations_are_reported() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    let ptr = my_alloc(40, 8);
+    let diff = checkpoint.diff();
+    assert!(!diff.is_clean());
+    assert_eq!(diff.allocations, 1);
+    assert_eq!(diff.new_blocks, 1);
+    assert_eq!(diff.leaks()[0].ptr, ptr);
+    assert!(diff.leaks()[0].size >= 40);
+
+    unsafe {
+        my_free(ptr);
+    }
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn rc_cycle_is_a_leak() {
+    struct Node {
+        next: RefCell<Option<MyRc<Node>>>,
+    }
+
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    let a = MyRc::new(Node { next: RefCell::new(None) });
+    let b = MyRc::new(Node { next: RefCell::new(Some(a.clone())) });
+    *a.next.borrow_mut() = Some(b.clone());
+    drop(a);
+    drop(b);
+
+    let diff = checkpoint.diff();
+    assert_eq!(diff.allocations, 2);
+    assert_eq!(diff.new_blocks, 2);
+}
+
+#[test]
+#[should_panic(expected = "new blocks alive")]
+fn assert_no_leaks_panics_on_a_leak() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    core::mem::forget(MyVec::<u8>::with_capacity(16));
+    checkpoint.assert_no_leaks();
+}
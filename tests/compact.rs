@@ -0,0 +1,174 @@
+//compact tests: handles sliding together, the gaps between blocks which can't move, and handles sliding past them
+
+mod support {
+    pub mod serial;
+    pub mod rng;
+}
+
+use memory_manager::{compact, heap_stats, my_alloc, my_free, usage_by_tag, validate_heap, HeapCheckpoint, MyHandle};
+use support::serial::heap;
+use support::rng::Rng;
+
+//the bytes of every allocated block
+fn used_bytes() -> usize {
+    usage_by_tag().iter().map(|usage| usage.bytes).sum()
+}
+
+#[test]
+fn handles_keep_their_values_when_moved() {
+    let _heap = heap();
+    compact();
+    let used = used_bytes();
+
+    //every other block is freed, so the handles are separated by small gaps
+    let mut handles = Vec::new();
+    let mut gaps = Vec::new();
+    for i in 0..16u64 {
+        handles.push(MyHandle::new([i; 4]));
+        gaps.push(my_alloc(48, 8));
+    }
+    let before = compact();
+    for ptr in gaps {
+        unsafe {
+            my_free(ptr);
+        }
+    }
+
+    let largest = compact();
+    assert!(largest > before + 48);
+    for (i, handle) in handles.iter().enumerate() {
+        assert_eq!(*handle.borrow(), [i as u64; 4]);
+    }
+
+    drop(handles);
+    compact();
+    assert_eq!(used_bytes(), used);
+}
+
+#[test]
+fn a_borrowed_handle_stays_in_place() {
+    let _heap = heap();
+    compact();
+    let used = used_bytes();
+
+    let gap = my_alloc(64, 8);
+    let mut pinned = MyHandle::new(1u64);
+    let moved = MyHandle::new(2u64);
+    unsafe {
+        my_free(gap);
+    }
+
+    let mut value = pinned.borrow_mut();
+    let address = &*value as *const u64;
+    compact();
+    assert_eq!(&*value as *const u64, address);
+    *value = 3;
+    drop(value);
+
+    compact();
+    assert_eq!((*pinned.borrow(), *moved.borrow()), (3, 2));
+    drop((pinned, moved));
+    assert_eq!(used_bytes(), used);
+}
+
+#[test]
+fn a_freed_empty_block_before_a_fixed_one() {
+    let _heap = heap();
+    compact();
+    let checkpoint = HeapCheckpoint::new();
+
+    //a free block of just a header, with no moved block in front of it
+    let a = my_alloc(0, 8);
+    let b = my_alloc(32, 8);
+    unsafe {
+        my_free(a);
+    }
+    compact();
+    validate_heap().unwrap();
+
+    unsafe {
+        my_free(b);
+    }
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn gaps_between_fixed_blocks_stay_free() {
+    let _heap = heap();
+    compact();
+    let checkpoint = HeapCheckpoint::new();
+
+    let blocks: Vec<_> = (0..8).map(|i| my_alloc(if i % 2 == 0 { 0 } else { 24 }, 8)).collect();
+    for &ptr in blocks.iter().step_by(2) {
+        unsafe {
+            my_free(ptr);
+        }
+    }
+
+    //without handles nothing moves, so every free byte has to stay in the free list
+    let before = heap_stats();
+    compact();
+    validate_heap().unwrap();
+    assert_eq!(heap_stats().free_bytes, before.free_bytes);
+    assert!(heap_stats().free_blocks <= before.free_blocks);
+
+    for &ptr in blocks.iter().skip(1).step_by(2) {
+        unsafe {
+            my_free(ptr);
+        }
+    }
+    checkpoint.assert_no_leaks();
+}
+
+#[repr(align(16))]
+struct Wide([u8; 24]);
+
+#[test]
+fn handles_slide_past_fixed_blocks() {
+    let _heap = heap();
+    compact();
+    let checkpoint = HeapCheckpoint::new();
+    let mut rng = Rng(0xC0C0);
+
+    let mut fixed = Vec::new();
+    let mut narrow: Vec<(MyHandle<[u64; 3]>, u64)> = Vec::new();
+    let mut wide: Vec<(MyHandle<Wide>, u8)> = Vec::new();
+    for round in 0..300u64 {
+        match rng.below(6) {
+            0 => fixed.push(my_alloc(rng.below(40), 8 << rng.below(2))),
+            1 if !fixed.is_empty() => unsafe {
+                my_free(fixed.swap_remove(rng.below(fixed.len())));
+            },
+            2 if narrow.len() < 20 => narrow.push((MyHandle::new([round; 3]), round)),
+            3 if wide.len() < 20 => wide.push((MyHandle::new(Wide([round as u8; 24])), round as u8)),
+            4 if !narrow.is_empty() => drop(narrow.swap_remove(rng.below(narrow.len()))),
+            4 if !wide.is_empty() => drop(wide.swap_remove(rng.below(wide.len()))),
+            _ => {
+                //a pinned handle can't move either
+                let pinned = narrow.first().map(|(handle, _)| handle.borrow());
+                compact();
+                drop(pinned);
+                validate_heap().unwrap();
+            }
+        }
+
+        for (handle, value) in &narrow {
+            assert_eq!(*handle.borrow(), [*value; 3]);
+        }
+        for (handle, value) in &wide {
+            let value_ref = handle.borrow();
+            assert_eq!(&*value_ref as *const Wide as usize % 16, 0);
+            assert_eq!(value_ref.0, [*value; 24]);
+        }
+    }
+
+    for ptr in fixed {
+        unsafe {
+            my_free(ptr);
+        }
+    }
+    drop((narrow, wide));
+    compact();
+    validate_heap().unwrap();
+    checkpoint.assert_no_leaks();
+}
@@ -0,0 +1,182 @@
+//fault injection tests: the policies, and that the fallible apis leak nothing when an allocation fails
+
+mod support {
+    pub mod counting;
+    pub mod serial;
+}
+
+use std::alloc::Layout;
+use std::cell::Cell;
+
+use memory_manager::boxed::MyBox;
+use memory_manager::{fault_stats, my_free, my_try_alloc, set_fault_policy, AllocError, FaultInjectingAllocator, FaultPolicy, FaultStats, MyAllocator, MyVec};
+use support::counting::Counting;
+use support::serial::heap;
+
+//the outcome of count allocations of 8 bytes, true for the failed ones
+fn outcomes(alloc: &FaultInjectingAllocator, count: usize) -> Vec<bool> {
+    (0..count).map(|_| match alloc.allocate(Layout::new::<u64>()) {
+        Ok(ptr) => {
+            unsafe {
+                alloc.deallocate(ptr, Layout::new::<u64>());
+            }
+            false
+        }
+        Err(AllocError) => true,
+    }).collect()
+}
+
+#[test]
+fn fail_nth_fails_once() {
+    let _heap = heap();
+
+    let alloc = FaultInjectingAllocator::new(FaultPolicy::FailNth(3));
+    assert_eq!(outcomes(&alloc, 5), [false, false, true, false, false]);
+    assert_eq!(alloc.stats(), FaultStats { attempts: 5, failures: 1, bytes_allocated: 32 });
+}
+
+#[test]
+fn probability_is_reproducible() {
+    let _heap = heap();
+
+    let policy = |seed| FaultPolicy::Probability { p: 0.5, seed };
+    let first = outcomes(&FaultInjectingAllocator::new(policy(42)), 200);
+    assert_eq!(first, outcomes(&FaultInjectingAllocator::new(policy(42)), 200));
+    assert_ne!(first, outcomes(&FaultInjectingAllocator::new(policy(7)), 200));
+    let failures = first.iter().filter(|&&failed| failed).count();
+    assert!((60..140).contains(&failures), "{} of 200 failed", failures);
+
+    //the 0 seed is usable too
+    assert_eq!(outcomes(&FaultInjectingAllocator::new(policy(0)), 50), outcomes(&FaultInjectingAllocator::new(policy(0)), 50));
+    assert!(!outcomes(&FaultInjectingAllocator::new(FaultPolicy::Probability { p: 0.0, seed: 1 }), 50).contains(&true));
+    assert!(!outcomes(&FaultInjectingAllocator::new(FaultPolicy::Probability { p: 1.0, seed: 1 }), 50).contains(&false));
+}
+
+#[test]
+fn byte_budget_counts_the_requested_bytes() {
+    let _heap = heap();
+
+    let alloc = FaultInjectingAllocator::new(FaultPolicy::ByteBudget(100));
+    let a = alloc.allocate(Layout::from_size_align(64, 8).unwrap()).unwrap();
+    assert!(alloc.allocate(Layout::from_size_align(64, 8).unwrap()).is_err());
+    let b = alloc.allocate(Layout::from_size_align(32, 8).unwrap()).unwrap();
+    //freeing doesn't give the bytes back
+    unsafe {
+        alloc.deallocate(a, Layout::from_size_align(64, 8).unwrap());
+        alloc.deallocate(b, Layout::from_size_align(32, 8).unwrap());
+    }
+    assert!(alloc.allocate(Layout::from_size_align(8, 8).unwrap()).is_err());
+    assert_eq!(alloc.stats(), FaultStats { attempts: 4, failures: 2, bytes_allocated: 96 });
+}
+
+#[test]
+fn grow_and_shrink_go_through_the_policy() {
+    let _heap = heap();
+    let (small, old, big) = (Layout::from_size_align(16, 8).unwrap(), Layout::from_size_align(64, 8).unwrap(), Layout::from_size_align(128, 8).unwrap());
+
+    let alloc = FaultInjectingAllocator::new(FaultPolicy::FailNth(2));
+    unsafe {
+        let ptr = alloc.allocate(old).unwrap();
+        ptr.as_ptr().write_bytes(7, 64);
+        //a failed grow leaves the block alone
+        assert_eq!(alloc.grow(ptr, old, big), Err(AllocError));
+        //the global heap shrinks in place, so the shrink was forwarded
+        assert_eq!(alloc.shrink(ptr, old, small), Ok(ptr));
+        let grown = alloc.grow(ptr, small, big).unwrap();
+        assert_eq!(*grown.as_ptr().add(15), 7);
+        alloc.deallocate(grown, big);
+    }
+    assert_eq!(alloc.stats(), FaultStats { attempts: 4, failures: 1, bytes_allocated: 64 + 16 + 128 });
+}
+
+#[test]
+fn a_failed_try_reserve_keeps_the_vec() {
+    let _heap = heap();
+    let counting = Counting::default();
+
+    let alloc = FaultInjectingAllocator::with_inner(FaultPolicy::FailNth(2), &counting);
+    let mut v = MyVec::new_in(&alloc);
+    v.try_reserve(4).unwrap();
+    for i in 1..=4u32 {
+        v.push(i);
+    }
+    let capacity = v.capacity();
+    assert_eq!(v.try_reserve(100), Err(AllocError));
+    assert_eq!((v.as_slice(), v.capacity()), (&[1, 2, 3, 4][..], capacity));
+    v.try_reserve(100).unwrap();
+    assert_eq!(v.as_slice(), [1, 2, 3, 4]);
+    assert_eq!(counting.live(), 1);
+
+    drop(v);
+    assert_eq!((counting.live(), counting.live_bytes.get()), (0, 0));
+}
+
+//counts its drops, so a value given to a failing constructor can be checked
+struct Tracked<'a>(&'a Cell<usize>);
+
+impl Drop for Tracked<'_> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+#[test]
+fn the_manager_policy_applies_to_every_allocation() {
+    let _heap = heap();
+
+    set_fault_policy(Some(FaultPolicy::FailNth(2)));
+    let a = my_try_alloc(16, 8);
+    assert!(!a.is_null());
+    assert!(my_try_alloc(16, 8).is_null());
+    assert_eq!(fault_stats(), Some(FaultStats { attempts: 2, failures: 1, bytes_allocated: 16 }));
+
+    set_fault_policy(Some(FaultPolicy::FailNth(1)));
+    let drops = Cell::new(0);
+    assert!(MyBox::try_new(Tracked(&drops)).is_err());
+    assert_eq!(drops.get(), 1);
+    set_fault_policy(Some(FaultPolicy::FailNth(1)));
+    assert!(MyVec::<u8>::try_with_capacity(8).is_err());
+    set_fault_policy(None);
+    assert_eq!(fault_stats(), None);
+
+    unsafe {
+        my_free(a);
+    }
+}
+
+//a value for every box, failing anywhere gives back what was allocated before
+fn build<A: MyAllocator + Copy>(count: u64, alloc: A) -> Result<MyVec<MyBox<u64, A>, A>, AllocError> {
+    let mut v = MyVec::try_with_capacity_in(2, alloc)?;
+    for i in 0..count {
+        v.try_reserve(1)?;
+        v.push(MyBox::try_new_in(i, alloc)?);
+    }
+    Ok(v)
+}
+
+#[test]
+fn every_failure_point_is_leak_free() {
+    let _heap = heap();
+    let counting = Counting::default();
+
+    //fails each allocation of the build in turn, until one gets through
+    let mut n = 1;
+    loop {
+        let alloc = FaultInjectingAllocator::with_inner(FaultPolicy::FailNth(n), &counting);
+        match build(12, &alloc) {
+            Ok(v) => {
+                assert_eq!(alloc.stats().failures, 0);
+                assert!(v.iter().map(|b| **b).eq(0..12));
+                break;
+            }
+            Err(AllocError) => {
+                assert_eq!(alloc.stats().attempts, n);
+                assert_eq!((counting.live(), counting.live_bytes.get()), (0, 0));
+            }
+        }
+        n += 1;
+    }
+    assert!(n > 12);
+
+    assert_eq!((counting.live(), counting.live_bytes.get()), (0, 0));
+}
@@ -0,0 +1,274 @@
+//MyHashMap and MyHashSet tests, checked against std's HashMap and HashSet
+
+mod support {
+    pub mod serial;
+    pub mod rng;
+}
+
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasherDefault, Hasher};
+
+use memory_manager::{HeapCheckpoint, MyHashMap, MyHashMapEntry, MyHashSet, MyVec};
+use support::serial::heap;
+use support::rng::Rng;
+
+fn assert_same<S: std::hash::BuildHasher>(mine: &MyHashMap<u32, u32, S>, std: &HashMap<u32, u32>) {
+    assert_eq!(mine.len(), std.len());
+    assert_eq!(mine.iter().len(), std.len());
+    for (k, v) in mine {
+        assert_eq!(std.get(k), Some(v));
+    }
+}
+
+//every key lands in one of four chains, so the probing and the DELETED slots get a workout
+#[derive(Default)]
+struct Colliding(u64);
+
+impl Hasher for Colliding {
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = self.0.wrapping_add(b as u64);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        (self.0 % 4) << 57
+    }
+}
+
+fn random_operations<S: std::hash::BuildHasher>(mut mine: MyHashMap<u32, u32, S>, seed: u64, keys: usize) {
+    let mut rng = Rng(seed);
+    let mut std = HashMap::new();
+    for i in 0..3000u32 {
+        let key = rng.below(keys) as u32;
+        match rng.below(8) {
+            0..=2 => assert_eq!(mine.insert(key, i), std.insert(key, i)),
+            3..=4 => assert_eq!(mine.remove(&key), std.remove(&key)),
+            5 => {
+                *mine.entry(key).or_insert(0) += i;
+                *std.entry(key).or_insert(0) += i;
+            }
+            6 => {
+                assert_eq!(mine.get(&key), std.get(&key));
+                assert_eq!(mine.contains_key(&key), std.contains_key(&key));
+            }
+            _ if i % 50 == 0 => {
+                mine.retain(|k, v| (k + *v) % 3 != 0);
+                std.retain(|k, v| (k + *v) % 3 != 0);
+            }
+            _ => {
+                if let Some(v) = mine.get_mut(&key) {
+                    *v += 1;
+                }
+                if let Some(v) = std.get_mut(&key) {
+                    *v += 1;
+                }
+            }
+        }
+        assert!(mine.len() <= mine.capacity());
+        if i % 16 == 0 {
+            assert_same(&mine, &std);
+        }
+    }
+    assert_same(&mine, &std);
+}
+
+#[test]
+fn random_operations_match_std() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    random_operations(MyHashMap::new(), 0xC0FFEE, 300);
+    random_operations(MyHashMap::with_hasher(RandomState::new()), 0xBEEF, 2000);
+    random_operations(MyHashMap::with_hasher(BuildHasherDefault::<Colliding>::default()), 0xF00D, 64);
+
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn the_table_comes_from_the_heap() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    let mut map: MyHashMap<u64, u64> = MyHashMap::new();
+    assert_eq!(checkpoint.diff().allocations, 0);
+    map.reserve(100);
+    let capacity = map.capacity();
+    assert!(capacity >= 100);
+    assert_eq!(checkpoint.diff().allocations, 1);
+
+    for i in 0..capacity as u64 {
+        map.insert(i, i * i);
+    }
+    assert_eq!(map.capacity(), capacity);
+    assert_eq!(checkpoint.diff().allocations, 1);
+
+    //removed slots are DELETED, once they fill the table it's rebuilt
+    for i in 0..capacity as u64 {
+        assert_eq!(map.remove(&i), Some(i * i));
+        map.insert(i + 1000, i);
+    }
+    assert_eq!(map.len(), capacity);
+    assert!((1000..1000 + capacity as u64).all(|k| map.get(&k) == Some(&(k - 1000))));
+
+    //clearing keeps the memory
+    let allocations = checkpoint.diff().allocations;
+    map.clear();
+    assert!(map.is_empty() && map.capacity() >= capacity);
+    assert_eq!(checkpoint.diff().allocations, allocations);
+    drop(map);
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn entry_api() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    //the keys are on the heap too, and are looked up by slices
+    let mut counts: MyHashMap<MyVec<u8>, usize> = MyHashMap::new();
+    for word in "a b a c b a".split(' ') {
+        *counts.entry(MyVec::from(word.as_bytes())).or_default() += 1;
+    }
+    assert_eq!((counts[&b"a"[..]], counts[&b"b"[..]], counts[&b"c"[..]]), (3, 2, 1));
+
+    counts.entry(MyVec::from(&b"a"[..])).and_modify(|n| *n *= 10).or_insert(0);
+    counts.entry(MyVec::from(&b"dd"[..])).and_modify(|n| *n *= 10).or_insert_with_key(|k| k.len());
+    assert_eq!((counts[&b"a"[..]], counts[&b"dd"[..]]), (30, 2));
+
+    match counts.entry(MyVec::from(&b"b"[..])) {
+        MyHashMapEntry::Occupied(mut entry) => {
+            assert_eq!(entry.key(), b"b");
+            assert_eq!(entry.insert(7), 2);
+            assert_eq!(entry.remove_entry(), (MyVec::from(&b"b"[..]), 7));
+        }
+        MyHashMapEntry::Vacant(_) => panic!("b is in the map"),
+    }
+    match counts.entry(MyVec::from(&b"e"[..])) {
+        MyHashMapEntry::Occupied(_) => panic!("e isn't in the map"),
+        MyHashMapEntry::Vacant(entry) => assert_eq!(entry.into_key(), *b"e"),
+    }
+    assert_eq!(counts.len(), 3);
+    assert_eq!(counts.get_key_value(&b"c"[..]).map(|(k, v)| (k.as_slice(), *v)), Some((&b"c"[..], 1)));
+
+    drop(counts);
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn iterators_and_traits() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    let mut map: MyHashMap<u32, MyVec<u32>> = (0..20).map(|i| (i, MyVec::from([i]))).collect();
+    for v in map.values_mut() {
+        v.push(0);
+    }
+    for (k, v) in &mut map {
+        v[1] = *k * 2;
+    }
+
+    let mut keys: Vec<u32> = map.keys().copied().collect();
+    keys.sort();
+    assert_eq!(keys, (0..20).collect::<Vec<_>>());
+    assert!(map.values().all(|v| v[1] == v[0] * 2));
+    assert_eq!(map.iter().len(), 20);
+
+    let copy = map.clone();
+    assert_eq!(copy, map);
+    map.get_mut(&3).unwrap().push(1);
+    assert_ne!(copy, map);
+
+    let mut all = map.clone();
+    let mut drained: Vec<u32> = all.drain().map(|(k, _)| k).collect();
+    drained.sort();
+    assert_eq!(drained, keys);
+    assert!(all.is_empty());
+
+    //the entries which aren't taken are dropped with the drain
+    let mut drained: Vec<u32> = map.drain().take(5).map(|(k, _)| k).collect();
+    assert!(map.is_empty());
+    drained.sort();
+    drained.dedup();
+    assert!(drained.len() == 5 && drained.iter().all(|k| keys.contains(k)));
+
+    let mut iter = copy.into_iter();
+    assert_eq!(iter.len(), 20);
+    iter.next();
+    assert_eq!(iter.len(), 19);
+    drop(iter);
+
+    let small = MyHashMap::<u8, char>::from([(1, 'a')]);
+    assert_eq!(format!("{:?}", small), "{1: 'a'}");
+    drop((map, all, small));
+
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn a_forgotten_drain_keeps_the_rest() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    let mut map: MyHashMap<u32, u32> = (0..100).map(|i| (i, i)).collect();
+    let mut drain = map.drain();
+    let taken: Vec<u32> = drain.by_ref().take(40).map(|(k, _)| k).collect();
+    std::mem::forget(drain);
+
+    assert_eq!(map.len(), 60);
+    for k in 0..100 {
+        assert_eq!(map.get(&k).is_some(), !taken.contains(&k), "{}", k);
+    }
+    //the keys which are still there are found, so inserting them again doesn't make duplicates
+    for k in 0..100 {
+        map.insert(k, k + 1);
+    }
+    assert_eq!(map.len(), 100);
+    assert_eq!(map.iter().count(), 100);
+    assert!(map.iter().all(|(k, v)| *v == k + 1));
+    drop(map);
+
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn hash_set() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+    let mut rng = Rng(0x5E7);
+
+    let mut mine = MyHashSet::new();
+    let mut std = HashSet::new();
+    for _ in 0..2000 {
+        let value = rng.below(500);
+        if rng.below(3) == 0 {
+            assert_eq!(mine.remove(&value), std.remove(&value));
+        } else {
+            assert_eq!(mine.insert(value), std.insert(value));
+        }
+    }
+    assert_eq!(mine.len(), std.len());
+    assert!(mine.iter().all(|v| std.contains(v)));
+
+    mine.retain(|v| v % 2 == 0);
+    std.retain(|v| v % 2 == 0);
+    let mut sorted: Vec<usize> = mine.iter().copied().collect();
+    let mut expected: Vec<usize> = std.into_iter().collect();
+    sorted.sort();
+    expected.sort();
+    assert_eq!(sorted, expected);
+
+    let evens: MyHashSet<usize> = (0..500).step_by(2).collect();
+    assert!(mine.is_subset(&evens));
+    assert_eq!(mine.take(&sorted[0]), Some(sorted[0]));
+    assert!(!mine.contains(&sorted[0]));
+
+    let words: MyHashSet<MyVec<u8>> = MyHashSet::from([MyVec::from(&b"x"[..]), MyVec::from(&b"y"[..])]);
+    assert_eq!(words.get(&b"x"[..]).map(|w| w.as_slice()), Some(&b"x"[..]));
+    assert_eq!(words.clone(), words);
+    assert_eq!(words.into_iter().count(), 2);
+
+    drop((mine, evens));
+    checkpoint.assert_no_leaks();
+}
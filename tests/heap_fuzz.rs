@@ -0,0 +1,61 @@
+//randomized differential test of my_alloc/my_free/my_shrink against a model of the live blocks
+//the same operations are run by the fuzz target (see fuzz/)
+
+mod support {
+    pub mod heap_ops;
+    pub mod rng;
+    pub mod serial;
+}
+
+use support::heap_ops::{Model, Op};
+use support::rng::Rng;
+
+const SEEDS: u64 = 64;
+const OPS_PER_SEED: usize = 2000;
+//half of the decoded allocations are up to 2 KiB, so the model has many more live bytes to check after every op
+const DECODED_INPUTS: u64 = 8;
+
+fn random_op(rng: &mut Rng, live: usize) -> Op {
+    //keep a few dozen blocks alive, so the heap fills up and fragments, but also empties sometimes
+    let alloc = live == 0 || rng.below(48) >= live;
+
+    if alloc {
+        //mostly small blocks, sometimes large ones which are served by the large region
+        let size = if rng.below(8) == 0 { rng.below(2048) } else { rng.below(128) };
+        Op::Alloc { size, alignment: 1 << rng.below(7) }
+    } else if rng.below(4) == 0 {
+        Op::Shrink { index: rng.below(live), size: rng.below(2048) }
+    } else {
+        Op::Free { index: rng.below(live) }
+    }
+}
+
+#[test]
+fn random_alloc_free_matches_model() {
+    for seed in 1..=SEEDS {
+        let mut rng = Rng(seed);
+        let mut model = Model::new();
+
+        for _ in 0..OPS_PER_SEED {
+            let op = random_op(&mut rng, model.live());
+            model.apply(op);
+        }
+
+        model.finish();
+    }
+}
+
+//random bytes through the fuzz target's decoding, so the fuzz path runs with the normal tests too
+#[test]
+fn decoded_bytes_match_model() {
+    let mut rng = Rng(0xF0F0);
+    for _ in 0..DECODED_INPUTS {
+        let bytes: Vec<u8> = (0..OPS_PER_SEED * 3).map(|_| rng.next() as u8).collect();
+
+        let mut model = Model::new();
+        for op in Op::decode(&bytes) {
+            model.apply(op);
+        }
+        model.finish();
+    }
+}
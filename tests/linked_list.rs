@@ -0,0 +1,215 @@
+//MyLinkedList tests, the cursor operations are checked against a Vec holding the same values
+//every node is a small block of the 8 KiB heap, so the lists stay at about a hundred values
+
+mod support {
+    pub mod serial;
+    pub mod rng;
+}
+
+use std::collections::LinkedList;
+
+use memory_manager::{compact, Budget, BudgetMode, HeapCheckpoint, MyLinkedList, MyVec};
+use support::serial::heap;
+use support::rng::Rng;
+
+fn assert_same(mine: &MyLinkedList<u32>, std: &LinkedList<u32>) {
+    assert_eq!(mine.len(), std.len());
+    assert!(mine.iter().eq(std.iter()), "{:?} != {:?}", mine, std);
+    assert!(mine.iter().rev().eq(std.iter().rev()));
+    assert_eq!((mine.front(), mine.back()), (std.front(), std.back()));
+}
+
+#[test]
+fn random_operations_match_std() {
+    let _heap = heap();
+    compact();
+    let checkpoint = HeapCheckpoint::new();
+    let mut rng = Rng(0x11A7);
+
+    let mut mine = MyLinkedList::new();
+    let mut std = LinkedList::new();
+    for i in 0..4000u32 {
+        //more pushes than pops, but never more than a hundred values
+        match rng.below(9) {
+            0..=3 if mine.len() >= 100 => assert_eq!(mine.pop_back(), std.pop_back()),
+            0..=1 => {
+                mine.push_back(i);
+                std.push_back(i);
+            }
+            2..=3 => {
+                mine.push_front(i);
+                std.push_front(i);
+            }
+            4 => assert_eq!(mine.pop_back(), std.pop_back()),
+            5 => assert_eq!(mine.pop_front(), std.pop_front()),
+            6 if mine.len() > 60 => {
+                let at = rng.below(mine.len() + 1);
+                let mut mine_tail = mine.split_off(at);
+                let mut std_tail = std.split_off(at);
+                assert_same(&mine, &std);
+                assert_same(&mine_tail, &std_tail);
+                mine_tail.append(&mut mine);
+                std_tail.append(&mut std);
+                assert!(mine.is_empty());
+                mine = mine_tail;
+                std = std_tail;
+            }
+            _ => {
+                if let (Some(a), Some(b)) = (mine.front_mut(), std.front_mut()) {
+                    *a += 1;
+                    *b += 1;
+                }
+            }
+        }
+        assert_same(&mine, &std);
+    }
+
+    let mut iter = mine.into_iter();
+    assert_eq!((iter.next(), iter.next_back(), iter.len()), (std.pop_front(), std.pop_back(), std.len()));
+    drop(iter);
+
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn cursor_edits_match_a_vec() {
+    let _heap = heap();
+    compact();
+    let checkpoint = HeapCheckpoint::new();
+    let mut rng = Rng(0xC0A5);
+
+    let mut list = MyLinkedList::new();
+    let mut model: Vec<u32> = Vec::new();
+    let mut cursor = list.cursor_front_mut();
+    //the cursor's position in the model, model.len() is the ghost position
+    let mut at = 0;
+    for i in 0..3000u32 {
+        match rng.below(8) {
+            0 => {
+                cursor.move_next();
+                at = if at == model.len() { 0 } else { at + 1 };
+            }
+            1 => {
+                cursor.move_prev();
+                at = if at == 0 { model.len() } else { at - 1 };
+            }
+            2..=3 if model.len() < 100 => {
+                cursor.insert_before(i);
+                model.insert(at, i);
+                at += 1;
+            }
+            4..=5 if model.len() < 100 => {
+                cursor.insert_after(i);
+                model.insert(if at == model.len() { 0 } else { at + 1 }, i);
+                if at == model.len() - 1 {
+                    at += 1;
+                }
+            }
+            _ => {
+                let removed = cursor.remove_current();
+                assert_eq!(removed, (at < model.len()).then(|| model.remove(at)));
+            }
+        }
+        let len = model.len();
+        assert_eq!(cursor.index(), (at < len).then_some(at));
+        assert_eq!(cursor.current().copied(), model.get(at).copied());
+        let next = if at == len { model.first() } else { model.get(at + 1) };
+        assert_eq!(cursor.peek_next().copied(), next.copied());
+        let prev = if at == len { model.last().copied() } else { at.checked_sub(1).map(|i| model[i]) };
+        assert_eq!(cursor.peek_prev().copied(), prev);
+    }
+
+    assert!(list.iter().eq(model.iter()));
+    let mut cursor = list.cursor_back_mut();
+    assert_eq!(cursor.index(), model.len().checked_sub(1));
+    while cursor.remove_current().is_some() {
+        cursor.move_prev();
+    }
+    assert!(list.is_empty());
+    drop(list);
+
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn nodes_and_values_are_freed() {
+    let _heap = heap();
+    compact();
+    let checkpoint = HeapCheckpoint::new();
+
+    //one allocation per node, one more per value which owns heap memory
+    let mut list = MyLinkedList::new();
+    for i in 0..10u32 {
+        list.push_back(MyVec::from([i]));
+    }
+    assert_eq!(checkpoint.diff().allocations, 20);
+
+    let mut cursor = list.cursor_front_mut();
+    cursor.move_next();
+    assert_eq!(cursor.remove_current().map(|v| v[0]), Some(1));
+    cursor.insert_after(MyVec::new());
+    cursor.insert_before(MyVec::from([100]));
+    assert_eq!(cursor.current().map(|v| v[0]), Some(2));
+    assert_eq!(checkpoint.diff().allocations, 21);
+
+    let copy = list.clone();
+    assert_eq!(copy, list);
+    assert_eq!(format!("{:?}", copy.iter().take(3).collect::<Vec<_>>()), "[[0], [100], [2]]");
+
+    //append and split_off only relink the nodes
+    let mut tail = list.split_off(5);
+    let allocations = checkpoint.diff().allocations;
+    list.append(&mut tail);
+    assert!(tail.is_empty());
+    assert_eq!(checkpoint.diff().allocations, allocations);
+    for v in list.iter_mut().rev().take(2) {
+        v.push(0);
+    }
+    assert_eq!(list.back().map(|v| v.len()), Some(2));
+
+    let mut iter = copy.into_iter();
+    iter.next_back();
+    drop(iter);
+    list.clear();
+    assert!(list.is_empty() && list.front().is_none());
+    list.push_front(MyVec::from([1]));
+    drop((list, tail));
+
+    let numbers = MyLinkedList::from([1, 2, 3]);
+    assert!(numbers.contains(&2) && !numbers.contains(&4));
+    drop(numbers);
+
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn split_lists_free_with_the_same_allocator() {
+    let _heap = heap();
+    compact();
+    let checkpoint = HeapCheckpoint::new();
+
+    //the split off and the appended nodes are kept, every list frees them through a reference to the same budget
+    let budget = Budget::new("list", 4096, BudgetMode::Reject);
+    let mut list = MyLinkedList::new_in(&budget);
+    list.extend(0..10u32);
+    let node = budget.current() / 10;
+    let mut tail = list.split_off(4);
+    assert_eq!(budget.current(), 10 * node);
+
+    //appending takes the nodes back, they stay with the budget
+    list.append(&mut tail);
+    assert!(list.iter().copied().eq(0..10));
+    assert_eq!((tail.len(), budget.current()), (0, 10 * node));
+    let tail = list.split_off(4);
+
+    //a list on another allocator can't take the nodes, the values are moved into new ones
+    let other = Budget::new("other", 4096, BudgetMode::Reject);
+    let mut moved = MyLinkedList::new_in(&other);
+    moved.extend(tail);
+    assert!(moved.iter().copied().eq(4..10));
+    assert_eq!((budget.current(), other.current()), (4 * node, 6 * node));
+
+    drop((list, moved));
+    assert_eq!((budget.current(), other.current()), (0, 0));
+    checkpoint.assert_no_leaks();
+}
@@ -0,0 +1,78 @@
+//MyPool tests, the chunks come from the global heap
+
+use std::cell::Cell;
+
+use memory_manager::{MyPool, MyVec, PoolStats};
+
+#[test]
+fn stats_follow_the_slots() {
+    let pool = MyPool::with_chunk_slots(4);
+    assert_eq!(pool.stats(), PoolStats { in_use: 0, chunks: 0, high_water_mark: 0 });
+
+    let mut boxes: Vec<_> = (0..5u64).map(|i| pool.alloc(i)).collect();
+    assert_eq!(pool.stats(), PoolStats { in_use: 5, chunks: 2, high_water_mark: 5 });
+
+    boxes.truncate(2);
+    *boxes[1] += 10;
+    assert_eq!(boxes.iter().map(|b| **b).collect::<Vec<_>>(), [0, 11]);
+    assert_eq!(pool.stats(), PoolStats { in_use: 2, chunks: 2, high_water_mark: 5 });
+
+    //the freed slots are used again before a new chunk is added
+    boxes.extend((0..6).map(|i| pool.alloc(i)));
+    assert_eq!(pool.stats(), PoolStats { in_use: 8, chunks: 2, high_water_mark: 8 });
+    boxes.push(pool.alloc(9));
+    assert_eq!(pool.stats().chunks, 3);
+
+    drop(boxes);
+    assert_eq!(pool.stats().in_use, 0);
+}
+
+#[test]
+fn the_last_freed_slot_comes_back_first() {
+    let pool = MyPool::new();
+    let a = pool.alloc([1u8; 3]);
+    let b = pool.alloc([2u8; 3]);
+    let freed = &*a as *const [u8; 3];
+    drop(a);
+    let c = pool.alloc([3u8; 3]);
+    assert_eq!(&*c as *const [u8; 3], freed);
+    assert_eq!((*b, *c), ([2; 3], [3; 3]));
+
+    let numbers = MyPool::new();
+    assert_eq!(format!("{}", numbers.alloc(7u32)), "7");
+}
+
+//counts its drops, so the test can see which values were dropped
+struct Tracked<'a>(&'a Cell<usize>);
+
+impl<'a> Drop for Tracked<'a> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+#[test]
+fn values_are_dropped_with_their_box() {
+    let pool = MyPool::with_chunk_slots(2);
+    {
+        let mut v = pool.alloc(MyVec::from_slice(&[1, 2]));
+        v.push(3);
+        let _w = pool.alloc(MyVec::from_slice(&[4]));
+        assert_eq!(v.as_slice(), [1, 2, 3]);
+    }
+    assert_eq!(pool.stats().in_use, 0);
+
+    //every value is dropped once, by its box
+    let drops = Cell::new(0);
+    let tracked = MyPool::with_chunk_slots(2);
+    let boxes: Vec<_> = (0..3).map(|_| tracked.alloc(Tracked(&drops))).collect();
+    assert_eq!(tracked.stats().chunks, 2);
+    drop(boxes);
+    assert_eq!((drops.get(), tracked.stats().in_use), (3, 0));
+}
+
+#[test]
+#[should_panic(expected = "capacity overflow")]
+fn oversized_chunks_panic() {
+    MyPool::<u64>::with_chunk_slots(usize::MAX / size_of::<u64>() + 2);
+}
@@ -0,0 +1,106 @@
+//SharedHeap tests, every region is mapped twice, so the two mappings are at different addresses like in two processes
+#![cfg(all(feature = "std", target_os = "linux", target_pointer_width = "64"))]
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use memory_manager::{SharedHeap, SharedVec, REGION_ALIGN};
+
+const LEN: usize = 1 << 16;
+
+fn temp_file(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("memory_manager_{}_{}.shm", std::process::id(), name))
+}
+
+//the region is created through one mapping and opened through another one, the file is removed right away
+fn two_mappings(name: &str) -> (SharedHeap, SharedHeap) {
+    let path = temp_file(name);
+    let _ = fs::remove_file(&path);
+    let first = SharedHeap::create(&path, LEN).unwrap();
+    let second = SharedHeap::open(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_ne!(first.at::<u8>(0), second.at::<u8>(0));
+    (first, second)
+}
+
+#[test]
+fn both_mappings_see_the_same_heap() {
+    let (first, second) = two_mappings("same");
+    let free_space = first.free_space();
+
+    //the vec itself lives in the region, the root tells the other mapping where
+    let root = first.alloc(size_of::<SharedVec<u64>>(), align_of::<SharedVec<u64>>()).unwrap();
+    unsafe {
+        first.at::<SharedVec<u64>>(root).write(SharedVec::new());
+    }
+    first.set_root(Some(root));
+
+    let offset = second.root().unwrap();
+    let vec = unsafe {
+        &mut *second.at::<SharedVec<u64>>(offset)
+    };
+    for i in 0..100 {
+        vec.push(&second, i);
+    }
+
+    let vec = unsafe {
+        &mut *first.at::<SharedVec<u64>>(root)
+    };
+    assert!(vec.as_slice(&first).iter().copied().eq(0..100));
+    assert_eq!(vec.pop(&first), Some(99));
+    assert_eq!(first.free_space(), second.free_space());
+
+    vec.free(&second);
+    unsafe {
+        second.free(root);
+    }
+    first.set_root(None);
+    assert_eq!(second.root(), None);
+    assert_eq!(first.free_space(), free_space);
+}
+
+#[test]
+fn offsets_are_aligned_in_both_mappings() {
+    let (first, second) = two_mappings("aligned");
+
+    let mut offsets = Vec::new();
+    for (i, alignment) in [1, 8, 16, 32, 64, 8, 64, 2].into_iter().enumerate() {
+        let offset = first.alloc(3 + i * 5, alignment).unwrap();
+        assert!(first.at::<u8>(offset).addr().is_multiple_of(alignment));
+        assert!(second.at::<u8>(offset).addr().is_multiple_of(alignment));
+        offsets.push(offset);
+    }
+    //only REGION_ALIGN is the same in every mapping
+    assert_eq!(first.alloc(8, REGION_ALIGN * 2), None);
+
+    let free_space = second.free_space();
+    for offset in offsets {
+        unsafe {
+            second.free(offset);
+        }
+    }
+    assert!(first.free_space() > free_space);
+    assert_eq!(first.alloc(LEN, 8), None);
+}
+
+#[test]
+fn a_dead_owner_does_not_keep_the_lock() {
+    let (first, second) = two_mappings("dead");
+
+    //a process which has exited (and was waited for) holds the lock, it's the word after the magic of the region header
+    let mut child = Command::new(env::current_exe().unwrap()).arg("--list").stdout(Stdio::null()).spawn().unwrap();
+    let dead = child.id();
+    child.wait().unwrap();
+    unsafe {
+        (*first.at::<AtomicU32>(size_of::<u64>())).store(dead, Ordering::Release);
+    }
+
+    let offset = second.alloc(16, 8).unwrap();
+    unsafe {
+        first.free(offset);
+    }
+    assert_eq!(unsafe { (*first.at::<AtomicU32>(size_of::<u64>())).load(Ordering::Acquire) }, 0);
+}
@@ -0,0 +1,180 @@
+//snapshot and restore tests, restore replaces the whole global heap, so every test holds it from start to end
+#![cfg(feature = "std")]
+
+mod support {
+    pub mod serial;
+}
+
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::process::Command;
+
+use memory_manager::{compact, register_tag, restore, snapshot, usage_by_tag, validate_heap, with_tag, MyHandle, MyVec};
+use support::serial::heap;
+
+//the file and the handle indices are passed to the child process in these
+const PATH_VAR: &str = "MEMORY_MANAGER_SNAPSHOT";
+const HANDLE_VAR: &str = "MEMORY_MANAGER_HANDLE";
+
+fn temp_file(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("memory_manager_{}_{}.snap", std::process::id(), name))
+}
+
+#[test]
+fn snapshot_survives_a_new_process() {
+    let _heap = heap();
+    compact();
+    let path = temp_file("process");
+
+    let tag = register_tag("moved").unwrap();
+    let handle = with_tag(tag, || MyHandle::new([1u64, 2, 3, 4]));
+    let big = MyHandle::new([7u8; 2048]);
+    snapshot(&path).unwrap();
+    let (index, big_index) = (handle.into_raw(), big.into_raw());
+
+    //the heap of the child is at another address
+    let child = Command::new(env::current_exe().unwrap())
+        .args(["--exact", "restore_in_a_new_process", "--ignored"])
+        .env(PATH_VAR, &path)
+        .env(HANDLE_VAR, format!("{} {}", index, big_index))
+        .output()
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+    assert!(child.status.success(), "{}{}", String::from_utf8_lossy(&child.stdout), String::from_utf8_lossy(&child.stderr));
+
+    unsafe {
+        drop(MyHandle::<[u64; 4]>::from_raw(index));
+        drop(MyHandle::<[u8; 2048]>::from_raw(big_index));
+    }
+}
+
+#[test]
+#[ignore = "started by snapshot_survives_a_new_process"]
+fn restore_in_a_new_process() {
+    let _heap = heap();
+    let path = env::var(PATH_VAR).unwrap();
+    let indices: Vec<usize> = env::var(HANDLE_VAR).unwrap().split(' ').map(|i| i.parse().unwrap()).collect();
+
+    unsafe {
+        restore(&path).unwrap();
+    }
+    validate_heap().unwrap();
+    let (handle, big) = unsafe {
+        (MyHandle::<[u64; 4]>::from_raw(indices[0]), MyHandle::<[u8; 2048]>::from_raw(indices[1]))
+    };
+    assert_eq!(*handle.borrow(), [1, 2, 3, 4]);
+    assert_eq!(*big.borrow(), [7; 2048]);
+    assert!(usage_by_tag().iter().any(|usage| usage.name == "moved" && usage.count == 1));
+
+    //the restored blocks are usable like any other
+    drop(big);
+    compact();
+    assert_eq!(*handle.borrow(), [1, 2, 3, 4]);
+    drop(handle);
+}
+
+#[test]
+fn restore_brings_back_the_freed_handles() {
+    let _heap = heap();
+    compact();
+    let path = temp_file("round_trip");
+
+    let handle = MyHandle::new([5u32; 8]);
+    snapshot(&path).unwrap();
+    let index = handle.into_raw();
+    unsafe {
+        drop(MyHandle::<[u32; 8]>::from_raw(index));
+        restore(&path).unwrap();
+    }
+    fs::remove_file(&path).unwrap();
+
+    let handle = unsafe { MyHandle::<[u32; 8]>::from_raw(index) };
+    assert_eq!(*handle.borrow(), [5; 8]);
+    drop(handle);
+}
+
+//overwrites len bytes at offset (from the end if negative)
+fn corrupt(bytes: &[u8], offset: isize, patch: &[u8]) -> Vec<u8> {
+    let mut bytes = bytes.to_vec();
+    let at = if offset < 0 { bytes.len() - offset.unsigned_abs() } else { offset as usize };
+    bytes[at..at + patch.len()].copy_from_slice(patch);
+    bytes
+}
+
+#[test]
+fn corrupt_snapshots_leave_the_heap_alone() {
+    let _heap = heap();
+    compact();
+    let path = temp_file("corrupt");
+    let live = MyVec::from([1u32, 2, 3]);
+    snapshot(&path).unwrap();
+    let good = fs::read(&path).unwrap();
+
+    //magic 8, version 4, usize width 4, byte order mark 4, heap size 8, heap base 8, first free 8
+    let first_free = 36;
+    //the heap ends before the large base 8, page count 4, pages 128 * 4, large size 8 and the large bytes
+    let heap_start = -(8192 + 8 + 4 + 128 * 4 + 8 + 65536);
+
+    let mut swapped = good[16..20].to_vec();
+    swapped.reverse();
+    let cases = [
+        (good[..good.len() - 1].to_vec(), "truncated"),
+        (corrupt(&good, 16, &swapped), "byte order"),
+        (corrupt(&good, first_free, &[3; 8]), "free list points outside the heap"),
+        (corrupt(&good, heap_start, &[3, 0, 0, 0, 0, 0, 0, 0]), "invalid size"),
+        //a handle ptr into the middle of nowhere: the first handle starts after the first free block
+        (corrupt(&good, first_free + 8 + 4, &[8; 8]), "handle doesn't point to an allocation"),
+    ];
+    for (bytes, reason) in cases {
+        fs::write(&path, bytes).unwrap();
+        let err = unsafe { restore(&path) }.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(err.to_string().contains(reason), "{} doesn't say {}", err, reason);
+        validate_heap().unwrap();
+        assert_eq!(live, [1, 2, 3]);
+    }
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn tag_names_are_not_leaked_again() {
+    let _heap = heap();
+    compact();
+    let (before, after) = (temp_file("before"), temp_file("after"));
+
+    snapshot(&before).unwrap();
+    let name = "restored";
+    register_tag(name).unwrap();
+    snapshot(&after).unwrap();
+
+    let restored_name = || {
+        let usages = usage_by_tag();
+        usages.iter().find(|usage| usage.name == name).map(|usage| usage.name.as_ptr())
+    };
+
+    //the registered name is used as it is
+    unsafe {
+        restore(&after).unwrap();
+    }
+    assert_eq!(restored_name(), Some(name.as_ptr()));
+
+    //a name which isn't registered any more is leaked once, then shared
+    unsafe {
+        restore(&before).unwrap();
+        assert_eq!(restored_name(), None);
+        restore(&after).unwrap();
+    }
+    let leaked = restored_name().unwrap();
+    assert_ne!(leaked, name.as_ptr());
+    unsafe {
+        restore(&before).unwrap();
+        restore(&after).unwrap();
+    }
+    assert_eq!(restored_name(), Some(leaked));
+
+    fs::remove_file(&before).unwrap();
+    fs::remove_file(&after).unwrap();
+}
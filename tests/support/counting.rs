@@ -0,0 +1,37 @@
+//a MyAllocator over the global heap which counts its blocks and bytes, grow and shrink are the trait's defaults
+
+use std::alloc::Layout;
+use std::cell::Cell;
+use std::ptr::NonNull;
+
+use memory_manager::{AllocError, GlobalHeap, MyAllocator};
+
+#[derive(Default)]
+pub struct Counting {
+    pub allocations: Cell<usize>,
+    pub deallocations: Cell<usize>,
+    pub live_bytes: Cell<usize>,
+}
+
+impl Counting {
+    pub fn live(&self) -> usize {
+        self.allocations.get() - self.deallocations.get()
+    }
+}
+
+unsafe impl MyAllocator for Counting {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        let ptr = GlobalHeap.allocate(layout)?;
+        self.allocations.set(self.allocations.get() + 1);
+        self.live_bytes.set(self.live_bytes.get() + layout.size());
+        Ok(ptr)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.deallocations.set(self.deallocations.get() + 1);
+        self.live_bytes.set(self.live_bytes.get() - layout.size());
+        unsafe {
+            GlobalHeap.deallocate(ptr, layout);
+        }
+    }
+}
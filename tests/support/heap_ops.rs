@@ -0,0 +1,175 @@
+//the operations shared by the randomized test and the fuzz target, and a model of the live blocks
+//every step is checked against the model and with validate_heap
+
+use std::sync::MutexGuard;
+
+use memory_manager::{compact, heap_stats, large_stats, my_free, my_shrink, my_try_alloc, my_usable_size, set_large_threshold, validate_heap};
+use super::serial::heap;
+
+const HEADER_SIZE: usize = size_of::<usize>() * 2;
+const LARGE_THRESHOLD: usize = 1024;
+const PAGE_SIZE: usize = 512;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Op {
+    Alloc { size: usize, alignment: usize },
+    //index into the live blocks, taken modulo their count
+    Free { index: usize },
+    //size is taken modulo the block's size + 1
+    Shrink { index: usize, size: usize },
+}
+
+impl Op {
+    //3 bytes per op, so any byte string is a valid sequence
+    pub fn decode(bytes: &[u8]) -> impl Iterator<Item = Op> + '_ {
+        bytes.chunks_exact(3).map(|op| {
+            let arg = u16::from_le_bytes([op[1], op[2]]) as usize;
+            if op[0] & 0x80 == 0 {
+                Op::Alloc { size: arg % 2048, alignment: 1 << (op[0] % 7) }
+            } else if op[0] & 0x40 == 0 {
+                Op::Free { index: arg }
+            } else {
+                Op::Shrink { index: (op[0] & 0x3F) as usize, size: arg }
+            }
+        })
+    }
+}
+
+struct Block {
+    ptr: *mut u8,
+    size: usize,
+    seed: u8,
+    //served by the large region, it stays there even if it's shrunk below the threshold
+    large: bool,
+}
+
+impl Block {
+    fn pattern(&self, i: usize) -> u8 {
+        self.seed.wrapping_add((i as u8).wrapping_mul(31))
+    }
+
+    fn fill(&self) {
+        for i in 0..self.size {
+            unsafe {
+                *self.ptr.add(i) = self.pattern(i);
+            }
+        }
+    }
+
+    fn check(&self) {
+        for i in 0..self.size {
+            let byte = unsafe {
+                *self.ptr.add(i)
+            };
+            assert_eq!(byte, self.pattern(i), "block at {:p} of {} bytes was overwritten at byte {}", self.ptr, self.size, i);
+        }
+    }
+}
+
+pub struct Model {
+    live: Vec<Block>,
+    next_seed: u8,
+    //free bytes of the heap when the model was created, all of them have to come back in finish
+    initial_free: usize,
+    _heap: MutexGuard<'static, ()>,
+}
+
+impl Model {
+    //merges the free blocks left by the previous model, so every sequence starts from an empty heap
+    //only one model may use the heap at a time, it's held until the model is dropped
+    pub fn new() -> Model {
+        let heap = heap();
+        compact();
+        set_large_threshold(LARGE_THRESHOLD);
+        validate_heap().unwrap();
+        Model { live: Vec::new(), next_seed: 0, initial_free: heap_stats().free_bytes, _heap: heap }
+    }
+
+    pub fn live(&self) -> usize {
+        self.live.len()
+    }
+
+    pub fn apply(&mut self, op: Op) {
+        match op {
+            Op::Alloc { size, alignment } => self.alloc(size, alignment),
+            Op::Free { index } => self.free(index),
+            Op::Shrink { index, size } => self.shrink(index, size),
+        }
+
+        validate_heap().unwrap();
+        let used: usize = self.live.iter().filter(|block| !block.large).map(|block| HEADER_SIZE + block.size.next_multiple_of(size_of::<usize>())).sum();
+        assert!(heap_stats().free_bytes + used <= self.initial_free, "the free list has more bytes than the heap");
+        for block in &self.live {
+            block.check();
+        }
+    }
+
+    //frees every live block, afterwards the heap has to have all of its free bytes back
+    pub fn finish(mut self) {
+        while !self.live.is_empty() {
+            self.apply(Op::Free { index: 0 });
+        }
+        assert_eq!(heap_stats().free_bytes, self.initial_free, "bytes were lost");
+        assert_eq!(large_stats().allocations, 0, "large allocations were lost");
+    }
+
+    fn alloc(&mut self, size: usize, alignment: usize) {
+        let ptr = my_try_alloc(size, alignment);
+
+        if ptr.is_null() && size >= LARGE_THRESHOLD {
+            //every alignment up to a page is satisfied by any free run
+            assert!(large_stats().largest_free < size.next_multiple_of(PAGE_SIZE), "large allocation of {} bytes failed, but a free run fits it", size);
+            return;
+        }
+        if ptr.is_null() {
+            //first fit has to find a block if there is one large enough for the worst case front padding
+            let worst_case = HEADER_SIZE + size.next_multiple_of(size_of::<usize>()) + alignment.max(8) - 8;
+            assert!(heap_stats().largest_free < worst_case, "allocation of {} bytes failed, but a free block fits it", size);
+            return;
+        }
+
+        assert!((ptr as usize).is_multiple_of(alignment), "{:p} is not aligned to {}", ptr, alignment);
+        let usable = unsafe {
+            my_usable_size(ptr)
+        };
+        assert!(usable >= size, "{:p} has only {} usable bytes, {} were requested", ptr, usable, size);
+        let (start, end) = (ptr as usize, ptr as usize + size);
+        for block in &self.live {
+            let (other_start, other_end) = (block.ptr as usize, block.ptr as usize + block.size);
+            assert!(end <= other_start || other_end <= start, "{:p} overlaps the live block at {:p}", ptr, block.ptr);
+        }
+
+        let block = Block { ptr, size, seed: self.next_seed, large: size >= LARGE_THRESHOLD };
+        self.next_seed = self.next_seed.wrapping_add(1);
+        block.fill();
+        self.live.push(block);
+    }
+
+    fn shrink(&mut self, index: usize, size: usize) {
+        if self.live.is_empty() {
+            return;
+        }
+
+        let index = index % self.live.len();
+        let block = &mut self.live[index];
+        let size = size % (block.size + 1);
+
+        let usable = unsafe {
+            my_shrink(block.ptr, size)
+        };
+        assert!(usable >= size, "{:p} has only {} usable bytes after shrinking to {}", block.ptr, usable, size);
+        block.size = size;
+    }
+
+    fn free(&mut self, index: usize) {
+        if self.live.is_empty() {
+            return;
+        }
+
+        let block = self.live.swap_remove(index % self.live.len());
+        block.check();
+        unsafe {
+            my_free(block.ptr);
+        }
+    }
+}
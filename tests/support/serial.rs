@@ -0,0 +1,11 @@
+//the tests of a binary run in parallel on the same global heap,
+//a test which looks at the whole heap or changes the manager's fault policy holds this for its duration
+
+use std::sync::{Mutex, MutexGuard};
+
+static HEAP_USER: Mutex<()> = Mutex::new(());
+
+//a panicking test poisons the mutex, the heap is still usable for the others
+pub fn heap() -> MutexGuard<'static, ()> {
+    HEAP_USER.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -0,0 +1,107 @@
+//allocation tag tests: attribution through with_tag, usage_by_tag and the tag kept in a block's header
+
+mod support {
+    pub mod serial;
+}
+
+use std::panic::catch_unwind;
+use std::sync::OnceLock;
+
+use memory_manager::{my_alloc, my_free, register_tag, usage_by_tag, with_tag, MyVec, Tag, TagUsage, TooManyTags};
+use support::serial::heap;
+
+//the tags live as long as the test binary, so they are registered once
+fn tags() -> (Tag, Tag) {
+    static TAGS: OnceLock<(Tag, Tag)> = OnceLock::new();
+    *TAGS.get_or_init(|| (register_tag("parser").unwrap(), register_tag("render").unwrap()))
+}
+
+fn usage(tag: Tag) -> TagUsage {
+    usage_by_tag()[tag.id() as usize]
+}
+
+#[test]
+fn allocations_go_to_the_innermost_scope() {
+    let _heap = heap();
+    let (parser, render) = tags();
+    let (before_parser, before_render) = (usage(parser), usage(render));
+    assert_eq!((before_parser.name, before_render.name), ("parser", "render"));
+
+    let (outer, inner, back) = with_tag(parser, || {
+        let outer = MyVec::from_slice(&[1u64; 4]);
+        let inner = with_tag(render, || MyVec::from_slice(&[2u64; 8]));
+        let back = MyVec::from_slice(&[3u8]);
+        (outer, inner, back)
+    });
+    let untagged = MyVec::from_slice(&[4u8]);
+
+    let (p, r) = (usage(parser), usage(render));
+    assert_eq!(p.count - before_parser.count, 2);
+    assert_eq!(r.count - before_render.count, 1);
+    assert!(p.bytes - before_parser.bytes > 32);
+    assert!(r.bytes - before_render.bytes >= 64);
+
+    drop((outer, inner, back, untagged));
+    assert_eq!((usage(parser), usage(render)), (before_parser, before_render));
+}
+
+#[test]
+fn a_panic_leaves_the_scope() {
+    let _heap = heap();
+    let (parser, _) = tags();
+    let before = usage(parser);
+
+    assert!(catch_unwind(|| with_tag(parser, || panic!("parse error"))).is_err());
+    let v = MyVec::from_slice(&[1u8]);
+    assert_eq!(usage(parser), before);
+
+    drop(v);
+    assert_eq!(usage(parser), before);
+}
+
+#[test]
+fn the_tag_stays_with_the_block() {
+    let _heap = heap();
+    let (_, render) = tags();
+    let before = usage(render);
+
+    let ptr = with_tag(render, || my_alloc(200, 8));
+    let allocated = usage(render);
+    assert_eq!(allocated.count, before.count + 1);
+    assert!((200..256).contains(&(allocated.bytes - before.bytes)));
+
+    //a vec grown outside the scope moves to the untagged usage
+    let mut v = with_tag(render, || MyVec::<u8>::with_capacity(4));
+    assert_eq!(usage(render).count, allocated.count + 1);
+    v.try_reserve(100).unwrap();
+    assert_eq!(usage(render), allocated);
+
+    //freeing outside the scope gives the bytes back to the block's own tag
+    unsafe {
+        my_free(ptr);
+    }
+    drop(v);
+    assert_eq!(usage(render), before);
+}
+
+#[test]
+fn registering_too_many_tags_fails() {
+    let _heap = heap();
+    tags();
+
+    let mut registered = usage_by_tag().len();
+    loop {
+        match register_tag("filler") {
+            Ok(tag) => assert_eq!(tag.id() as usize, registered),
+            Err(e) => {
+                assert_eq!(e, TooManyTags);
+                assert_eq!(e.to_string(), "unable to register tag, too many tags");
+                break;
+            }
+        }
+        registered += 1;
+    }
+    assert_eq!(registered, 64);
+    assert_eq!(usage_by_tag().len(), 64);
+    assert_eq!(register_tag("one more"), Err(TooManyTags));
+}
@@ -0,0 +1,500 @@
+//MyVec tests, the ones which check the heap hold support::serial::heap
+
+mod support {
+    pub mod serial;
+}
+
+use memory_manager::{HeapCheckpoint, MyVec};
+use support::serial::heap;
+
+#[test]
+fn empty_vec_doesnt_allocate() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    let v: MyVec<u64> = MyVec::new();
+    let w: MyVec<u64> = MyVec::with_capacity(0);
+    assert_eq!(checkpoint.diff().allocations, 0);
+
+    assert_eq!(v.capacity(), 0);
+    assert_eq!(w.capacity(), 0);
+    assert!(v.as_slice().is_empty());
+    assert_eq!(v.iter().count(), 0);
+    assert!((v.as_slice().as_ptr() as usize).is_multiple_of(align_of::<u64>()));
+
+    drop(v);
+    drop(w);
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn empty_into_iter() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    let v: MyVec<String> = MyVec::new();
+    let mut iter = v.into_iter();
+    assert_eq!(iter.size_hint(), (0, Some(0)));
+    assert!(iter.next().is_none());
+    drop(iter);
+
+    //a vec which had a buffer and was emptied
+    let mut v = MyVec::from_slice(&[1, 2, 3]);
+    v.clear();
+    assert_eq!(v.into_iter().count(), 0);
+
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn shrinking_to_empty_frees_the_buffer() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    let mut v: MyVec<u32> = MyVec::with_capacity(32);
+    v.shrink_to_fit();
+    assert_eq!(v.capacity(), 0);
+    assert_eq!(checkpoint.diff().allocations, 0);
+
+    v.push(7);
+    assert_eq!(v.as_slice(), &[7]);
+    drop(v);
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn zero_sized_values() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    let mut v: MyVec<()> = MyVec::with_capacity(10);
+    assert_eq!(v.capacity(), usize::MAX);
+
+    for _ in 0..1000 {
+        v.push(());
+    }
+    v.insert(500, ());
+    v.reserve(1 << 40);
+    assert_eq!(v.len(), 1001);
+    assert_eq!(v.iter().count(), 1001);
+    assert_eq!(v.pop(), Some(()));
+    assert_eq!(v.remove(0), ());
+    assert_eq!(checkpoint.diff().allocations, 0);
+
+    let clone = v.clone();
+    assert_eq!(clone.len(), 999);
+    assert_eq!(v.into_iter().count(), 999);
+    drop(clone);
+
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn zero_sized_values_with_drop() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    struct Counted;
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let mut v = MyVec::new();
+    for _ in 0..10 {
+        v.push(Counted);
+    }
+    drop(v.pop());
+    assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+
+    let mut iter = v.into_iter();
+    iter.next();
+    assert_eq!(DROPS.load(Ordering::Relaxed), 2);
+    drop(iter);
+    assert_eq!(DROPS.load(Ordering::Relaxed), 10);
+}
+
+#[test]
+fn zero_sized_capacity_overflow() {
+    let mut v: MyVec<()> = MyVec::new();
+    assert!(v.try_reserve(usize::MAX).is_ok());
+    v.push(());
+    assert!(v.try_reserve(usize::MAX).is_err());
+}
+
+//the Vec API of MyVec, every operation is checked against std's Vec
+
+fn assert_same<T: PartialEq + std::fmt::Debug>(mine: &MyVec<T>, std: &[T]) {
+    assert_eq!(mine.as_slice(), std);
+}
+
+#[test]
+fn reserve_and_resize() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    let mut mine: MyVec<u32> = MyVec::new();
+    let mut std = Vec::new();
+
+    mine.reserve_exact(5);
+    assert!(mine.capacity() >= 5);
+    mine.reserve(20);
+    assert!(mine.capacity() >= 20);
+    assert!(mine.try_reserve_exact(usize::MAX).is_err());
+
+    mine.resize(7, 3);
+    std.resize(7, 3);
+    assert_same(&mine, &std);
+    mine.resize(2, 9);
+    std.resize(2, 9);
+    assert_same(&mine, &std);
+
+    let mut counter = 0;
+    mine.resize_with(6, || { counter += 1; counter });
+    let mut counter = 0;
+    std.resize_with(6, || { counter += 1; counter });
+    assert_same(&mine, &std);
+
+    drop(mine);
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn retain_and_dedup() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    let values = [1, 1, 2, 3, 3, 3, 4, 5, 5, 6, 7, 7, 8];
+    let mut mine = MyVec::from_slice(&values);
+    let mut std = values.to_vec();
+
+    mine.retain(|&x| x != 3);
+    std.retain(|&x| x != 3);
+    assert_same(&mine, &std);
+
+    mine.retain_mut(|x| { *x *= 10; *x != 70 });
+    std.retain_mut(|x| { *x *= 10; *x != 70 });
+    assert_same(&mine, &std);
+
+    mine.dedup();
+    std.dedup();
+    assert_same(&mine, &std);
+
+    mine.dedup_by_key(|x| *x / 30);
+    std.dedup_by_key(|x| *x / 30);
+    assert_same(&mine, &std);
+
+    mine.dedup_by(|a, b| *a == *b + 20);
+    std.dedup_by(|a, b| *a == *b + 20);
+    assert_same(&mine, &std);
+
+    drop(mine);
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn retain_drops_removed_values() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    let mut mine = MyVec::new();
+    for i in 0..8 {
+        mine.push(MyVec::from_slice(&[i]));
+    }
+    mine.retain(|v| v[0] % 2 == 0);
+    mine.dedup_by_key(|v| v[0] / 4);
+    assert_eq!(mine.iter().map(|v| v[0]).collect::<Vec<_>>(), [0, 4]);
+
+    drop(mine);
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn split_off_and_swap_remove() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    let mut mine = MyVec::from_slice(&[1, 2, 3, 4, 5, 6]);
+    let mut std = vec![1, 2, 3, 4, 5, 6];
+
+    let mine_tail = mine.split_off(4);
+    let std_tail = std.split_off(4);
+    assert_same(&mine, &std);
+    assert_same(&mine_tail, &std_tail);
+
+    assert_eq!(mine.swap_remove(0), std.swap_remove(0));
+    assert_same(&mine, &std);
+    assert_eq!(mine.swap_remove(2), std.swap_remove(2));
+    assert_same(&mine, &std);
+
+    assert!(mine.split_off(mine.len()).is_empty());
+
+    drop((mine, mine_tail));
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn extend_from_within() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    let mut mine = MyVec::from_slice(&[1, 2, 3, 4]);
+    let mut std = vec![1, 2, 3, 4];
+
+    mine.extend_from_within(1..3);
+    std.extend_from_within(1..3);
+    assert_same(&mine, &std);
+    mine.extend_from_within(..);
+    std.extend_from_within(..);
+    assert_same(&mine, &std);
+    mine.extend_from_within(5..=5);
+    std.extend_from_within(5..=5);
+    assert_same(&mine, &std);
+
+    drop(mine);
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn splice() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    let values = [0, 1, 2, 3, 4, 5, 6, 7];
+    //shorter, same length, longer, longer without an exact size hint, at the end, everything removed
+    let cases: [(usize, usize, &[i32], bool); 6] = [
+        (1, 5, &[10, 11], false),
+        (2, 4, &[20, 21], false),
+        (3, 4, &[30, 31, 32, 33, 34], false),
+        (3, 4, &[30, 31, 32, 33, 34], true),
+        (8, 8, &[40], false),
+        (0, 8, &[], false),
+    ];
+
+    for (start, end, replacement, filtered) in cases {
+        let mut mine = MyVec::from_slice(&values);
+        let mut std = values.to_vec();
+
+        let (mine_removed, std_removed): (Vec<_>, Vec<_>) = if filtered {
+            (
+                mine.splice(start..end, replacement.iter().copied().filter(|_| true)).collect(),
+                std.splice(start..end, replacement.iter().copied().filter(|_| true)).collect(),
+            )
+        } else {
+            (
+                mine.splice(start..end, replacement.iter().copied()).collect(),
+                std.splice(start..end, replacement.iter().copied()).collect(),
+            )
+        };
+        assert_eq!(mine_removed, std_removed);
+        assert_same(&mine, &std);
+    }
+
+    //the removed values aren't taken
+    let mut mine = MyVec::from_slice(&values);
+    let mut std = values.to_vec();
+    drop(mine.splice(2..6, [9; 7]));
+    drop(std.splice(2..6, [9; 7]));
+    assert_same(&mine, &std);
+
+    drop(mine);
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn getters() {
+    let _heap = heap();
+
+    let mut mine = MyVec::from_slice(&[4, 5, 6]);
+    let std = Vec::from([4, 5, 6]);
+
+    assert_eq!(mine.first(), std.first());
+    assert_eq!(mine.last(), std.last());
+    assert_eq!(mine.get(1), std.get(1));
+    assert_eq!(mine.get(3), std.get(3));
+    assert_eq!(mine.get(1..), std.get(1..));
+    assert_eq!(mine.get(2..4), std.get(2..4));
+    assert!(mine.contains(&6));
+    assert!(!mine.contains(&7));
+
+    *mine.get_mut(0).unwrap() = 40;
+    mine.get_mut(1..).unwrap().reverse();
+    assert_eq!(mine.as_slice(), &[40, 6, 5]);
+    assert!(mine.get_mut(5).is_none());
+
+    let empty: MyVec<i32> = MyVec::new();
+    assert_eq!(empty.first(), None);
+    assert_eq!(empty.last(), None);
+}
+
+#[test]
+fn leak_and_into_boxed_slice() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    let mut mine: MyVec<u64> = MyVec::with_capacity(16);
+    mine.extend_from_slice(&[1, 2, 3]);
+    let boxed = mine.into_boxed_slice();
+    assert_eq!(&*boxed, &[1, 2, 3]);
+    assert_eq!(boxed.len(), vec![1, 2, 3].into_boxed_slice().len());
+    drop(boxed);
+
+    let empty: MyVec<u64> = MyVec::from_slice(&[1]).split_off(1);
+    assert!(empty.into_boxed_slice().is_empty());
+    checkpoint.assert_no_leaks();
+
+    let leaked: &'static mut [u32] = MyVec::from_slice(&[7, 8]).leak();
+    leaked[0] += 1;
+    assert_eq!(leaked, &[8, 8]);
+    assert_eq!(checkpoint.diff().allocations, 1);
+    unsafe {
+        memory_manager::my_free(leaked.as_mut_ptr() as *mut u8);
+    }
+}
+
+#[test]
+fn standard_traits() {
+    use std::collections::{BTreeSet, HashMap};
+    use memory_manager::my_vec;
+
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    //collect, extend, deref to a slice
+    let mut mine: MyVec<i32> = (1..=4).collect();
+    mine.extend([5, 6]);
+    mine.extend(&[7]);
+    assert_eq!(mine, [1, 2, 3, 4, 5, 6, 7]);
+    assert_eq!(mine.iter().sum::<i32>(), 28);
+    assert_eq!(mine.binary_search(&5), Ok(4));
+    mine.reverse();
+    assert_eq!(&mine[1..3], &[6, 5]);
+    mine[..2].copy_from_slice(&[0, 0]);
+    assert_eq!(mine.as_slice(), &[0, 0, 5, 4, 3, 2, 1]);
+
+    fn total(values: &[i32]) -> i32 {
+        values.iter().sum()
+    }
+    assert_eq!(total(&mine), 15);
+    assert_eq!(total(mine.as_ref()), 15);
+
+    //comparisons like std's Vec
+    let a = my_vec![1, 2, 3];
+    let b = MyVec::from(&[1, 2, 4][..]);
+    assert!(a < b);
+    assert_eq!(a.cmp(&b), vec![1, 2, 3].cmp(&vec![1, 2, 4]));
+    assert_ne!(a, b);
+    assert_eq!(a, &[1, 2, 3][..]);
+    assert_eq!(a, MyVec::<i32>::from([1, 2, 3]));
+    assert!(MyVec::<i32>::new() < a);
+
+    //keys of maps, looked up by slice
+    let mut map = HashMap::new();
+    map.insert(my_vec![1u8, 2], "a");
+    map.insert(my_vec![3u8], "b");
+    assert_eq!(map.get(&[1u8, 2][..]), Some(&"a"));
+    assert_eq!(map.get(&my_vec![3u8]), Some(&"b"));
+    assert_eq!(map.get(&[4u8][..]), None);
+
+    let set: BTreeSet<MyVec<u8>> = [my_vec![2, 1], my_vec![1, 9], my_vec![2]].into_iter().collect();
+    assert_eq!(set.iter().map(|v| v.as_slice()).collect::<Vec<_>>(), [&[1, 9][..], &[2], &[2, 1]]);
+
+    //macro forms
+    let empty: MyVec<u8> = my_vec![];
+    assert!(empty.is_empty());
+    assert_eq!(my_vec![7u16; 3], [7, 7, 7]);
+    assert_eq!(my_vec!["a".to_string(); 2], ["a", "a"]);
+    assert_eq!(my_vec![1, 2,], [1, 2]);
+    assert_eq!(MyVec::<u8>::default(), []);
+
+    drop((mine, a, b, map, set));
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn cloning_from_slices() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    let strings = ["a".to_string(), "b".to_string()];
+    let mut mine = MyVec::from_slice(&strings);
+    mine.insert_slice(1, &strings);
+    mine.extend_from_slice(&strings[..1]);
+    assert_eq!(mine, ["a", "a", "b", "b", "a"]);
+
+    drop(mine);
+    drop(strings);
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn iterators_from_both_ends() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    let mut mine = MyVec::from_slice(&[1, 2, 3, 4, 5, 6]);
+    let std = Vec::from([1, 2, 3, 4, 5, 6]);
+
+    //borrowing iterators
+    assert!(mine.iter().rev().eq(std.iter().rev()));
+    assert_eq!(mine.iter().len(), 6);
+    assert_eq!(mine.iter().nth_back(1), std.iter().nth_back(1));
+    let mut iter = mine.iter();
+    assert_eq!((iter.next(), iter.next_back()), (Some(&1), Some(&6)));
+    assert_eq!(iter.len(), 4);
+    assert_eq!(iter.as_slice(), &[2, 3, 4, 5]);
+    let clone = iter.clone();
+    assert!(iter.eq(clone));
+
+    let mut iter = mine.iter_mut();
+    *iter.next_back().unwrap() *= 10;
+    *iter.next().unwrap() *= 10;
+    assert_eq!(iter.as_slice(), &[2, 3, 4, 5]);
+    assert_eq!(iter.len(), 4);
+    for value in iter.rev().step_by(2) {
+        *value = 0;
+    }
+    assert_eq!(mine, [10, 2, 0, 4, 0, 60]);
+
+    //a fused iterator keeps returning None
+    let mut iter = mine.iter();
+    iter.by_ref().for_each(drop);
+    assert_eq!((iter.next(), iter.next_back(), iter.len()), (None, None, 0));
+
+    //owning iterator, the values left in the middle are dropped with it
+    let strings = MyVec::from([MyVec::from([1]), MyVec::from([2]), MyVec::from([3]), MyVec::from([4])]);
+    let mut iter = strings.into_iter();
+    assert_eq!(iter.next_back(), Some(MyVec::from([4])));
+    assert_eq!(iter.next(), Some(MyVec::from([1])));
+    assert_eq!(iter.as_slice(), [MyVec::from([2]), MyVec::from([3])]);
+    assert_eq!(iter.len(), 2);
+    drop(iter);
+
+    let zipped: Vec<(i32, i32)> = MyVec::from([1, 2, 3]).into_iter().rev().zip(std.iter().copied()).collect();
+    assert_eq!(zipped, [(3, 1), (2, 2), (1, 3)]);
+
+    drop(mine);
+
+    //drain from both ends, the tail is still put back
+    let mut mine = MyVec::from_slice(&[1, 2, 3, 4, 5, 6, 7]);
+    let mut std = vec![1, 2, 3, 4, 5, 6, 7];
+    let mut drain = mine.drain(1..6);
+    let mut std_drain = std.drain(1..6);
+    assert_eq!(drain.len(), std_drain.len());
+    assert_eq!(drain.next_back(), std_drain.next_back());
+    assert_eq!(drain.next(), std_drain.next());
+    assert_eq!(drain.as_slice(), std_drain.as_slice());
+    assert_eq!(drain.rev().collect::<Vec<_>>(), std_drain.rev().collect::<Vec<_>>());
+    assert_eq!(mine, std.as_slice());
+
+    let mut drain = mine.drain(..);
+    drain.next_back();
+    drop(drain);
+    assert!(mine.is_empty());
+
+    drop(mine);
+    checkpoint.assert_no_leaks();
+}
@@ -0,0 +1,202 @@
+//MyVecDeque tests, checked against std's VecDeque
+
+mod support {
+    pub mod serial;
+    pub mod rng;
+}
+
+use std::collections::VecDeque;
+
+use memory_manager::{HeapCheckpoint, MyVec, MyVecDeque};
+use support::serial::heap;
+use support::rng::Rng;
+
+fn assert_same<T: PartialEq + std::fmt::Debug>(mine: &MyVecDeque<T>, std: &VecDeque<T>) {
+    assert_eq!(mine.len(), std.len());
+    assert!(mine.iter().eq(std.iter()), "{:?} != {:?}", mine, std);
+    assert!(mine.iter().rev().eq(std.iter().rev()));
+    assert_eq!(mine.front(), std.front());
+    assert_eq!(mine.back(), std.back());
+}
+
+#[test]
+fn random_operations_match_std() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+    let mut rng = Rng(0xDEC0);
+
+    let mut mine = MyVecDeque::new();
+    let mut std = VecDeque::new();
+    for i in 0..4000u32 {
+        match rng.below(10) {
+            0..=2 => {
+                mine.push_back(i);
+                std.push_back(i);
+            }
+            3..=4 => {
+                mine.push_front(i);
+                std.push_front(i);
+            }
+            5 => assert_eq!(mine.pop_back(), std.pop_back()),
+            6 => assert_eq!(mine.pop_front(), std.pop_front()),
+            7 if !std.is_empty() => {
+                let n = rng.below(std.len() + 1);
+                mine.rotate_left(n);
+                std.rotate_left(n);
+            }
+            8 if !std.is_empty() => {
+                let index = rng.below(std.len());
+                mine[index] += 1;
+                std[index] += 1;
+                let n = rng.below(std.len() + 1);
+                mine.rotate_right(n);
+                std.rotate_right(n);
+            }
+            _ => {
+                let (front, back) = mine.as_slices();
+                assert_eq!(front.len() + back.len(), std.len());
+                if i % 7 == 0 {
+                    assert_eq!(mine.make_contiguous(), std.make_contiguous());
+                }
+            }
+        }
+        assert_same(&mine, &std);
+    }
+
+    drop(mine);
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn growing_a_wrapped_deque() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    //the values wrap around before every growth, from both sides
+    for front_pushes in 0..12 {
+        let mut mine = MyVecDeque::with_capacity(4);
+        let mut std = VecDeque::new();
+        for i in 0..40 {
+            if i % 3 == 0 || i < front_pushes {
+                mine.push_front(i);
+                std.push_front(i);
+            } else {
+                mine.push_back(i);
+                std.push_back(i);
+            }
+            assert_same(&mine, &std);
+        }
+    }
+
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn contiguous_and_slices() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    let mut mine: MyVecDeque<i32> = MyVecDeque::with_capacity(8);
+    let capacity = mine.capacity();
+    for i in 0..capacity as i32 - 2 {
+        mine.push_back(i);
+    }
+    mine.pop_front();
+    mine.pop_front();
+    mine.push_back(100);
+    mine.push_back(101);
+    mine.push_back(102);
+
+    let (front, back) = mine.as_slices();
+    assert_eq!(back, &[102]);
+    assert_eq!(front.len(), capacity - 2);
+    assert!(mine.contains(&102) && mine.contains(&2) && !mine.contains(&0));
+
+    let expected: Vec<i32> = mine.iter().copied().collect();
+    assert_eq!(mine.make_contiguous(), expected.as_slice());
+    assert_eq!(mine.as_slices().1, &[] as &[i32]);
+    assert_eq!(mine.capacity(), capacity);
+
+    for value in &mut mine {
+        *value *= 2;
+    }
+    mine.swap(0, 1);
+    assert_eq!((mine[0], mine[1]), (expected[1] * 2, expected[0] * 2));
+    assert_eq!(mine.get(mine.len()), None);
+
+    drop(mine);
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn conversions_reuse_the_buffer() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    let vec = MyVec::from([1, 2, 3, 4, 5]);
+    let ptr = vec.as_ptr();
+    let mut deque = MyVecDeque::from(vec);
+    assert_eq!(checkpoint.diff().allocations, 1);
+
+    //wrap the values around without growing, then turn it back into a vec
+    deque.pop_back();
+    deque.push_front(0);
+    deque.rotate_right(1);
+    let vec = MyVec::from(deque);
+    assert_eq!(vec.as_ptr(), ptr);
+    assert_eq!(vec, [4, 0, 1, 2, 3]);
+    assert_eq!(checkpoint.diff().allocations, 1);
+
+    let deque: MyVecDeque<i32> = vec.into_iter().rev().collect();
+    let mut iter = deque.clone().into_iter();
+    assert_eq!((iter.len(), iter.next(), iter.next_back()), (5, Some(3), Some(4)));
+    assert_eq!(format!("{:?}", deque), "[3, 2, 1, 0, 4]");
+    assert_eq!(deque, deque.clone());
+    drop((deque, iter));
+
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn drops_every_value_once() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    let mut deque = MyVecDeque::new();
+    for i in 0..10 {
+        deque.push_front(MyVec::from([i]));
+        deque.push_back(MyVec::from([i]));
+    }
+    deque.truncate(15);
+    let mut iter = deque.into_iter();
+    iter.next();
+    iter.next_back();
+    drop(iter);
+
+    let mut deque = MyVecDeque::new();
+    deque.extend([MyVec::from([1]), MyVec::from([2])]);
+    deque.push_front(MyVec::from([0]));
+    deque.clear();
+    assert!(deque.is_empty());
+    drop(deque);
+
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn zero_sized_values() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+
+    let mut deque = MyVecDeque::new();
+    for _ in 0..100 {
+        deque.push_front(());
+        deque.push_back(());
+    }
+    assert_eq!(deque.len(), 200);
+    assert_eq!(deque.pop_front(), Some(()));
+    assert_eq!(deque.make_contiguous().len(), 199);
+    deque.rotate_left(50);
+    assert_eq!(deque.iter().count(), 199);
+    assert_eq!(checkpoint.diff().allocations, 0);
+}
@@ -0,0 +1,233 @@
+//panic safety of MyVec: values whose Clone or Drop panics are put into vecs,
+//after every caught panic each value must have been dropped at most once, and at the end exactly once
+
+mod support {
+    pub mod serial;
+}
+
+use std::cell::RefCell;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use memory_manager::{HeapCheckpoint, MyVec};
+use support::serial::heap;
+
+//counts the drops of every probe it made
+#[derive(Default)]
+struct Tracker {
+    drops: RefCell<Vec<u32>>,
+}
+
+impl Tracker {
+    fn probe(&self) -> Probe<'_> {
+        let mut drops = self.drops.borrow_mut();
+        drops.push(0);
+        Probe { id: drops.len() - 1, tracker: self, panic_on_drop: false, panic_on_clone: false }
+    }
+
+    fn probes(&self, n: usize) -> MyVec<Probe<'_>> {
+        (0..n).map(|_| self.probe()).collect()
+    }
+
+    fn dropped(&self, id: usize) -> u32 {
+        self.drops.borrow()[id]
+    }
+
+    fn assert_at_most_once(&self) {
+        let drops = self.drops.borrow();
+        assert!(drops.iter().all(|&d| d <= 1), "double drop: {:?}", drops);
+    }
+
+    fn assert_all_once(&self) {
+        let drops = self.drops.borrow();
+        assert!(drops.iter().all(|&d| d == 1), "not every probe was dropped once: {:?}", drops);
+    }
+}
+
+struct Probe<'a> {
+    id: usize,
+    tracker: &'a Tracker,
+    panic_on_drop: bool,
+    panic_on_clone: bool,
+}
+
+impl Clone for Probe<'_> {
+    fn clone(&self) -> Self {
+        if self.panic_on_clone {
+            panic!("clone of probe {}", self.id);
+        }
+        self.tracker.probe()
+    }
+}
+
+impl Drop for Probe<'_> {
+    fn drop(&mut self) {
+        self.tracker.drops.borrow_mut()[self.id] += 1;
+        if self.panic_on_drop {
+            panic!("drop of probe {}", self.id);
+        }
+    }
+}
+
+//runs f, which has to panic, and checks that nothing was dropped twice
+fn panics(tracker: &Tracker, f: impl FnOnce()) {
+    assert!(catch_unwind(AssertUnwindSafe(f)).is_err(), "expected a panic");
+    tracker.assert_at_most_once();
+}
+
+fn ids(v: &MyVec<Probe<'_>>) -> Vec<usize> {
+    v.iter().map(|p| p.id).collect()
+}
+
+#[test]
+fn clone_panics() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+    let tracker = Tracker::default();
+
+    let mut v = tracker.probes(5);
+    v[3].panic_on_clone = true;
+
+    panics(&tracker, || drop(v.clone()));
+    //the clones of the first three were dropped with the unfinished vec
+    assert_eq!((5..8).map(|id| tracker.dropped(id)).collect::<Vec<_>>(), [1, 1, 1]);
+
+    //the clones made before the panic stay in the vec
+    panics(&tracker, || v.extend_from_within(2..));
+    assert_eq!(v.len(), 6);
+
+    let mut source = tracker.probes(2);
+    source[1].panic_on_clone = true;
+    panics(&tracker, || v.extend_from_slice(&source));
+    assert_eq!(v.len(), 7);
+    drop(source);
+
+    let mut value = tracker.probe();
+    value.panic_on_clone = true;
+    panics(&tracker, || v.resize(10, value));
+    assert_eq!(v.len(), 7);
+
+    drop(v);
+    tracker.assert_all_once();
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn drop_panics_while_clearing() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+    let tracker = Tracker::default();
+
+    let mut v = tracker.probes(6);
+    v[4].panic_on_drop = true;
+    panics(&tracker, || v.truncate(2));
+    assert_eq!(ids(&v), [0, 1]);
+    assert!((2..6).all(|id| tracker.dropped(id) == 1));
+
+    v[0].panic_on_drop = true;
+    panics(&tracker, || v.clear());
+    assert!(v.is_empty());
+
+    v.push(tracker.probe());
+    drop(v);
+    tracker.assert_all_once();
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn drop_panics_while_dropping_the_vec() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+    let tracker = Tracker::default();
+
+    let mut v = tracker.probes(4);
+    v[1].panic_on_drop = true;
+    panics(&tracker, || drop(v));
+
+    tracker.assert_all_once();
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn drop_panics_in_iterators() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+    let tracker = Tracker::default();
+
+    //the owning iterator still frees its buffer
+    let mut v = tracker.probes(5);
+    v[3].panic_on_drop = true;
+    let mut iter = v.into_iter();
+    drop(iter.next());
+    panics(&tracker, || drop(iter));
+
+    //the drain still puts the tail back
+    let mut v = tracker.probes(6);
+    let first = v[0].id;
+    v[2].panic_on_drop = true;
+    panics(&tracker, || drop(v.drain(1..4)));
+    assert_eq!(ids(&v), [first, first + 4, first + 5]);
+
+    //so does the splice
+    v[1].panic_on_drop = true;
+    let replacement = tracker.probes(3);
+    panics(&tracker, || drop(v.splice(1..2, replacement)));
+    assert_eq!(v.len(), 2);
+    drop(v);
+
+    tracker.assert_all_once();
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn splice_replacement_panics() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+    let tracker = Tracker::default();
+
+    let mut v = tracker.probes(4);
+    let first = v[0].id;
+    let mut replacement = tracker.probes(3).into_iter();
+    //no size hint, so the tail is moved for every value after the gap is filled
+    let panicking = std::iter::from_fn(move || Some(replacement.next().expect("replacement ran out")));
+
+    panics(&tracker, || drop(v.splice(1..2, panicking)));
+    //the three replacements made it in, the tail is back after them
+    assert_eq!(v.len(), 6);
+    assert_eq!(v[0].id, first);
+    assert_eq!(ids(&v)[4..], [first + 2, first + 3]);
+
+    drop(v);
+    tracker.assert_all_once();
+    checkpoint.assert_no_leaks();
+}
+
+#[test]
+fn closures_panic_in_retain_and_dedup() {
+    let _heap = heap();
+    let checkpoint = HeapCheckpoint::new();
+    let tracker = Tracker::default();
+
+    //the values which weren't checked yet are kept
+    let mut v = tracker.probes(6);
+    let first = v[0].id;
+    panics(&tracker, || v.retain(|p| {
+        assert!(p.id != first + 4);
+        p.id % 2 == 0
+    }));
+    assert_eq!(ids(&v), [first, first + 2, first + 4, first + 5]);
+
+    //a removed value panics while it's dropped
+    v[1].panic_on_drop = true;
+    panics(&tracker, || v.retain(|p| p.id != first + 2));
+    assert_eq!(ids(&v), [first, first + 4, first + 5]);
+
+    panics(&tracker, || v.dedup_by(|a, _| {
+        assert!(a.id != first + 5);
+        true
+    }));
+    assert_eq!(ids(&v), [first, first + 5]);
+
+    drop(v);
+    tracker.assert_all_once();
+    checkpoint.assert_no_leaks();
+}